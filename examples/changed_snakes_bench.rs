@@ -0,0 +1,81 @@
+
+
+
+use clap::Parser;
+use rust_slither::game::world::World;
+use rust_slither::GameConfig;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+
+#[derive(Parser, Debug)]
+#[command(about = "Compares per-tick changed-snake scans against the per-sector index")]
+struct BenchArgs {
+   
+    #[arg(long, default_value = "1000")]
+    bots: u16,
+
+   
+    #[arg(long, default_value = "200")]
+    viewers: usize,
+
+   
+    #[arg(long, default_value = "50")]
+    ticks: u32,
+
+   
+    #[arg(long, default_value = "2000")]
+    view_radius: f32,
+}
+
+fn main() {
+    let args = BenchArgs::parse();
+    let mut world = World::new(GameConfig {
+        initial_bots: args.bots,
+        ..Default::default()
+    });
+    world.init();
+
+    let (mut scan_time, mut index_time) = (Duration::ZERO, Duration::ZERO);
+    let (mut scanned, mut indexed) = (0usize, 0usize);
+
+    for _ in 0..args.ticks {
+        world.tick(world.config.frame_time_ms);
+
+        let mut viewer_ids: Vec<_> = world.snakes().keys().copied().collect();
+        viewer_ids.sort_unstable();
+        let viewers: Vec<_> = viewer_ids
+            .iter()
+            .take(args.viewers)
+            .filter_map(|id| world.get_snake(*id))
+            .map(|snake| snake.head_pos())
+            .collect();
+
+        let started = Instant::now();
+        for &(px, py) in &viewers {
+            for id in world.changed_snakes() {
+                scanned += 1;
+                if let Some(snake) = world.get_snake(*id) {
+                    let (sx, sy) = snake.head_pos();
+                    black_box((sx - px).abs() < args.view_radius && (sy - py).abs() < args.view_radius);
+                }
+            }
+        }
+        scan_time += started.elapsed();
+
+        let started = Instant::now();
+        for &(px, py) in &viewers {
+            if let Some(range) = world.sectors.sector_range_in_viewport(px, py, args.view_radius) {
+                for id in world.changed_snakes_in(range) {
+                    indexed += 1;
+                    black_box(world.get_snake(id));
+                }
+            }
+        }
+        index_time += started.elapsed();
+    }
+
+    println!("{} bots, {} viewers, {} ticks", args.bots, args.viewers, args.ticks);
+    println!("  full scan:    {:>10} snakes examined in {:?}", scanned, scan_time);
+    println!("  sector index: {:>10} snakes examined in {:?}", indexed, index_time);
+}
@@ -0,0 +1,148 @@
+
+
+
+use clap::{Parser, ValueEnum};
+use futures_util::{SinkExt, StreamExt};
+use rand::Rng;
+use rust_slither::protocol::client::{
+    angle_frame, boost_frame, identity_frame, is_init_frame, is_pre_init_frame, start_login_frame,
+};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+
+#[derive(Parser, Debug)]
+#[command(about = "Headless load-test client for the slither.io server")]
+struct LoadTestArgs {
+   
+    #[arg(long, default_value = "ws://127.0.0.1:8080")]
+    url: String,
+
+   
+    #[arg(short, long, default_value = "10")]
+    clients: usize,
+
+   
+    #[arg(short, long, default_value = "30")]
+    duration: u64,
+
+   
+    #[arg(long, default_value = "100")]
+    input_interval_ms: u64,
+
+   
+    #[arg(long, value_enum, default_value = "random")]
+    steering: Steering,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Steering {
+   
+    Random,
+   
+    Circle,
+}
+
+#[derive(Default)]
+struct Stats {
+    connected: AtomicU64,
+    failed: AtomicU64,
+    initialized: AtomicU64,
+    messages: AtomicU64,
+    bytes: AtomicU64,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = LoadTestArgs::parse();
+    let stats = Arc::new(Stats::default());
+    let deadline = Instant::now() + Duration::from_secs(args.duration);
+
+    let mut tasks = Vec::with_capacity(args.clients);
+    for index in 0..args.clients {
+        let url = args.url.clone();
+        let stats = stats.clone();
+        let interval = Duration::from_millis(args.input_interval_ms);
+        let steering = args.steering;
+        tasks.push(tokio::spawn(async move {
+            if let Err(e) = run_client(index, &url, steering, interval, deadline, &stats).await {
+                stats.failed.fetch_add(1, Ordering::Relaxed);
+                eprintln!("client {} failed: {}", index, e);
+            }
+        }));
+    }
+
+    for task in tasks {
+        let _ = task.await;
+    }
+
+    let secs = args.duration.max(1) as f64;
+    let messages = stats.messages.load(Ordering::Relaxed);
+    let bytes = stats.bytes.load(Ordering::Relaxed);
+    println!("clients:      {}", args.clients);
+    println!("connected:    {}", stats.connected.load(Ordering::Relaxed));
+    println!("initialized:  {}", stats.initialized.load(Ordering::Relaxed));
+    println!("failed:       {}", stats.failed.load(Ordering::Relaxed));
+    println!("messages:     {} ({:.1}/s)", messages, messages as f64 / secs);
+    println!("bytes:        {} ({:.1} KiB/s)", bytes, bytes as f64 / secs / 1024.0);
+}
+
+async fn run_client(
+    index: usize,
+    url: &str,
+    steering: Steering,
+    interval: Duration,
+    deadline: Instant,
+    stats: &Stats,
+) -> anyhow::Result<()> {
+    let (ws, _) = connect_async(url).await?;
+    stats.connected.fetch_add(1, Ordering::Relaxed);
+    let (mut sender, mut receiver) = ws.split();
+
+    sender.send(Message::Binary(start_login_frame())).await?;
+
+    let mut ticker = tokio::time::interval(interval);
+    let mut angle: f32 = rand::thread_rng().gen_range(0.0..std::f32::consts::TAU);
+    let mut playing = false;
+
+    loop {
+        if Instant::now() >= deadline {
+            let _ = sender.close().await;
+            return Ok(());
+        }
+
+        tokio::select! {
+            msg = receiver.next() => {
+                let data = match msg {
+                    Some(Ok(Message::Binary(data))) => data,
+                    Some(Ok(Message::Close(_))) | None => anyhow::bail!("server closed the connection"),
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(e.into()),
+                };
+                stats.messages.fetch_add(1, Ordering::Relaxed);
+                stats.bytes.fetch_add(data.len() as u64, Ordering::Relaxed);
+
+                if !playing && is_pre_init_frame(&data, true) {
+                    let name = format!("loadtest{}", index);
+                    sender.send(Message::Binary(identity_frame((index % 40) as u8, &name))).await?;
+                } else if !playing && is_init_frame(&data, true) {
+                    playing = true;
+                    stats.initialized.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            _ = ticker.tick(), if playing => {
+                angle = match steering {
+                    Steering::Random => angle + rand::thread_rng().gen_range(-0.6..0.6),
+                    Steering::Circle => angle + 0.15,
+                };
+                sender.send(Message::Binary(angle_frame(angle))).await?;
+                if rand::thread_rng().gen_bool(0.02) {
+                    let boost = rand::thread_rng().gen_bool(0.5);
+                    sender.send(Message::Binary(boost_frame(boost))).await?;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use clap::Parser;
+use rust_slither::config::snake_consts::{BOT_LOOKAHEAD_CONE, BOT_LOOKAHEAD_DISTANCE};
+use rust_slither::game::math::angle_difference;
+use rust_slither::game::world::World;
+use rust_slither::GameConfig;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+
+#[derive(Parser, Debug)]
+#[command(about = "Compares bot look-ahead against every snake with the per-sector extent index")]
+struct BenchArgs {
+   
+    #[arg(long, default_value = "500")]
+    bots: u16,
+
+   
+    #[arg(long, default_value = "50")]
+    ticks: u32,
+}
+
+
+fn scan_all(world: &World, x: f32, y: f32, angle: f32) -> Option<(u16, f32)> {
+    let mut nearest: Option<(u16, f32)> = None;
+    for snake in world.snakes().values() {
+        if snake.dead {
+            continue;
+        }
+        for part in &snake.body {
+            let (dx, dy) = (part.x - x, part.y - y);
+            let dist = dx.hypot(dy);
+            if dist > BOT_LOOKAHEAD_DISTANCE || nearest.is_some_and(|(_, best)| dist >= best) {
+                continue;
+            }
+            if angle_difference(angle, dy.atan2(dx)).abs() <= BOT_LOOKAHEAD_CONE {
+                nearest = Some((snake.id, dist));
+            }
+        }
+    }
+    nearest
+}
+
+fn main() {
+    let args = BenchArgs::parse();
+    let mut world = World::new(GameConfig {
+        initial_bots: args.bots,
+        max_players: args.bots.max(GameConfig::default().max_players),
+        ..Default::default()
+    });
+    world.init();
+
+    let (mut scan_time, mut index_time) = (Duration::ZERO, Duration::ZERO);
+    let mut lookups = 0usize;
+
+    for _ in 0..args.ticks {
+        world.tick(world.config.frame_time_ms);
+
+        let heads: Vec<_> = world
+            .snakes()
+            .values()
+            .filter(|snake| !snake.dead)
+            .map(|snake| (snake.head_pos(), snake.angle))
+            .collect();
+        lookups += heads.len();
+
+        let started = Instant::now();
+        for &((x, y), angle) in &heads {
+            black_box(scan_all(&world, x, y, angle));
+        }
+        scan_time += started.elapsed();
+
+        let started = Instant::now();
+        for &((x, y), angle) in &heads {
+            black_box(world.obstacle_ahead(x, y, angle, BOT_LOOKAHEAD_DISTANCE, BOT_LOOKAHEAD_CONE));
+        }
+        index_time += started.elapsed();
+    }
+
+    println!("{} bots, {} ticks, {} look-aheads", args.bots, args.ticks, lookups);
+    println!("  full scan:    {:?}", scan_time);
+    println!("  extent index: {:?}", index_time);
+}
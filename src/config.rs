@@ -1,6 +1,12 @@
 
 
-use clap::Parser;
+use crate::protocol::packet::{decode_handshake_secret, HANDSHAKE_SECRET};
+use crate::protocol::types::{CUSTOM_SKIN_COLOR_COUNT, MAX_NICKNAME_CHARS, SKIN_COUNT};
+use clap::{Parser, ValueEnum};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::PathBuf;
 
 
 #[derive(Parser, Debug, Clone)]
@@ -12,10 +18,58 @@ pub struct ServerArgs {
     pub port: u16,
 
    
+    #[arg(long = "extra-port")]
+    pub extra_ports: Vec<u16>,
+
+   
+    #[arg(long = "allowed-origin")]
+    pub allowed_origins: Vec<String>,
+
+   
+    #[arg(long = "announcement", value_name = "INTERVAL_MS:TEXT", value_parser = Announcement::parse)]
+    pub announcements: Vec<Announcement>,
+
+   
+    #[arg(long, value_parser = parse_handshake_secret)]
+    pub handshake_secret: Option<String>,
+
+   
+    #[arg(long)]
+    pub ban_file: Option<PathBuf>,
+
+   
+    #[arg(long)]
+    pub real_ip_header: Option<String>,
+
+   
+    #[arg(long = "trusted-proxy")]
+    pub trusted_proxies: Vec<IpAddr>,
+
+   
+    #[arg(long)]
+    pub admin_token: Option<String>,
+
+   
+    #[arg(long, requires_all = ["tls_cert", "tls_key"])]
+    pub tls_port: Option<u16>,
+
+   
+    #[arg(long)]
+    pub tls_cert: Option<PathBuf>,
+
+   
+    #[arg(long)]
+    pub tls_key: Option<PathBuf>,
+
+   
     #[arg(short, long)]
     pub verbose: bool,
 
    
+    #[arg(long, value_enum, default_value = "compact")]
+    pub log_format: LogFormat,
+
+   
     #[arg(short, long)]
     pub debug: bool,
 
@@ -26,10 +80,305 @@ pub struct ServerArgs {
    
     #[arg(long, default_value = "true")]
     pub bot_respawn: bool,
+
+   
+    #[arg(long)]
+    pub unique_bot_names: bool,
+
+   
+    #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
+    pub player_respawn: bool,
+
+   
+    #[arg(long, default_value = "500")]
+    pub max_players: u16,
+
+   
+    #[arg(long)]
+    pub leaderboard_path: Option<PathBuf>,
+
+   
+    #[arg(long)]
+    pub skins_path: Option<PathBuf>,
+
+   
+    #[arg(long)]
+    pub minimap_food: bool,
+
+   
+    #[arg(long, value_enum, default_value = "kill")]
+    pub boundary: BoundaryBehavior,
+
+   
+    #[arg(long, default_value = "0")]
+    pub boundary_warning: f32,
+
+   
+    #[arg(long, value_enum, default_value = "both-die")]
+    pub head_on: HeadOnRule,
+
+   
+    #[arg(long, default_value = "0")]
+    pub min_food_spacing: f32,
+
+   
+    #[arg(long, value_enum, default_value = "skip")]
+    pub tick_catch_up: TickCatchUp,
+
+   
+    #[arg(long, value_name = "VERSION")]
+    pub absolute_rotation_from: Option<u8>,
+
+   
+    #[arg(long, default_value = "0")]
+    pub kill_mass_transfer: f32,
+
+   
+    #[arg(long)]
+    pub kill_transfer_replaces_drop: bool,
+
+   
+    #[arg(long, default_value = "0")]
+    pub input_buffer_ms: u64,
+
+   
+    #[arg(long, default_value = "10000")]
+    pub write_timeout_ms: u64,
+
+   
+    #[arg(long, default_value = "24")]
+    pub max_name_chars: usize,
+
+   
+    #[arg(long, default_value = "0")]
+    pub sector_enter_padding: f32,
+
+   
+    #[arg(long, default_value = "0")]
+    pub sector_leave_padding: f32,
+
+   
+    #[arg(long)]
+    pub verify_checksum: bool,
+
+   
+    #[arg(long)]
+    pub debug_overlay: bool,
+
+   
+    #[arg(long)]
+    pub debug_text: bool,
+}
+
+impl ServerArgs {
+   
+    pub fn listeners(&self) -> Vec<ListenerConfig> {
+        let mut listeners: Vec<_> = std::iter::once(self.port)
+            .chain(self.extra_ports.iter().copied())
+            .map(ListenerConfig::plain)
+            .collect();
+
+        if let (Some(port), Some(cert_path), Some(key_path)) =
+            (self.tls_port, self.tls_cert.clone(), self.tls_key.clone())
+        {
+            listeners.push(ListenerConfig {
+                port,
+                tls: Some(TlsConfig { cert_path, key_path }),
+            });
+        }
+
+        listeners
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListenerConfig {
+   
+    pub port: u16,
+   
+    pub tls: Option<TlsConfig>,
+}
+
+impl ListenerConfig {
+   
+    pub fn plain(port: u16) -> Self {
+        Self { port, tls: None }
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+}
+
+
+pub fn parse_handshake_secret(secret: &str) -> Result<String, String> {
+    if decode_handshake_secret(secret.as_bytes()).is_none() {
+        return Err(format!("handshake secret {:?} is too short to derive a login checksum", secret));
+    }
+    Ok(secret.to_string())
 }
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Announcement {
+    pub interval_ms: u64,
+    pub text: String,
+}
+
+impl Announcement {
+   
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (interval, text) = spec
+            .split_once(':')
+            .ok_or_else(|| format!("announcement {:?} must look like INTERVAL_MS:TEXT", spec))?;
+        let interval_ms = interval
+            .trim()
+            .parse()
+            .map_err(|_| format!("announcement interval {:?} is not a number of milliseconds", interval))?;
+        Ok(Self {
+            interval_ms,
+            text: text.to_string(),
+        })
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkinInfo {
+    pub name: String,
+    pub color: u8,
+    pub locked: bool,
+}
+
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SkinRegistry {
+    skins: HashMap<u8, SkinInfo>,
+   
+    pub fallback: u8,
+}
+
+impl SkinRegistry {
+   
+    pub fn insert(&mut self, index: u8, info: SkinInfo) {
+        self.skins.insert(index, info);
+    }
+
+   
+    pub fn get(&self, index: u8) -> Option<&SkinInfo> {
+        self.skins.get(&index)
+    }
+
+   
+    pub fn is_locked(&self, index: u8) -> bool {
+        self.get(index).is_some_and(|skin| skin.locked)
+    }
+
+   
+    pub fn resolve(&self, index: u8) -> u8 {
+        if self.is_locked(index) {
+            self.fallback
+        } else {
+            index
+        }
+    }
+
+   
+    pub fn from_json(text: &str) -> Result<Self, String> {
+        let value: Value = serde_json::from_str(text).map_err(|e| format!("invalid skin registry: {}", e))?;
+        let fallback = value.get("fallback").and_then(Value::as_u64).unwrap_or(0);
+        if fallback >= SKIN_COUNT as u64 {
+            return Err(format!("fallback skin {} is out of range, skins must be below {}", fallback, SKIN_COUNT));
+        }
+        let mut registry = Self {
+            fallback: fallback as u8,
+            ..Default::default()
+        };
+
+        let skins = value.get("skins").and_then(Value::as_array).ok_or("skin registry needs a \"skins\" array")?;
+        for (i, skin) in skins.iter().enumerate() {
+            let field = |name: &str| skin.get(name).and_then(Value::as_u64);
+            let index = field("index").and_then(|v| u8::try_from(v).ok());
+            let Some(index) = index else {
+                return Err(format!("skin entry {} has no valid index", i));
+            };
+            let color = field("color").unwrap_or(0);
+            if color >= CUSTOM_SKIN_COLOR_COUNT as u64 {
+                return Err(format!(
+                    "skin {} color {} is out of range, colors must be below {}",
+                    index, color, CUSTOM_SKIN_COLOR_COUNT
+                ));
+            }
+            registry.insert(index, SkinInfo {
+                name: skin.get("name").and_then(Value::as_str).unwrap_or_default().to_string(),
+                color: color as u8,
+                locked: skin.get("locked").and_then(Value::as_bool).unwrap_or(false),
+            });
+        }
+
+        if registry.is_locked(registry.fallback) {
+            return Err(format!("fallback skin {} is locked", registry.fallback));
+        }
+        Ok(registry)
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum LogFormat {
+    #[default]
+    Compact,
+    Json,
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum BoundaryBehavior {
+   
+    #[default]
+    Kill,
+   
+    Bounce,
+   
+    Clamp,
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum TickCatchUp {
+   
+    Burst,
+   
+    Delay,
+   
+    #[default]
+    Skip,
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum HeadOnRule {
+   
+    #[default]
+    BothDie,
+   
+    LongerSurvives,
+}
+
+
+// Sector coordinates are a single byte on the wire (AddSector/RemoveSector/SetFood)
+// and in `SectorGrid`, so the grid can never be wider than this.
+pub const MAX_SECTOR_COUNT_ALONG_EDGE: u16 = u8::MAX as u16;
+
+
+pub const FOOD_COLOR_COUNT: u8 = 28;
+
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct GameConfig {
    
     pub game_radius: u32,
@@ -42,30 +391,147 @@ pub struct GameConfig {
    
     pub protocol_version: u8,
    
+    pub max_protocol_version: u8,
+   
+    pub absolute_rotation_from: Option<u8>,
+   
     pub frame_time_ms: u64,
    
+    pub movement_substep_ms: u64,
+   
+    pub tick_catch_up: TickCatchUp,
+   
     pub death_radius: u32,
    
+    pub boundary_behavior: BoundaryBehavior,
+   
+    pub boundary_warning_distance: f32,
+   
+    pub head_on_rule: HeadOnRule,
+   
+    pub kill_mass_transfer: f32,
+    pub kill_transfer_replaces_drop: bool,
+   
     pub move_step_distance: u16,
 
    
     pub initial_bots: u16,
     pub bot_respawn: bool,
+   
+    pub unique_bot_names: bool,
+   
+    pub player_respawn: bool,
+
+   
+    pub max_players: u16,
+
+   
+    pub spectator_limit: usize,
+   
+    pub spectator_idle_ms: u64,
 
    
     pub food_spawn_rate: u16,
+   
+    pub min_food_spacing: f32,
+    pub food_per_edge_sector: u16,
+    pub food_value_multiplier: u16,
+    pub eat_radius_multiplier: f32,
+
+   
+   
+    pub length_soft_cap_start: usize,
+    pub length_soft_cap: usize,
+    pub length_soft_cap_exponent: f32,
+    pub food_colors: Vec<u8>,
+    pub food_min_size: u8,
+    pub food_max_size: u8,
     pub spawn_prob_near_snake: u16,
     pub spawn_prob_on_snake: u16,
     pub spawn_prob_random: u16,
 
    
-    pub human_snake_start_score: u16,
-    pub bot_snake_start_score: u16,
+    pub human_start_parts: u16,
+    pub human_start_fullness: u32,
+    pub bot_start_parts: u16,
+    pub bot_start_fullness: u32,
     pub snake_min_length: u16,
 
    
     pub boost_cost: u16,
     pub boost_drop_size: u8,
+
+   
+    pub handshake_timeout_ms: u64,
+
+   
+    pub resync_interval_ms: u64,
+
+   
+    pub input_buffer_ms: u64,
+
+   
+   
+    pub write_timeout_ms: u64,
+
+   
+    pub victory_messages: bool,
+
+   
+    pub max_custom_skin_len: usize,
+
+   
+    pub max_name_chars: usize,
+
+   
+    pub verify_checksum: bool,
+
+   
+    pub minimap_food_hotspots: bool,
+    pub minimap_food_threshold: u16,
+
+   
+    pub leaderboard_path: Option<PathBuf>,
+    pub hall_of_fame_size: usize,
+
+   
+    pub rng_seed: u64,
+
+   
+    pub debug_overlay: bool,
+
+   
+    pub debug_text: bool,
+
+   
+    pub allowed_origins: Vec<String>,
+
+   
+    pub announcements: Vec<Announcement>,
+
+   
+    pub handshake_secret: Option<Vec<u8>>,
+
+   
+    pub ban_file: Option<PathBuf>,
+   
+    pub real_ip_header: Option<String>,
+    pub trusted_proxies: Vec<IpAddr>,
+
+   
+    pub admin_token: Option<String>,
+
+   
+    pub far_snake_detail: usize,
+    pub far_snake_distance: f32,
+
+   
+   
+    pub sector_enter_padding: f32,
+    pub sector_leave_padding: f32,
+
+   
+    pub skin_registry: SkinRegistry,
 }
 
 impl Default for GameConfig {
@@ -76,45 +542,406 @@ impl Default for GameConfig {
             sector_size: 480,
             sector_count_along_edge: 90,
             protocol_version: 14,
+            max_protocol_version: 31,
+            absolute_rotation_from: None,
             frame_time_ms: 8,
+            movement_substep_ms: 8,
+            tick_catch_up: TickCatchUp::Skip,
             death_radius: 21120,
+            boundary_behavior: BoundaryBehavior::Kill,
+            boundary_warning_distance: 0.0,
+            head_on_rule: HeadOnRule::BothDie,
+            kill_mass_transfer: 0.0,
+            kill_transfer_replaces_drop: false,
             move_step_distance: 42,
 
             initial_bots: 0,
             bot_respawn: true,
+            unique_bot_names: false,
+            player_respawn: true,
+
+            max_players: 500,
+
+            spectator_limit: 50,
+            spectator_idle_ms: 10_000,
 
             food_spawn_rate: 2,
+            min_food_spacing: 0.0,
+            food_per_edge_sector: 50,
+            food_value_multiplier: 2,
+            eat_radius_multiplier: snake_consts::EAT_RADIUS_MULTIPLIER,
+            length_soft_cap_start: 0,
+            length_soft_cap: 0,
+            length_soft_cap_exponent: 2.0,
+            food_colors: (0..FOOD_COLOR_COUNT).collect(),
+            food_min_size: 5,
+            food_max_size: 15,
             spawn_prob_near_snake: 25,
             spawn_prob_on_snake: 25,
             spawn_prob_random: 50,
 
-            human_snake_start_score: 5,
-            bot_snake_start_score: 5,
+            human_start_parts: 10,
+            human_start_fullness: 0,
+            bot_start_parts: 10,
+            bot_start_fullness: 0,
             snake_min_length: 2,
 
             boost_cost: 20,
             boost_drop_size: 10,
+
+            handshake_timeout_ms: 10_000,
+
+            resync_interval_ms: 0,
+
+            input_buffer_ms: 0,
+
+            write_timeout_ms: 10_000,
+
+            victory_messages: true,
+
+            max_custom_skin_len: u8::MAX as usize,
+            max_name_chars: MAX_NICKNAME_CHARS,
+
+            verify_checksum: false,
+
+            minimap_food_hotspots: false,
+            minimap_food_threshold: 20,
+
+            leaderboard_path: None,
+            hall_of_fame_size: 10,
+
+            rng_seed: 12345,
+
+            debug_overlay: false,
+            debug_text: false,
+
+            allowed_origins: Vec::new(),
+
+            announcements: Vec::new(),
+
+            handshake_secret: None,
+
+            ban_file: None,
+            real_ip_header: None,
+            trusted_proxies: Vec::new(),
+
+            admin_token: None,
+
+            far_snake_detail: 1,
+            far_snake_distance: 1000.0,
+
+            sector_enter_padding: 0.0,
+            sector_leave_padding: 0.0,
+
+            skin_registry: SkinRegistry::default(),
         }
     }
 }
 
 impl GameConfig {
    
+    pub fn builder() -> GameConfigBuilder {
+        GameConfigBuilder::new()
+    }
+
+   
+    pub fn negotiate_protocol_version(&self, requested: u8) -> u8 {
+        if requested == 0 {
+            self.protocol_version
+        } else {
+            requested.clamp(self.protocol_version, self.max_protocol_version.max(self.protocol_version))
+        }
+    }
+
+   
+    pub fn playable_radius(&self, margin: f32) -> f32 {
+        (self.death_radius as f32 - margin).max(0.0)
+    }
+
+   
+    pub fn food_target(&self) -> usize {
+        self.sector_count_along_edge as usize * self.food_per_edge_sector as usize
+    }
+
+   
+    pub fn preinit_secret(&self) -> &[u8] {
+        self.handshake_secret.as_deref().unwrap_or(HANDSHAKE_SECRET)
+    }
+
+   
+    pub fn sector_view_radii(&self, view_radius: f32) -> (f32, f32) {
+        (view_radius + self.sector_enter_padding, view_radius + self.sector_leave_padding)
+    }
+
+   
+    pub fn growth_factor(&self, parts: usize) -> f32 {
+        if self.length_soft_cap_start == 0 || parts <= self.length_soft_cap_start {
+            return 1.0;
+        }
+        let span = (self.length_soft_cap - self.length_soft_cap_start) as f32;
+        let progress = ((parts - self.length_soft_cap_start) as f32 / span).min(1.0);
+        (1.0 - progress).powf(self.length_soft_cap_exponent)
+    }
+
+   
+    pub fn validate(&self) -> Result<(), String> {
+        if self.frame_time_ms == 0 {
+            return Err("frame_time_ms must be greater than 0".to_string());
+        }
+
+        if self.sector_size == 0 {
+            return Err("sector_size must be greater than 0".to_string());
+        }
+
+        if self.sector_count_along_edge == 0 {
+            return Err("sector_count_along_edge must be greater than 0".to_string());
+        }
+
+        if self.sector_count_along_edge > MAX_SECTOR_COUNT_ALONG_EDGE {
+            return Err(format!(
+                "sector_count_along_edge is {} but at most {} sectors per edge are supported",
+                self.sector_count_along_edge, MAX_SECTOR_COUNT_ALONG_EDGE
+            ));
+        }
+
+        if self.far_snake_detail == 0 {
+            return Err("far_snake_detail must be at least 1".to_string());
+        }
+
+        if !(self.sector_enter_padding >= 0.0 && self.sector_leave_padding >= self.sector_enter_padding) {
+            return Err(format!(
+                "sector paddings must satisfy 0 <= sector_enter_padding <= sector_leave_padding, got {} and {}",
+                self.sector_enter_padding, self.sector_leave_padding
+            ));
+        }
+
+        if !self.boundary_warning_distance.is_finite() || self.boundary_warning_distance < 0.0 {
+            return Err(format!(
+                "boundary_warning_distance must be a non-negative number, got {}",
+                self.boundary_warning_distance
+            ));
+        }
+
+        if !self.min_food_spacing.is_finite() || self.min_food_spacing < 0.0 {
+            return Err(format!(
+                "min_food_spacing must be a non-negative number, got {}",
+                self.min_food_spacing
+            ));
+        }
+
+        if decode_handshake_secret(self.preinit_secret()).is_none() {
+            return Err("handshake_secret is too short to derive a login checksum".to_string());
+        }
+
+        if let Some(announcement) = self.announcements.iter().find(|a| a.interval_ms == 0) {
+            return Err(format!("announcement {:?} needs a non-zero interval", announcement.text));
+        }
+
+        if !(0.0..=1.0).contains(&self.kill_mass_transfer) {
+            return Err(format!(
+                "kill_mass_transfer must be between 0 and 1, got {}",
+                self.kill_mass_transfer
+            ));
+        }
+
+        if self.eat_radius_multiplier.is_nan() || self.eat_radius_multiplier <= 0.0 {
+            return Err(format!(
+                "eat_radius_multiplier must be positive, got {}",
+                self.eat_radius_multiplier
+            ));
+        }
+
+        let exponent_ok = self.length_soft_cap_exponent > 0.0;
+        if self.length_soft_cap_start > 0 && (self.length_soft_cap <= self.length_soft_cap_start || !exponent_ok) {
+            return Err(format!(
+                "length soft cap needs length_soft_cap > length_soft_cap_start and a positive exponent, got {}..{} ^ {}",
+                self.length_soft_cap_start, self.length_soft_cap, self.length_soft_cap_exponent
+            ));
+        }
+
+        if self.real_ip_header.is_some() && self.trusted_proxies.is_empty() {
+            return Err("real_ip_header is only honoured from trusted proxies, but none are configured".to_string());
+        }
+
+        if self.admin_token.as_deref().is_some_and(str::is_empty) {
+            return Err("admin_token must not be empty".to_string());
+        }
+
+        if self.food_colors.is_empty() {
+            return Err("food_colors must list at least one color".to_string());
+        }
+
+        if let Some(color) = self.food_colors.iter().find(|&&c| c >= FOOD_COLOR_COUNT) {
+            return Err(format!(
+                "food color {} is out of range, colors must be below {}",
+                color, FOOD_COLOR_COUNT
+            ));
+        }
+
+        if self.food_min_size == 0 || self.food_min_size > self.food_max_size {
+            return Err(format!(
+                "food sizes must satisfy 0 < food_min_size <= food_max_size, got {}..{}",
+                self.food_min_size, self.food_max_size
+            ));
+        }
+
+        if self.max_custom_skin_len > u8::MAX as usize {
+            return Err(format!(
+                "max_custom_skin_len is {} but skins longer than {} bytes cannot be sent",
+                self.max_custom_skin_len,
+                u8::MAX
+            ));
+        }
+
+        if self.max_name_chars == 0 || self.max_name_chars > MAX_NICKNAME_CHARS {
+            return Err(format!(
+                "max_name_chars must be between 1 and {}, got {}",
+                MAX_NICKNAME_CHARS, self.max_name_chars
+            ));
+        }
+
+        let grid_extent = self.sector_count_along_edge as u32 * self.sector_size as u32;
+        let world_extent = self.game_radius * 2;
+        if grid_extent < world_extent {
+            return Err(format!(
+                "sector grid covers {} units ({} sectors of {}) but the world is {} units across; \
+                 increase sector_count_along_edge to at least {}",
+                grid_extent,
+                self.sector_count_along_edge,
+                self.sector_size,
+                world_extent,
+                world_extent.div_ceil(self.sector_size as u32)
+            ));
+        }
+
+        Ok(())
+    }
+
+   
     pub fn sector_diag_size(&self) -> u16 {
        
         680
     }
 
    
+    pub fn sector_grid_size(&self) -> Result<u8, String> {
+        u8::try_from(self.sector_count_along_edge).map_err(|_| {
+            format!(
+                "sector_count_along_edge {} exceeds the maximum of {}",
+                self.sector_count_along_edge, MAX_SECTOR_COUNT_ALONG_EDGE
+            )
+        })
+    }
+
+   
     pub fn total_sectors(&self) -> u32 {
         (self.sector_count_along_edge as u32).pow(2)
     }
+}
+
+
+macro_rules! builder_setters {
+    ($($field:ident: $ty:ty),* $(,)?) => {
+        $(
+            pub fn $field(mut self, $field: $ty) -> Self {
+                self.config.$field = $field;
+                self
+            }
+        )*
+    };
+}
+
+
+#[derive(Debug, Clone, Default)]
+pub struct GameConfigBuilder {
+    config: GameConfig,
+}
+
+impl GameConfigBuilder {
+   
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    builder_setters! {
+        game_radius: u32,
+        max_snake_parts: u16,
+        sector_size: u16,
+        sector_count_along_edge: u16,
+        protocol_version: u8,
+        max_protocol_version: u8,
+        absolute_rotation_from: Option<u8>,
+        frame_time_ms: u64,
+        movement_substep_ms: u64,
+        tick_catch_up: TickCatchUp,
+        death_radius: u32,
+        boundary_behavior: BoundaryBehavior,
+        boundary_warning_distance: f32,
+        head_on_rule: HeadOnRule,
+        kill_mass_transfer: f32,
+        kill_transfer_replaces_drop: bool,
+        move_step_distance: u16,
+        initial_bots: u16,
+        bot_respawn: bool,
+        unique_bot_names: bool,
+        player_respawn: bool,
+        max_players: u16,
+        spectator_limit: usize,
+        spectator_idle_ms: u64,
+        food_spawn_rate: u16,
+        min_food_spacing: f32,
+        food_per_edge_sector: u16,
+        food_value_multiplier: u16,
+        eat_radius_multiplier: f32,
+        length_soft_cap_start: usize,
+        length_soft_cap: usize,
+        length_soft_cap_exponent: f32,
+        food_colors: Vec<u8>,
+        food_min_size: u8,
+        food_max_size: u8,
+        spawn_prob_near_snake: u16,
+        spawn_prob_on_snake: u16,
+        spawn_prob_random: u16,
+        human_start_parts: u16,
+        human_start_fullness: u32,
+        bot_start_parts: u16,
+        bot_start_fullness: u32,
+        snake_min_length: u16,
+        boost_cost: u16,
+        boost_drop_size: u8,
+        handshake_timeout_ms: u64,
+        resync_interval_ms: u64,
+        input_buffer_ms: u64,
+        write_timeout_ms: u64,
+        victory_messages: bool,
+        max_custom_skin_len: usize,
+        max_name_chars: usize,
+        verify_checksum: bool,
+        minimap_food_hotspots: bool,
+        minimap_food_threshold: u16,
+        leaderboard_path: Option<PathBuf>,
+        hall_of_fame_size: usize,
+        rng_seed: u64,
+        debug_overlay: bool,
+        debug_text: bool,
+        allowed_origins: Vec<String>,
+        announcements: Vec<Announcement>,
+        handshake_secret: Option<Vec<u8>>,
+        ban_file: Option<PathBuf>,
+        real_ip_header: Option<String>,
+        trusted_proxies: Vec<IpAddr>,
+        admin_token: Option<String>,
+        far_snake_detail: usize,
+        far_snake_distance: f32,
+        sector_enter_padding: f32,
+        sector_leave_padding: f32,
+        skin_registry: SkinRegistry,
+    }
 
    
-    pub fn world_to_sector(&self, x: f32, y: f32) -> (u8, u8) {
-        let sector_x = ((x + self.game_radius as f32) / self.sector_size as f32) as u8;
-        let sector_y = ((y + self.game_radius as f32) / self.sector_size as f32) as u8;
-        (sector_x, sector_y)
+    pub fn build(self) -> Result<GameConfig, String> {
+        self.config.validate()?;
+        Ok(self.config)
     }
 }
 
@@ -129,6 +956,8 @@ pub mod snake_consts {
    
     pub const ANGULAR_SPEED: f32 = 4.125;
    
+    pub const TURN_FACTOR_MIN: f32 = 0.13;
+   
     pub const TAIL_K: f32 = 0.43;
    
     pub const TAIL_STEP_DISTANCE: f32 = 24.0;
@@ -141,9 +970,58 @@ pub mod snake_consts {
    
     pub const AI_STEP_INTERVAL_MS: u64 = 250;
    
+    pub const BOT_TURN_CHANGE_CHANCE: f32 = 0.3;
+   
+    pub const BOT_TURN_DECAY: f32 = 0.5;
+   
+    pub const BOT_LOOKAHEAD_DISTANCE: f32 = 200.0;
+   
+    pub const BOT_LOOKAHEAD_CONE: f32 = 0.5;
+   
     pub const BOOST_COST: u32 = 20;
    
     pub const BOOST_DROP_SIZE: u8 = 10;
+   
+    pub const MAX_SCALE: f32 = 6.0;
+   
+    pub const PARTS_PER_SCALE: f32 = 106.0;
+   
+    pub const BODY_WIDTH: f32 = 29.0;
+   
+    pub const EAT_RADIUS_MULTIPLIER: f32 = 1.7;
+   
+    pub const FULLNESS_PER_PART: u32 = 100;
+
+   
+    pub const MAX_FULLNESS: u32 = 0xFF_FFFF;
+   
+    pub const DEATH_DROP_RATIO: f32 = 0.5;
+   
+    pub const DEATH_FOOD_MIN_SIZE: u8 = 10;
+   
+    pub const DEATH_FOOD_MAX_SIZE: u8 = 60;
+}
+
+
+pub mod world_consts {
+   
+    pub const SPAWN_EDGE_MARGIN: f32 = 3840.0;
+   
+    pub const FOOD_EDGE_MARGIN: f32 = 200.0;
+   
+    pub const FOOD_SPACING_ATTEMPTS: usize = 4;
+   
+    pub const SPAWN_CLEARANCE: f32 = 100.0;
+   
+    pub const MIN_SPAWN_CLEARANCE: f32 = 50.0;
+   
+    pub const SPAWN_ATTEMPTS_PER_TIER: usize = 100;
+   
+    pub const DEATH_ANIMATION_TICKS: u64 = 125;
+   
+    pub const FEED_RADIUS: f32 = 60.0;
+   
+    pub const MAX_FEED_COUNT: u16 = 500;
 }
 
 
@@ -156,4 +1034,181 @@ pub mod timing {
     pub const PING_TIMEOUT_MS: u64 = 30000;
    
     pub const SESSION_CLEANUP_INTERVAL_MS: u64 = 5000;
+   
+    pub const LOOP_STALL_THRESHOLD_MS: u64 = 2000;
+   
+    pub const MAX_CATCH_UP_TICKS: u32 = 4;
+   
+    pub const PARSE_ERROR_LOG_INTERVAL_MS: u64 = 5000;
+   
+    pub const REQUEST_HEAD_TIMEOUT_MS: u64 = 5000;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_protocol_negotiation_clamps_to_supported_range() {
+        let config = GameConfig::default();
+        assert_eq!(config.negotiate_protocol_version(0), 14);
+        assert_eq!(config.negotiate_protocol_version(25), 25);
+        assert_eq!(config.negotiate_protocol_version(40), 31);
+        assert_eq!(config.negotiate_protocol_version(9), 14);
+    }
+
+    #[test]
+    fn test_growth_factor_decays_toward_the_soft_cap() {
+        let config = GameConfig {
+            length_soft_cap_start: 100,
+            length_soft_cap: 200,
+            length_soft_cap_exponent: 2.0,
+            ..Default::default()
+        };
+        assert_eq!(config.growth_factor(50), 1.0);
+        assert_eq!(config.growth_factor(100), 1.0);
+        assert!((config.growth_factor(150) - 0.25).abs() < 1e-6);
+        assert_eq!(config.growth_factor(250), 0.0);
+        assert_eq!(GameConfig::default().growth_factor(10_000), 1.0);
+        assert!(GameConfig { length_soft_cap: 50, ..config }.validate().is_err());
+    }
+
+    #[test]
+    fn test_player_respawn_flag_can_be_turned_off() {
+        let parse = |args: &[&str]| ServerArgs::try_parse_from(std::iter::once("rust_slither").chain(args.iter().copied()));
+        assert!(parse(&[]).unwrap().player_respawn);
+        assert!(parse(&["--player-respawn", "true"]).unwrap().player_respawn);
+        assert!(!parse(&["--player-respawn", "false"]).unwrap().player_respawn);
+        assert!(!parse(&["--player-respawn=false"]).unwrap().player_respawn);
+        assert!(parse(&["--player-respawn", "sometimes"]).is_err());
+    }
+
+    #[test]
+    fn test_preinit_secret_falls_back_to_the_builtin() {
+        assert_eq!(GameConfig::default().preinit_secret(), HANDSHAKE_SECRET);
+
+        let custom = String::from_utf8(HANDSHAKE_SECRET.iter().rev().copied().collect()).unwrap();
+        let config = GameConfig {
+            handshake_secret: Some(parse_handshake_secret(&custom).unwrap().into_bytes()),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+        assert_eq!(config.preinit_secret(), custom.as_bytes());
+
+        assert!(parse_handshake_secret("short").is_err());
+        let config = GameConfig {
+            handshake_secret: Some(b"short".to_vec()),
+            ..Default::default()
+        };
+        assert!(config.validate().unwrap_err().contains("too short"));
+    }
+
+    #[test]
+    fn test_announcement_parse() {
+        assert_eq!(
+            Announcement::parse("60000:Restart in 5: minutes").unwrap(),
+            Announcement {
+                interval_ms: 60_000,
+                text: "Restart in 5: minutes".to_string(),
+            }
+        );
+        assert!(Announcement::parse("soon:Restart").is_err());
+        assert!(Announcement::parse("Restart").is_err());
+    }
+
+    #[test]
+    fn test_skin_registry_from_json() {
+        let registry = SkinRegistry::from_json(
+            r#"{"fallback": 1, "skins": [{"index": 5, "name": "Neon", "color": 3, "locked": true}, {"index": 6}]}"#,
+        )
+        .unwrap();
+        assert_eq!(registry.get(5).map(|s| s.name.as_str()), Some("Neon"));
+        assert_eq!((registry.resolve(5), registry.resolve(6), registry.resolve(40)), (1, 6, 40));
+
+        assert!(SkinRegistry::from_json(r#"{"skins": [{"index": 300}]}"#).is_err());
+        assert!(SkinRegistry::from_json(r#"{"skins": [{"index": 5, "color": 259}]}"#).is_err());
+        assert!(SkinRegistry::from_json(r#"{"skins": [{"index": 5, "color": 40}]}"#).is_err());
+        assert!(SkinRegistry::from_json(r#"{"fallback": 257, "skins": []}"#).is_err());
+        assert!(SkinRegistry::from_json(r#"{"fallback": 66, "skins": []}"#).is_err());
+        assert!(SkinRegistry::from_json(r#"{"fallback": 5, "skins": [{"index": 5, "locked": true}]}"#).is_err());
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        assert!(GameConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_oversized_sector_count() {
+        let config = GameConfig {
+            sector_count_along_edge: 300,
+            ..Default::default()
+        };
+        let err = config.validate().unwrap_err();
+        assert!(err.contains("sector_count_along_edge"));
+    }
+
+    #[test]
+    fn test_sector_grid_size_does_not_wrap() {
+        let config = GameConfig {
+            sector_count_along_edge: 256,
+            game_radius: 100,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+        assert!(config.sector_grid_size().is_err());
+
+        let config = GameConfig {
+            sector_count_along_edge: MAX_SECTOR_COUNT_ALONG_EDGE,
+            ..Default::default()
+        };
+        assert_eq!(config.sector_grid_size(), Ok(255));
+    }
+
+    #[test]
+    fn test_validate_rejects_grid_not_covering_world() {
+        let config = GameConfig {
+            sector_count_along_edge: 40,
+            ..Default::default()
+        };
+        let err = config.validate().unwrap_err();
+        assert!(err.contains("at least 90"));
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_frame_time() {
+        let config = GameConfig {
+            frame_time_ms: 0,
+            ..Default::default()
+        };
+        let err = config.validate().unwrap_err();
+        assert!(err.contains("frame_time_ms"));
+    }
+
+    #[test]
+    fn test_builder_matches_manual_assignment() {
+        let mut manual = GameConfig::default();
+        manual.game_radius = 16000;
+        manual.death_radius = 15500;
+        manual.initial_bots = 12;
+        manual.bot_respawn = false;
+        manual.rng_seed = 7;
+
+        let built = GameConfig::builder()
+            .game_radius(16000)
+            .death_radius(15500)
+            .initial_bots(12)
+            .bot_respawn(false)
+            .rng_seed(7)
+            .build()
+            .unwrap();
+
+        assert_eq!(built, manual);
+    }
+
+    #[test]
+    fn test_builder_validates() {
+        let err = GameConfig::builder().frame_time_ms(0).build().unwrap_err();
+        assert!(err.contains("frame_time_ms"));
+    }
 }
@@ -1,9 +1,51 @@
 
 
+use crate::config::GameConfig;
 use crate::protocol::outgoing::FoodData;
 
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoodPalette {
+    pub colors: Vec<u8>,
+    pub min_size: u8,
+    pub max_size: u8,
+}
+
+impl FoodPalette {
+   
+    pub fn from_config(config: &GameConfig) -> Self {
+        Self {
+            colors: config.food_colors.clone(),
+            min_size: config.food_min_size,
+            max_size: config.food_max_size,
+        }
+    }
+
+   
+    pub fn color(&self, rng: &mut impl FnMut() -> f32) -> u8 {
+        let roll = rng();
+        if self.colors.is_empty() {
+            return 0;
+        }
+        let index = ((roll * self.colors.len() as f32) as usize).min(self.colors.len() - 1);
+        self.colors[index]
+    }
+
+   
+    pub fn size(&self, rng: &mut impl FnMut() -> f32) -> u8 {
+        let span = self.max_size.saturating_sub(self.min_size) as f32;
+        self.min_size.saturating_add((rng() * span) as u8).min(self.max_size)
+    }
+}
+
+impl Default for FoodPalette {
+    fn default() -> Self {
+        Self::from_config(&GameConfig::default())
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Food {
     pub x: u16,
     pub y: u16,
@@ -18,30 +60,35 @@ impl Food {
     }
 
    
-    pub fn random(game_radius: u32, rng: &mut impl FnMut() -> f32) -> Self {
-       
-        let angle = rng() * std::f32::consts::PI * 2.0;
-        let r = rng().sqrt() * (game_radius as f32 * 0.95);
-
-        let x = (game_radius as f32 + r * angle.cos()) as u16;
-        let y = (game_radius as f32 + r * angle.sin()) as u16;
-
-        let size = (rng() * 10.0) as u8 + 5;
-        let color = (rng() * 28.0) as u8;
+    pub fn random_at(x: u16, y: u16, palette: &FoodPalette, rng: &mut impl FnMut() -> f32) -> Self {
+        let size = palette.size(rng);
+        let color = palette.color(rng);
 
         Self { x, y, size, color }
     }
 
    
-    pub fn near(x: u16, y: u16, offset: f32, rng: &mut impl FnMut() -> f32) -> Self {
+    pub fn near(x: u16, y: u16, offset: f32, palette: &FoodPalette, rng: &mut impl FnMut() -> f32) -> Self {
+        let size = (rng() * 15.0) as u8 + 10;
+        Self::near_with_size(x, y, offset, size, palette, rng)
+    }
+
+   
+    pub fn near_with_size(
+        x: u16,
+        y: u16,
+        offset: f32,
+        size: u8,
+        palette: &FoodPalette,
+        rng: &mut impl FnMut() -> f32,
+    ) -> Self {
         let angle = rng() * std::f32::consts::PI * 2.0;
         let r = rng() * offset;
 
         let new_x = (x as f32 + r * angle.cos()) as u16;
         let new_y = (y as f32 + r * angle.sin()) as u16;
 
-        let size = (rng() * 15.0) as u8 + 10;
-        let color = (rng() * 28.0) as u8;
+        let color = palette.color(rng);
 
         Self {
             x: new_x,
@@ -52,8 +99,8 @@ impl Food {
     }
 
    
-    pub fn value(&self) -> u16 {
-        self.size as u16 * 2
+    pub fn value(&self, multiplier: u16) -> u16 {
+        (self.size as u16).saturating_mul(multiplier)
     }
 
    
@@ -65,13 +112,6 @@ impl Food {
             color: self.color,
         }
     }
-
-   
-    pub fn sector_coords(&self, sector_size: u16) -> (u8, u8) {
-        let sx = (self.x / sector_size) as u8;
-        let sy = (self.y / sector_size) as u8;
-        (sx, sy)
-    }
 }
 
 impl From<Food> for FoodData {
@@ -96,6 +136,8 @@ pub struct FoodSpawned {
 }
 
 
+// Foods are kept sorted by x so position queries only scan the slice whose x
+// lies within the query radius instead of the whole sector.
 #[derive(Debug, Clone, Default)]
 pub struct FoodCollection {
     foods: Vec<Food>,
@@ -112,7 +154,8 @@ impl FoodCollection {
 
     pub fn add(&mut self, food: Food) -> bool {
         if self.foods.len() < self.max_capacity {
-            self.foods.push(food);
+            let idx = self.foods.partition_point(|f| f.x <= food.x);
+            self.foods.insert(idx, food);
             true
         } else {
             false
@@ -121,7 +164,7 @@ impl FoodCollection {
 
     pub fn remove(&mut self, index: usize) -> Option<Food> {
         if index < self.foods.len() {
-            Some(self.foods.swap_remove(index))
+            Some(self.foods.remove(index))
         } else {
             None
         }
@@ -130,17 +173,12 @@ impl FoodCollection {
     pub fn remove_at_position(&mut self, x: u16, y: u16, tolerance: u16) -> Option<Food> {
         let tolerance_sq = (tolerance as u32).pow(2);
 
-        for i in 0..self.foods.len() {
+        let index = self.x_window(x, tolerance).find(|&i| {
             let food = &self.foods[i];
-            let dx = (food.x as i32 - x as i32).abs() as u32;
-            let dy = (food.y as i32 - y as i32).abs() as u32;
+            Self::distance_sq(food, x, y) <= tolerance_sq
+        })?;
 
-            if dx * dx + dy * dy <= tolerance_sq {
-                return Some(self.foods.swap_remove(i));
-            }
-        }
-
-        None
+        Some(self.foods.remove(index))
     }
 
     pub fn foods(&self) -> &[Food] {
@@ -171,16 +209,26 @@ impl FoodCollection {
     pub fn find_in_radius(&self, x: u16, y: u16, radius: u16) -> Vec<(usize, &Food)> {
         let radius_sq = (radius as u32).pow(2);
 
-        self.foods
-            .iter()
-            .enumerate()
-            .filter(|(_, food)| {
-                let dx = (food.x as i32 - x as i32).abs() as u32;
-                let dy = (food.y as i32 - y as i32).abs() as u32;
-                dx * dx + dy * dy <= radius_sq
-            })
+        self.x_window(x, radius)
+            .map(|i| (i, &self.foods[i]))
+            .filter(|(_, food)| Self::distance_sq(food, x, y) <= radius_sq)
             .collect()
     }
+
+   
+    fn x_window(&self, x: u16, radius: u16) -> std::ops::Range<usize> {
+        let min_x = x.saturating_sub(radius);
+        let max_x = x.saturating_add(radius);
+        let start = self.foods.partition_point(|f| f.x < min_x);
+        let end = self.foods.partition_point(|f| f.x <= max_x);
+        start..end
+    }
+
+    fn distance_sq(food: &Food, x: u16, y: u16) -> u32 {
+        let dx = food.x.abs_diff(x) as u32;
+        let dy = food.y.abs_diff(y) as u32;
+        dx * dx + dy * dy
+    }
 }
 
 
@@ -207,10 +255,29 @@ mod tests {
         assert_eq!(food.color, 5);
     }
 
+    #[test]
+    fn test_food_near_uses_palette() {
+        let palette = FoodPalette {
+            colors: vec![20],
+            min_size: 12,
+            max_size: 12,
+        };
+        let mut rng = crate::game::math::SimpleRng::new(7);
+        for _ in 0..50 {
+            let food = Food::near(1000, 1000, 30.0, &palette, &mut || rng.next_f32());
+            assert_eq!(food.color, 20);
+            assert!((10..25).contains(&food.size), "size {}", food.size);
+
+            let food = Food::near_with_size(1000, 1000, 30.0, 7, &palette, &mut || rng.next_f32());
+            assert_eq!((food.color, food.size), (20, 7));
+        }
+    }
+
     #[test]
     fn test_food_value() {
         let food = Food::new(0, 0, 10, 0);
-        assert_eq!(food.value(), 20);
+        assert_eq!(food.value(2), 20);
+        assert_eq!(food.value(5), 50);
     }
 
     #[test]
@@ -228,4 +295,54 @@ mod tests {
         assert!(removed.is_some());
         assert!(!collection.is_full());
     }
+
+    #[test]
+    fn test_find_in_radius_matches_linear_scan() {
+        let mut rng = crate::game::math::SimpleRng::new(42);
+        let mut collection = FoodCollection::new(100);
+
+        for _ in 0..100 {
+            let x = rng.range(0, 480) as u16;
+            let y = rng.range(0, 480) as u16;
+            collection.add(Food::new(x, y, 5, 0));
+        }
+
+        for _ in 0..50 {
+            let qx = rng.range(0, 480) as u16;
+            let qy = rng.range(0, 480) as u16;
+            let radius = rng.range(5, 60) as u16;
+
+            let mut indexed: Vec<(u16, u16)> = collection
+                .find_in_radius(qx, qy, radius)
+                .into_iter()
+                .map(|(_, f)| (f.x, f.y))
+                .collect();
+
+            let radius_sq = (radius as u32).pow(2);
+            let mut linear: Vec<(u16, u16)> = collection
+                .iter()
+                .filter(|f| FoodCollection::distance_sq(f, qx, qy) <= radius_sq)
+                .map(|f| (f.x, f.y))
+                .collect();
+
+            indexed.sort();
+            linear.sort();
+            assert_eq!(indexed, linear);
+        }
+    }
+
+    #[test]
+    fn test_position_query_scans_fewer_foods() {
+        let mut collection = FoodCollection::new(100);
+        for i in 0..100 {
+            collection.add(Food::new(i * 4, 200, 5, 0));
+        }
+
+        let candidates = collection.x_window(200, 10).len();
+        assert!(candidates <= 6, "scanned {} of {}", candidates, collection.len());
+
+        let removed = collection.remove_at_position(201, 200, 2).unwrap();
+        assert_eq!((removed.x, removed.y), (200, 200));
+        assert_eq!(collection.len(), 99);
+    }
 }
@@ -0,0 +1,158 @@
+
+
+
+use serde_json::{json, Value};
+use std::io;
+use std::path::Path;
+use thiserror::Error;
+
+
+#[derive(Debug, Error)]
+pub enum HallOfFameError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("malformed entry {0}")]
+    Malformed(usize),
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HallOfFameEntry {
+    pub name: String,
+    pub score: u32,
+    pub timestamp: u64,
+}
+
+impl HallOfFameEntry {
+    fn to_json(&self) -> Value {
+        json!({
+            "name": self.name,
+            "score": self.score,
+            "timestamp": self.timestamp,
+        })
+    }
+
+    fn from_json(value: &Value) -> Option<Self> {
+        Some(Self {
+            name: value.get("name")?.as_str()?.to_string(),
+            score: u32::try_from(value.get("score")?.as_u64()?).ok()?,
+            timestamp: value.get("timestamp")?.as_u64()?,
+        })
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HallOfFame {
+    entries: Vec<HallOfFameEntry>,
+    capacity: usize,
+}
+
+impl HallOfFame {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn entries(&self) -> &[HallOfFameEntry] {
+        &self.entries
+    }
+
+   
+    pub fn qualifies(&self, score: u32) -> bool {
+        self.capacity > 0
+            && (self.entries.len() < self.capacity
+                || self.entries.last().is_some_and(|last| score > last.score))
+    }
+
+   
+    pub fn record(&mut self, name: &str, score: u32, timestamp: u64) -> bool {
+        if !self.qualifies(score) {
+            return false;
+        }
+
+       
+        let pos = self.entries.partition_point(|e| e.score >= score);
+        self.entries.insert(
+            pos,
+            HallOfFameEntry {
+                name: name.to_string(),
+                score,
+                timestamp,
+            },
+        );
+        self.entries.truncate(self.capacity);
+        true
+    }
+
+    pub fn to_json(&self) -> Value {
+        Value::Array(self.entries.iter().map(HallOfFameEntry::to_json).collect())
+    }
+
+   
+    pub fn from_json(value: &Value, capacity: usize) -> Result<Self, HallOfFameError> {
+        let mut hall = Self::new(capacity);
+        let items = value.as_array().ok_or(HallOfFameError::Malformed(0))?;
+        for (i, item) in items.iter().enumerate() {
+            let entry = HallOfFameEntry::from_json(item).ok_or(HallOfFameError::Malformed(i))?;
+            hall.record(&entry.name, entry.score, entry.timestamp);
+        }
+        Ok(hall)
+    }
+
+   
+    pub fn load(path: &Path, capacity: usize) -> Result<Self, HallOfFameError> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::from_json(&serde_json::from_str(&text)?, capacity),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::new(capacity)),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+   
+    pub fn save(&self, path: &Path) -> Result<(), HallOfFameError> {
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_string_pretty(&self.to_json())?)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_stay_sorted_and_capped_across_save_load() {
+        let path = std::env::temp_dir().join(format!("slither_hof_{}.json", std::process::id()));
+        let mut hall = HallOfFame::new(3);
+
+        assert!(hall.record("a", 50, 1));
+        assert!(hall.record("b", 80, 2));
+        assert!(hall.record("c", 20, 3));
+        assert!(hall.record("d", 60, 4));
+        assert!(!hall.record("e", 10, 5));
+        hall.save(&path).unwrap();
+
+        let mut loaded = HallOfFame::load(&path, 3).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, hall);
+
+        assert!(loaded.record("f", 70, 6));
+        let names: Vec<_> = loaded.entries().iter().map(|e| e.name.as_str()).collect();
+        let scores: Vec<_> = loaded.entries().iter().map(|e| e.score).collect();
+        assert_eq!(names, ["b", "f", "d"]);
+        assert_eq!(scores, [80, 70, 60]);
+    }
+
+    #[test]
+    fn test_missing_file_loads_empty() {
+        let path = std::env::temp_dir().join("slither_hof_does_not_exist.json");
+        let hall = HallOfFame::load(&path, 5).unwrap();
+        assert!(hall.entries().is_empty());
+    }
+}
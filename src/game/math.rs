@@ -41,6 +41,19 @@ pub fn point_in_circle(px: f32, py: f32, cx: f32, cy: f32, radius: f32) -> bool
 }
 
 
+
+pub fn random_point_in_circle(
+    cx: f32,
+    cy: f32,
+    radius: f32,
+    rng: &mut impl FnMut() -> f32,
+) -> (f32, f32) {
+    let angle = rng() * std::f32::consts::PI * 2.0;
+    let r = rng().sqrt() * radius;
+    (cx + r * angle.cos(), cy + r * angle.sin())
+}
+
+
 #[inline]
 pub fn circles_intersect(x1: f32, y1: f32, r1: f32, x2: f32, y2: f32, r2: f32) -> bool {
     let combined_radius = r1 + r2;
@@ -167,6 +180,14 @@ impl SimpleRng {
         Self { state: seed }
     }
 
+   
+    pub fn stream(seed: u64, stream: u64) -> Self {
+        let mut z = seed ^ stream.wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        Self::new(z ^ (z >> 31))
+    }
+
     pub fn next_u64(&mut self) -> u64 {
         self.state = self.state.wrapping_mul(6364136223846793005).wrapping_add(1);
         self.state
@@ -258,6 +279,16 @@ impl Default for Viewport {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_rng_streams_are_independent() {
+        let mut a = SimpleRng::stream(42, 0);
+        let mut b = SimpleRng::stream(42, 1);
+        let mut a_again = SimpleRng::stream(42, 0);
+        let first: Vec<u64> = (0..4).map(|_| a.next_u64()).collect();
+        assert_eq!(first, (0..4).map(|_| a_again.next_u64()).collect::<Vec<_>>());
+        assert_ne!(first, (0..4).map(|_| b.next_u64()).collect::<Vec<_>>());
+    }
+
     #[test]
     fn test_distance() {
         assert!((distance(0.0, 0.0, 3.0, 4.0) - 5.0).abs() < 0.1);
@@ -11,8 +11,10 @@ pub mod food;
 pub mod snake;
 pub mod sector;
 pub mod world;
+pub mod hall_of_fame;
 
 pub use food::Food;
 pub use snake::Snake;
 pub use sector::{Sector, SectorGrid};
-pub use world::World;
+pub use world::{SnakeInfo, World};
+pub use hall_of_fame::HallOfFame;
@@ -137,17 +137,12 @@ impl SectorGrid {
 
    
     pub fn sectors_in_viewport(&self, viewport_x: f32, viewport_y: f32, radius: f32) -> Vec<(u8, u8)> {
-        let min_x = ((viewport_x - radius) / self.sector_size as f32).floor() as i32;
-        let max_x = ((viewport_x + radius) / self.sector_size as f32).ceil() as i32;
-        let min_y = ((viewport_y - radius) / self.sector_size as f32).floor() as i32;
-        let max_y = ((viewport_y + radius) / self.sector_size as f32).ceil() as i32;
-
         let mut result = Vec::new();
 
-        for y in min_y..=max_y {
-            for x in min_x..=max_x {
-                if x >= 0 && x < self.size as i32 && y >= 0 && y < self.size as i32 {
-                    result.push((x as u8, y as u8));
+        if let Some(range) = self.sector_range_in_viewport(viewport_x, viewport_y, radius) {
+            for y in range.min_y..=range.max_y {
+                for x in range.min_x..=range.max_x {
+                    result.push((x, y));
                 }
             }
         }
@@ -156,6 +151,31 @@ impl SectorGrid {
     }
 
    
+    pub fn sector_range_in_viewport(
+        &self,
+        viewport_x: f32,
+        viewport_y: f32,
+        radius: f32,
+    ) -> Option<SectorRange> {
+        let min_x = ((viewport_x - radius) / self.sector_size as f32).floor() as i32;
+        let max_x = ((viewport_x + radius) / self.sector_size as f32).ceil() as i32;
+        let min_y = ((viewport_y - radius) / self.sector_size as f32).floor() as i32;
+        let max_y = ((viewport_y + radius) / self.sector_size as f32).ceil() as i32;
+
+        let last = self.size as i32 - 1;
+        if max_x < 0 || max_y < 0 || min_x > last || min_y > last {
+            return None;
+        }
+
+        Some(SectorRange {
+            min_x: min_x.max(0) as u8,
+            min_y: min_y.max(0) as u8,
+            max_x: max_x.min(last) as u8,
+            max_y: max_y.min(last) as u8,
+        })
+    }
+
+   
     pub fn add_snake(&mut self, id: SnakeId, world_x: f32, world_y: f32) {
         let (sx, sy) = self.world_to_sector(world_x, world_y);
         if let Some(sector) = self.get_mut(sx, sy) {
@@ -264,10 +284,31 @@ pub enum SectorEvent {
 }
 
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SectorRange {
+    pub min_x: u8,
+    pub min_y: u8,
+    pub max_x: u8,
+    pub max_y: u8,
+}
+
+impl SectorRange {
+   
+    pub fn contains(&self, x: u8, y: u8) -> bool {
+        (self.min_x..=self.max_x).contains(&x) && (self.min_y..=self.max_y).contains(&y)
+    }
+}
+
+
+
 #[derive(Debug, Default)]
 pub struct SectorTracker {
    
-    visible: HashSet<(u8, u8)>,
+    visible: Vec<(u8, u8)>,
+   
+    scratch: Vec<(u8, u8)>,
+   
+    last_ranges: (Option<SectorRange>, Option<SectorRange>),
 }
 
 impl SectorTracker {
@@ -281,42 +322,89 @@ impl SectorTracker {
         grid: &SectorGrid,
         viewport_x: f32,
         viewport_y: f32,
-        view_radius: f32,
+        enter_radius: f32,
+        leave_radius: f32,
     ) -> Vec<SectorEvent> {
-        let new_visible: HashSet<_> = grid
-            .sectors_in_viewport(viewport_x, viewport_y, view_radius)
-            .into_iter()
-            .collect();
+        self.update_ranges(
+            grid.sector_range_in_viewport(viewport_x, viewport_y, enter_radius),
+            grid.sector_range_in_viewport(viewport_x, viewport_y, enter_radius.max(leave_radius)),
+        )
+    }
 
-        let mut events = Vec::new();
+   
+    pub fn update_range(&mut self, range: Option<SectorRange>) -> Vec<SectorEvent> {
+        self.update_ranges(range, range)
+    }
 
-       
-        for &(x, y) in new_visible.difference(&self.visible) {
-            events.push(SectorEvent::Entered { x, y });
+   
+   
+    pub fn update_ranges(
+        &mut self,
+        enter: Option<SectorRange>,
+        leave: Option<SectorRange>,
+    ) -> Vec<SectorEvent> {
+        if (enter, leave) == self.last_ranges && !self.visible.is_empty() {
+            return Vec::new();
+        }
+        self.last_ranges = (enter, leave);
+
+        self.scratch.clear();
+        if let Some(range) = leave {
+            for x in range.min_x..=range.max_x {
+                for y in range.min_y..=range.max_y {
+                    if enter.is_some_and(|e| e.contains(x, y)) || self.is_visible(x, y) {
+                        self.scratch.push((x, y));
+                    }
+                }
+            }
         }
 
-       
-        for &(x, y) in self.visible.difference(&new_visible) {
-            events.push(SectorEvent::Left { x, y });
+        let mut events = Vec::new();
+        let (mut old_i, mut new_i) = (0, 0);
+
+        while old_i < self.visible.len() || new_i < self.scratch.len() {
+            let old = self.visible.get(old_i);
+            let new = self.scratch.get(new_i);
+
+            match (old, new) {
+                (Some(&o), Some(&n)) if o == n => {
+                    old_i += 1;
+                    new_i += 1;
+                }
+                (Some(&(x, y)), Some(&n)) if (x, y) < n => {
+                    events.push(SectorEvent::Left { x, y });
+                    old_i += 1;
+                }
+                (Some(&(x, y)), None) => {
+                    events.push(SectorEvent::Left { x, y });
+                    old_i += 1;
+                }
+                (_, Some(&(x, y))) => {
+                    events.push(SectorEvent::Entered { x, y });
+                    new_i += 1;
+                }
+                (None, None) => break,
+            }
         }
 
-        self.visible = new_visible;
+        std::mem::swap(&mut self.visible, &mut self.scratch);
         events
     }
 
    
-    pub fn visible_sectors(&self) -> &HashSet<(u8, u8)> {
+    pub fn visible_sectors(&self) -> &[(u8, u8)] {
         &self.visible
     }
 
    
     pub fn is_visible(&self, x: u8, y: u8) -> bool {
-        self.visible.contains(&(x, y))
+        self.visible.binary_search(&(x, y)).is_ok()
     }
 
    
     pub fn clear(&mut self) {
         self.visible.clear();
+        self.last_ranges = (None, None);
     }
 }
 
@@ -342,7 +430,29 @@ mod tests {
         assert_eq!((sx, sy), (1, 1));
 
         let (sx, sy) = grid.world_to_sector(21600.0, 21600.0);
-        assert_eq!((sx, sy), (44, 44));
+        assert_eq!((sx, sy), (45, 45));
+    }
+
+    #[test]
+    fn test_world_to_sector_corners_and_center() {
+        let config = crate::config::GameConfig::default();
+        let count = config.sector_grid_size().unwrap();
+        let grid = SectorGrid::new(count, config.sector_size, 100);
+        let diameter = 2.0 * config.game_radius as f32;
+        let last = count - 1;
+
+        let cases = [
+            ((0.0, 0.0), (0, 0)),
+            ((diameter, 0.0), (last, 0)),
+            ((0.0, diameter), (0, last)),
+            ((diameter, diameter), (last, last)),
+            ((diameter - 1.0, diameter - 1.0), (last, last)),
+            ((config.game_radius as f32, config.game_radius as f32), (45, 45)),
+            ((-50.0, diameter + 50.0), (0, last)),
+        ];
+        for ((x, y), expected) in cases {
+            assert_eq!(grid.world_to_sector(x, y), expected, "at ({}, {})", x, y);
+        }
     }
 
     #[test]
@@ -362,11 +472,73 @@ mod tests {
         let grid = SectorGrid::new(90, 480, 100);
         let mut tracker = SectorTracker::new();
 
-        let events = tracker.update(&grid, 1000.0, 1000.0, 500.0);
+        let events = tracker.update(&grid, 1000.0, 1000.0, 500.0, 500.0);
         assert!(!events.is_empty());
 
        
-        let events = tracker.update(&grid, 1000.0, 1000.0, 500.0);
+        let events = tracker.update(&grid, 1000.0, 1000.0, 500.0, 500.0);
         assert!(events.is_empty());
     }
+
+    #[test]
+    fn test_sector_tracker_hysteresis_stops_boundary_thrashing() {
+        let grid = SectorGrid::new(90, 480, 100);
+        let mut strict = SectorTracker::new();
+        let mut padded = SectorTracker::new();
+        strict.update(&grid, 2370.0, 2000.0, 500.0, 500.0);
+        padded.update(&grid, 2370.0, 2000.0, 500.0, 700.0);
+
+        let (mut strict_events, mut padded_events) = (0, 0);
+        for i in 0..20 {
+            let x = if i % 2 == 0 { 2390.0 } else { 2370.0 };
+            strict_events += strict.update(&grid, x, 2000.0, 500.0, 500.0).len();
+            padded_events += padded.update(&grid, x, 2000.0, 500.0, 700.0).len();
+        }
+
+        assert_eq!(strict_events, 20 * 4);
+        assert_eq!(padded_events, 4);
+        assert!(padded.is_visible(7, 4));
+
+        let events = padded.update(&grid, 2000.0, 2000.0, 500.0, 700.0);
+        assert!(events.iter().any(|e| matches!(e, SectorEvent::Left { x: 7, y: 4 })));
+        assert!(!padded.is_visible(7, 4));
+    }
+
+    #[test]
+    fn test_sector_tracker_stationary_does_not_allocate() {
+        let grid = SectorGrid::new(90, 480, 100);
+        let mut tracker = SectorTracker::new();
+        tracker.update(&grid, 1000.0, 1000.0, 500.0, 500.0);
+
+        for i in 0..100 {
+            let events = tracker.update(&grid, 1000.0 + i as f32, 1000.0, 500.0, 500.0);
+            assert!(events.is_empty());
+            assert_eq!(events.capacity(), 0);
+        }
+    }
+
+    #[test]
+    fn test_sector_tracker_events_match_set_difference() {
+        let grid = SectorGrid::new(90, 480, 100);
+        let mut tracker = SectorTracker::new();
+        tracker.update(&grid, 1000.0, 1000.0, 500.0, 500.0);
+        let before: HashSet<_> = tracker.visible_sectors().iter().copied().collect();
+
+        let events = tracker.update(&grid, 1500.0, 1200.0, 500.0, 500.0);
+        let after: HashSet<_> = grid.sectors_in_viewport(1500.0, 1200.0, 500.0).into_iter().collect();
+
+        let mut entered = HashSet::new();
+        let mut left = HashSet::new();
+        for event in events {
+            match event {
+                SectorEvent::Entered { x, y } => assert!(entered.insert((x, y))),
+                SectorEvent::Left { x, y } => assert!(left.insert((x, y))),
+            }
+        }
+
+        assert_eq!(entered, after.difference(&before).copied().collect());
+        assert_eq!(left, before.difference(&after).copied().collect());
+        assert!(tracker.visible_sectors().iter().all(|&(x, y)| after.contains(&(x, y))));
+        assert!(tracker.is_visible(3, 2));
+    }
 }
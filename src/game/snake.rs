@@ -1,11 +1,14 @@
 
 
-use crate::config::snake_consts;
-use crate::game::food::Food;
+use crate::config::{snake_consts, BoundaryBehavior};
+use crate::game::food::{Food, FoodPalette};
 use crate::game::math::{
-    distance_squared, lerp_angle, move_towards_angle, normalize_angle, BoundingBox, Viewport,
+    distance_squared, lerp_angle, move_towards_angle, normalize_angle, segment_circle_intersect,
+    BoundingBox, Viewport,
 };
-use crate::protocol::types::{SnakeChanges, SnakeId};
+#[cfg(feature = "bots")]
+use crate::game::math::SimpleRng;
+use crate::protocol::types::{fullness_to_fam, SnakeChanges, SnakeId};
 use std::collections::VecDeque;
 use std::f32::consts::PI;
 
@@ -44,7 +47,7 @@ pub struct Snake {
    
     pub name: String,
    
-    pub custom_skin: Option<String>,
+    pub custom_skin: Option<Vec<u8>>,
    
     pub speed: f32,
    
@@ -56,10 +59,16 @@ pub struct Snake {
    
     pub bounding_box: BoundingBox,
    
+    pub prev_head: (f32, f32),
+   
     pub viewport: Viewport,
    
     pub body: VecDeque<BodyPart>,
    
+    pub min_length: usize,
+   
+    pub movement_substep_ms: u64,
+   
     pub foods_eaten: Vec<Food>,
    
     pub foods_spawned: Vec<Food>,
@@ -68,19 +77,36 @@ pub struct Snake {
    
     pub dying: bool,
    
+    pub out_of_bounds: bool,
+   
     pub dead: bool,
    
+    pub death_processed: bool,
+   
     rot_time_accum: u64,
    
+    #[cfg(feature = "bots")]
     ai_time_accum: u64,
    
-    prev_head_x: f32,
-    prev_head_y: f32,
+    #[cfg(feature = "bots")]
+    ai_turn: f32,
+   
+    reported_head: (i32, i32),
+   
+    last_head_delta: (i16, i16),
 }
 
 impl Snake {
    
-    pub fn new(id: SnakeId, x: f32, y: f32, name: String, skin: u8, start_length: usize) -> Self {
+    pub fn new(
+        id: SnakeId,
+        x: f32,
+        y: f32,
+        name: String,
+        skin: u8,
+        start_length: usize,
+        start_fullness: u32,
+    ) -> Self {
         let mut body = VecDeque::with_capacity(start_length.max(10));
 
        
@@ -100,19 +126,27 @@ impl Snake {
             speed: snake_consts::BASE_MOVE_SPEED as f32,
             angle: PI / 2.0,
             target_angle: PI / 2.0,
-            fullness: 0,
+            fullness: start_fullness,
             bounding_box: BoundingBox::new(x, y, 50.0),
+            prev_head: (x, y),
             viewport: Viewport::default(),
             body,
+            min_length: 1,
+            movement_substep_ms: 0,
             foods_eaten: Vec::new(),
             foods_spawned: Vec::new(),
             kills: 0,
             dying: false,
+            out_of_bounds: false,
             dead: false,
+            death_processed: false,
             rot_time_accum: 0,
+            #[cfg(feature = "bots")]
             ai_time_accum: 0,
-            prev_head_x: x,
-            prev_head_y: y,
+            #[cfg(feature = "bots")]
+            ai_turn: 0.0,
+            reported_head: (x.round() as i32, y.round() as i32),
+            last_head_delta: (0, 0),
         };
 
         snake.update_bounding_box();
@@ -136,7 +170,7 @@ impl Snake {
     pub fn head_pos_u16(&self) -> (u16, u16) {
         self.body
             .front()
-            .map(|p| (p.x as u16, p.y as u16))
+            .map(|p| (p.x.round() as u16, p.y.round() as u16))
             .unwrap_or((0, 0))
     }
 
@@ -152,26 +186,33 @@ impl Snake {
 
    
     pub fn scale(&self) -> f32 {
-        let base = 1.0;
-        let growth = (self.fullness as f32 / 10000.0).min(2.0);
-        base + growth * 0.5
+        scale_for_parts(self.body.len())
     }
 
    
     pub fn body_radius(&self) -> f32 {
-        14.0 * self.scale()
+        body_radius_for_scale(self.scale())
+    }
+
+   
+    pub fn eat_radius(&self, multiplier: f32) -> f32 {
+        self.body_radius() * multiplier
     }
 
    
     pub fn score(&self) -> u32 {
        
         let parts = self.body.len() as f32;
-        let fam = self.fullness as f32 / 16777215.0;
-        ((15.0 * (parts - 1.0) + fam) / 3.0 - 8.0).floor().max(1.0) as u32
+        ((15.0 * (parts - 1.0) + self.fam()) / 3.0 - 8.0).floor().max(1.0) as u32
     }
 
    
-    pub fn tick(&mut self, dt_ms: u64, game_radius: f32) {
+    pub fn fam(&self) -> f32 {
+        fullness_to_fam(self.fullness)
+    }
+
+   
+    pub fn tick(&mut self, dt_ms: u64, game_radius: f32, boundary: BoundaryBehavior) {
         if self.dead {
             return;
         }
@@ -179,29 +220,32 @@ impl Snake {
         self.changes.clear();
         self.foods_eaten.clear();
 
-       
-        self.rot_time_accum += dt_ms;
-        while self.rot_time_accum >= snake_consts::ROT_STEP_INTERVAL_MS {
-            self.rot_time_accum -= snake_consts::ROT_STEP_INTERVAL_MS;
-            self.update_rotation();
-        }
+        self.prev_head = self.head_pos();
+        let substep = if self.movement_substep_ms == 0 { dt_ms } else { self.movement_substep_ms };
+        let mut remaining = dt_ms;
+        while remaining > 0 {
+            let step = remaining.min(substep);
+            remaining -= step;
 
-       
-        self.update_speed(dt_ms);
+           
+            self.rot_time_accum += step;
+            while self.rot_time_accum >= snake_consts::ROT_STEP_INTERVAL_MS {
+                self.rot_time_accum -= snake_consts::ROT_STEP_INTERVAL_MS;
+                self.update_rotation();
+            }
 
-       
-        self.move_forward(dt_ms);
+           
+            self.update_speed(step);
 
-       
-        let (hx, hy) = self.head_pos();
-        let dist_from_center = (hx * hx + hy * hy).sqrt();
-        if dist_from_center > game_radius * 0.98 {
-            self.dying = true;
-            self.changes.set_dying();
+           
+            self.move_forward(step);
         }
 
+        self.apply_boundary(game_radius, boundary);
+        let (hx, hy) = self.head_pos();
+
        
-        if self.accelerating && self.fullness > 0 {
+        if self.accelerating {
             self.handle_boost_cost();
         }
 
@@ -209,12 +253,69 @@ impl Snake {
         self.update_bounding_box();
 
        
-        self.prev_head_x = hx;
-        self.prev_head_y = hy;
+        let reported = (hx.round() as i32, hy.round() as i32);
+        let delta = |now: i32, before: i32| (now - before).clamp(i16::MIN as i32, i16::MAX as i32) as i16;
+        self.last_head_delta = (
+            delta(reported.0, self.reported_head.0),
+            delta(reported.1, self.reported_head.1),
+        );
+        self.reported_head = reported;
     }
 
    
-    pub fn tick_ai(&mut self, dt_ms: u64) {
+    fn apply_boundary(&mut self, game_radius: f32, boundary: BoundaryBehavior) {
+        let limit = game_radius * 0.98;
+        let Some(head) = self.body.front_mut() else {
+            return;
+        };
+        let (dx, dy) = (head.x - game_radius, head.y - game_radius);
+        let dist_from_center = (dx * dx + dy * dy).sqrt();
+        if dist_from_center <= limit {
+            return;
+        }
+
+        let (nx, ny) = (dx / dist_from_center, dy / dist_from_center);
+        match boundary {
+            BoundaryBehavior::Kill => {
+                self.dying = true;
+                self.out_of_bounds = true;
+                self.changes.set_dying();
+                return;
+            }
+            BoundaryBehavior::Bounce => {
+                let (vx, vy) = (self.angle.cos(), self.angle.sin());
+                let dot = vx * nx + vy * ny;
+                if dot > 0.0 {
+                    self.angle = normalize_angle((vy - 2.0 * dot * ny).atan2(vx - 2.0 * dot * nx));
+                    self.target_angle = self.angle;
+                    self.changes.set_angle();
+                    self.changes.set_wangle();
+                }
+            }
+            BoundaryBehavior::Clamp => {}
+        }
+
+        head.x = game_radius + nx * limit;
+        head.y = game_radius + ny * limit;
+    }
+
+   
+    pub fn warn_near_boundary(&mut self, game_radius: f32, warning_distance: f32) {
+        if self.dead || self.out_of_bounds {
+            return;
+        }
+        let (hx, hy) = self.head_pos();
+        let dist_from_center = ((hx - game_radius).powi(2) + (hy - game_radius).powi(2)).sqrt();
+        let warn = dist_from_center > game_radius * 0.98 - warning_distance;
+        if warn && !self.dying {
+            self.changes.set_dying();
+        }
+        self.dying = warn;
+    }
+
+   
+    #[cfg(feature = "bots")]
+    pub fn tick_ai(&mut self, dt_ms: u64, rng: &mut impl FnMut() -> f32) {
         if !self.is_bot || self.dead {
             return;
         }
@@ -224,23 +325,35 @@ impl Snake {
             self.ai_time_accum = 0;
 
            
-            let random = (self.id as f32 * 0.1 + self.fullness as f32 * 0.001) % 1.0;
-            if random < 0.1 {
-               
-                self.target_angle += (random - 0.05) * PI;
-                self.target_angle = normalize_angle(self.target_angle);
-            }
+            self.ai_turn = if rng() < snake_consts::BOT_TURN_CHANGE_CHANCE {
+                rng() * 2.0 - 1.0
+            } else {
+                self.ai_turn * snake_consts::BOT_TURN_DECAY
+            };
         }
+
+       
+        let per_ms = self.max_turn_per_step() / snake_consts::ROT_STEP_INTERVAL_MS as f32;
+        self.target_angle = normalize_angle(self.target_angle + self.ai_turn * per_ms * dt_ms as f32);
+    }
+
+   
+    #[cfg(feature = "bots")]
+    pub fn steer_away(&mut self, bearing_offset: f32) {
+        let away = if bearing_offset >= 0.0 { -PI / 2.0 } else { PI / 2.0 };
+        self.ai_turn = 0.0;
+        self.target_angle = normalize_angle(self.angle + away);
+    }
+
+   
+    pub fn max_turn_per_step(&self) -> f32 {
+        snake_consts::ANGULAR_SPEED * 0.001 * turn_factor_for_scale(self.scale())
     }
 
    
     fn update_rotation(&mut self) {
         let prev_angle = self.angle;
-        self.angle = move_towards_angle(
-            self.angle,
-            self.target_angle,
-            snake_consts::ANGULAR_SPEED * 0.001,
-        );
+        self.angle = move_towards_angle(self.angle, self.target_angle, self.max_turn_per_step());
 
         if (self.angle - prev_angle).abs() > 0.001 {
             self.changes.set_angle();
@@ -263,6 +376,14 @@ impl Snake {
             self.speed += change;
             self.changes.set_speed();
         }
+
+        let clamped = self
+            .speed
+            .clamp(snake_consts::BASE_MOVE_SPEED as f32, snake_consts::BOOST_SPEED as f32);
+        if clamped != self.speed {
+            self.speed = clamped;
+            self.changes.set_speed();
+        }
     }
 
    
@@ -304,14 +425,25 @@ impl Snake {
 
    
     fn handle_boost_cost(&mut self) {
-       
-        if self.fullness >= snake_consts::BOOST_COST as u32 {
-            self.fullness -= snake_consts::BOOST_COST as u32;
+        if self.fullness < snake_consts::BOOST_COST && self.body.len() > self.min_length {
+            self.body.pop_back();
+            self.add_fullness(snake_consts::FULLNESS_PER_PART);
+        }
+
+        if self.fullness >= snake_consts::BOOST_COST {
+            self.fullness = self.fullness.saturating_sub(snake_consts::BOOST_COST);
             self.changes.set_fullness();
+        } else {
+            self.accelerating = false;
         }
     }
 
    
+    pub fn can_boost(&self) -> bool {
+        self.fullness >= snake_consts::BOOST_COST || self.body.len() > self.min_length
+    }
+
+   
     pub fn set_target_angle(&mut self, angle: f32) {
         let new_angle = normalize_angle(angle);
         if (new_angle - self.target_angle).abs() > 0.001 {
@@ -322,13 +454,12 @@ impl Snake {
 
    
     pub fn set_accelerating(&mut self, accelerating: bool) {
-        self.accelerating = accelerating;
+        self.accelerating = accelerating && self.can_boost();
     }
 
    
-    pub fn eat_food(&mut self, food: Food) {
-        let value = food.value() as u32;
-        self.fullness += value;
+    pub fn eat_food(&mut self, food: Food, value_multiplier: u16, growth_factor: f32) {
+        self.add_fullness((food.value(value_multiplier) as f32 * growth_factor).round() as u32);
         self.foods_eaten.push(food);
         self.changes.set_fullness();
 
@@ -337,9 +468,24 @@ impl Snake {
     }
 
    
+    pub fn absorb(&mut self, amount: u32) {
+        if amount == 0 {
+            return;
+        }
+        self.add_fullness(amount);
+        self.changes.set_fullness();
+        self.try_grow();
+    }
+
+   
+    fn add_fullness(&mut self, amount: u32) {
+        self.fullness = self.fullness.saturating_add(amount).min(snake_consts::MAX_FULLNESS);
+    }
+
+   
     fn try_grow(&mut self) {
        
-        let target_parts = (self.fullness / 100).min(500) as usize + 10;
+        let target_parts = (self.fullness / snake_consts::FULLNESS_PER_PART).min(500) as usize + 10;
         if self.body.len() < target_parts {
             if let Some(tail) = self.body.back() {
                 let new_part = BodyPart::new(tail.x, tail.y);
@@ -349,14 +495,46 @@ impl Snake {
     }
 
    
-    pub fn kill(&mut self, rng: &mut impl FnMut() -> f32) {
+    pub fn mass(&self) -> u32 {
+        self.fullness + self.body.len() as u32 * snake_consts::FULLNESS_PER_PART
+    }
+
+   
+    pub fn death_drop_value(&self) -> u32 {
+        (self.mass() as f32 * snake_consts::DEATH_DROP_RATIO) as u32
+    }
+
+   
+    pub fn kill(&mut self, value_multiplier: u16, palette: &FoodPalette, rng: &mut impl FnMut() -> f32) {
         self.dying = true;
         self.dead = true;
         self.changes.set_dead();
 
+        if self.body.is_empty() || value_multiplier == 0 {
+            return;
+        }
+
+        let mut remaining = self.death_drop_value() / value_multiplier as u32;
+        if remaining == 0 {
+            return;
+        }
+
        
-        for part in &self.body {
-            let food = Food::near(part.x as u16, part.y as u16, 20.0, rng);
+        let per_part = remaining.div_ceil(self.body.len() as u32);
+        let by_scale = (snake_consts::DEATH_FOOD_MIN_SIZE as f32 * self.scale()) as u32;
+        let pellet_size = per_part.max(by_scale).clamp(
+            snake_consts::DEATH_FOOD_MIN_SIZE as u32,
+            snake_consts::DEATH_FOOD_MAX_SIZE as u32,
+        );
+        let pellet_count = remaining.div_ceil(pellet_size) as usize;
+        let jitter = self.body_radius();
+
+        for i in 0..pellet_count {
+            let size = remaining.min(pellet_size) as u8;
+            remaining -= size as u32;
+
+            let part = &self.body[i * self.body.len() / pellet_count];
+            let food = Food::near_with_size(part.x as u16, part.y as u16, jitter, size, palette, rng);
             self.foods_spawned.push(food);
         }
     }
@@ -395,10 +573,7 @@ impl Snake {
 
    
     pub fn head_delta(&self) -> (i16, i16) {
-        let (hx, hy) = self.head_pos();
-        let dx = (hx - self.prev_head_x) as i16;
-        let dy = (hy - self.prev_head_y) as i16;
-        (dx, dy)
+        self.last_head_delta
     }
 
    
@@ -407,6 +582,16 @@ impl Snake {
     }
 
    
+    pub fn head_on_with(&self, other: &Snake) -> bool {
+        if self.id == other.id {
+            return false;
+        }
+        let ((x1, y1), (x2, y2)) = (self.head_pos(), other.head_pos());
+        let combined_radius = self.body_radius() + other.body_radius();
+        distance_squared(x1, y1, x2, y2) <= combined_radius * combined_radius
+    }
+
+   
     pub fn collides_with(&self, other: &Snake) -> bool {
         if self.id == other.id {
             return false;
@@ -419,6 +604,7 @@ impl Snake {
 
        
         let (head_x, head_y) = self.head_pos();
+        let (prev_x, prev_y) = self.prev_head;
         let head_radius = self.body_radius();
 
         for (i, part) in other.body.iter().enumerate() {
@@ -435,6 +621,11 @@ impl Snake {
             {
                 return true;
             }
+
+           
+            if segment_circle_intersect(prev_x, prev_y, head_x, head_y, part.x, part.y, combined_radius) {
+                return true;
+            }
         }
 
         false
@@ -442,6 +633,26 @@ impl Snake {
 }
 
 
+// Same curve the client uses for `sc`: 1.0 at two parts, growing linearly and
+// capped at MAX_SCALE.
+pub fn scale_for_parts(parts: usize) -> f32 {
+    let parts = parts.max(2) as f32;
+    (1.0 + (parts - 2.0) / snake_consts::PARTS_PER_SCALE).min(snake_consts::MAX_SCALE)
+}
+
+
+pub fn body_radius_for_scale(scale: f32) -> f32 {
+    snake_consts::BODY_WIDTH * scale / 2.0
+}
+
+
+pub fn turn_factor_for_scale(scale: f32) -> f32 {
+    let slack = ((snake_consts::MAX_SCALE + 1.0 - scale) / snake_consts::MAX_SCALE).clamp(0.0, 1.0);
+    snake_consts::TURN_FACTOR_MIN + (1.0 - snake_consts::TURN_FACTOR_MIN) * slack * slack
+}
+
+
+#[cfg(feature = "bots")]
 pub const BOT_NAMES: &[&str] = &[
     "Bumba",
     "nick26",
@@ -463,44 +674,373 @@ pub const BOT_NAMES: &[&str] = &[
 ];
 
 
-pub fn random_bot_name(rng: &mut impl FnMut() -> f32) -> String {
-    let idx = (rng() * BOT_NAMES.len() as f32) as usize;
-    BOT_NAMES[idx % BOT_NAMES.len()].to_string()
+#[cfg(feature = "bots")]
+pub fn random_bot_name(rng: &mut SimpleRng) -> &'static str {
+    BOT_NAMES[rng.range(0, BOT_NAMES.len() as u32) as usize]
+}
+
+
+#[cfg(feature = "bots")]
+pub fn unique_bot_name(base: &str, is_taken: impl Fn(&str) -> bool) -> String {
+    if !is_taken(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{} {}", base, n))
+        .find(|name| !is_taken(name))
+        .unwrap_or_else(|| base.to_string())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::protocol::writer::RELATIVE_COORD_RANGE;
+    #[cfg(feature = "bots")]
+    use crate::game::math::angle_difference;
 
     #[test]
     fn test_snake_creation() {
-        let snake = Snake::new(1, 1000.0, 1000.0, "Test".to_string(), 0, 10);
+        let snake = Snake::new(1, 1000.0, 1000.0, "Test".to_string(), 0, 10, 0);
         assert_eq!(snake.id, 1);
         assert_eq!(snake.length(), 10);
         assert!(snake.head().is_some());
     }
 
+    #[test]
+    fn test_fast_snake_crossing_a_body_collides() {
+        let other = Snake::new(2, 1000.0, 1200.0, String::new(), 0, 20, 0);
+
+        let crossing_snake = |from: f32, to: f32| {
+            let mut snake = Snake::new(1, from, 1000.0, String::new(), 0, 10, 0);
+            snake.prev_head = (from, 1000.0);
+            snake.body[0].x = to;
+            snake.update_bounding_box();
+            snake
+        };
+
+        let tunneling = crossing_snake(900.0, 1100.0);
+        let (hx, hy) = tunneling.head_pos();
+        assert!(other.body.iter().all(|p| distance_squared(hx, hy, p.x, p.y) > 60.0 * 60.0));
+        assert!(tunneling.collides_with(&other));
+
+        assert!(!crossing_snake(1100.0, 1150.0).collides_with(&other));
+    }
+
+    #[test]
+    fn test_eat_radius_scales_with_body_radius() {
+        let small = Snake::new(1, 1000.0, 1000.0, String::new(), 0, 2, 0);
+        let large = Snake::new(2, 1000.0, 1000.0, String::new(), 0, 300, 0);
+        let m = snake_consts::EAT_RADIUS_MULTIPLIER;
+
+        assert!(large.body_radius() > small.body_radius());
+        for snake in [&small, &large] {
+            assert!((snake.eat_radius(m) / snake.body_radius() - m).abs() < 1e-5);
+        }
+        assert!(
+            large.eat_radius(m) - large.body_radius() > small.eat_radius(m) - small.body_radius()
+        );
+    }
+
+    #[cfg(feature = "bots")]
+    #[test]
+    fn test_bot_names_are_in_bounds_and_disambiguated() {
+        let mut rng = SimpleRng::new(7);
+        for _ in 0..10_000 {
+            assert!(BOT_NAMES.contains(&random_bot_name(&mut rng)));
+        }
+
+        let taken = ["Snek", "Snek 2"];
+        assert_eq!(unique_bot_name("Snek", |n| taken.contains(&n)), "Snek 3");
+        assert_eq!(unique_bot_name("Viper", |n| taken.contains(&n)), "Viper");
+    }
+
+    #[cfg(feature = "bots")]
+    #[test]
+    fn test_bot_heading_changes_gradually() {
+        let mut bot = Snake::new(1, 10_000.0, 10_000.0, String::new(), 0, 10, 0);
+        bot.is_bot = true;
+        let draws = [0.1, 0.9, 0.5, 0.8, 0.2, 0.05];
+        let mut next = 0;
+        let mut rng = || {
+            next += 1;
+            draws[next % draws.len()]
+        };
+
+        let max_step = bot.max_turn_per_step();
+        let max_target_drift = max_step / snake_consts::ROT_STEP_INTERVAL_MS as f32 * 8.0;
+        let (start, mut angle_changes) = (bot.angle, 0);
+        for _ in 0..500 {
+            let (angle, target) = (bot.angle, bot.target_angle);
+            bot.tick_ai(8, &mut rng);
+            bot.tick(8, 21600.0, BoundaryBehavior::Kill);
+            assert!(angle_difference(target, bot.target_angle).abs() <= max_target_drift + 1e-6);
+            assert!(angle_difference(angle, bot.angle).abs() <= max_step + 1e-6);
+            angle_changes += (bot.angle != angle) as usize;
+        }
+
+        assert!(angle_changes > 500 * 8 / snake_consts::AI_STEP_INTERVAL_MS as usize);
+        assert!(angle_difference(start, bot.angle).abs() > max_step);
+    }
+
     #[test]
     fn test_snake_movement() {
-        let mut snake = Snake::new(1, 1000.0, 1000.0, "Test".to_string(), 0, 10);
+        let mut snake = Snake::new(1, 1000.0, 1000.0, "Test".to_string(), 0, 10, 0);
         snake.angle = 0.0;
         snake.target_angle = 0.0;
 
         let (initial_x, _) = snake.head_pos();
-        snake.tick(100, 21600.0);
+        snake.tick(100, 21600.0, BoundaryBehavior::Kill);
         let (new_x, _) = snake.head_pos();
 
         assert!(new_x > initial_x);
     }
 
+    #[test]
+    fn test_relative_moves_reconstruct_absolute_position() {
+        let mut snake = Snake::new(1, 1000.0, 1000.0, "Test".to_string(), 0, 10, 0);
+        snake.angle = 0.3;
+        snake.target_angle = 2.0;
+
+        let (x0, y0) = snake.head_pos_u16();
+        let (mut x, mut y) = (x0 as i32, y0 as i32);
+        for tick in 0..1000 {
+            if tick % 150 == 0 {
+                snake.target_angle = normalize_angle(snake.target_angle + 1.7);
+            }
+            snake.tick(8, 21600.0, BoundaryBehavior::Kill);
+
+            let (dx, dy) = snake.head_delta();
+            assert!(RELATIVE_COORD_RANGE.contains(&dx) && RELATIVE_COORD_RANGE.contains(&dy));
+            x += dx as i32;
+            y += dy as i32;
+
+            let (ax, ay) = snake.head_pos_u16();
+            assert_eq!((x, y), (ax as i32, ay as i32), "desync at tick {}", tick);
+        }
+    }
+
+    #[test]
+    fn test_body_shape_does_not_depend_on_frame_time() {
+        let run = |frame_time_ms: u64| {
+            let mut snake = Snake::new(1, 10_000.0, 10_000.0, "Shape".to_string(), 0, 30, 0);
+            snake.movement_substep_ms = 8;
+            snake.target_angle = normalize_angle(snake.angle + 2.5);
+            for _ in 0..960 / frame_time_ms {
+                snake.tick(frame_time_ms, 21600.0, BoundaryBehavior::Kill);
+            }
+            snake
+        };
+        let (fine, coarse) = (run(8), run(16));
+
+        assert!((fine.angle - coarse.angle).abs() < 1e-4);
+        for (a, b) in fine.body.iter().zip(&coarse.body) {
+            let close = (a.x - b.x).abs() < 0.01 && (a.y - b.y).abs() < 0.01;
+            assert!(close, "{:?} vs {:?}", (a.x, a.y), (b.x, b.y));
+        }
+    }
+
+    #[test]
+    fn test_large_head_jump_saturates_instead_of_wrapping() {
+        let mut snake = Snake::new(1, 1000.0, 1000.0, "Jump".to_string(), 0, 10, 0);
+        for part in snake.body.iter_mut() {
+            part.x += 40_000.0;
+        }
+        snake.tick(8, 100_000.0, BoundaryBehavior::Clamp);
+
+        let (dx, _) = snake.head_delta();
+        assert_eq!(dx, i16::MAX);
+        assert!(!RELATIVE_COORD_RANGE.contains(&dx));
+    }
+
+    #[test]
+    fn test_death_drop_scales_with_mass() {
+        let mut rng_state = 0.37f32;
+        let mut rng = || {
+            rng_state = (rng_state * 9301.0 + 0.49297).fract();
+            rng_state
+        };
+
+        let mut small = Snake::new(1, 5000.0, 5000.0, "Small".to_string(), 0, 10, 0);
+        small.kill(2, &FoodPalette::default(), &mut rng);
+        let small_value: u32 = small.foods_spawned.iter().map(|f| f.value(2) as u32).sum();
+
+        let mut big = Snake::new(2, 5000.0, 5000.0, "Big".to_string(), 0, 300, 0);
+        big.fullness = 40_000;
+        let expected = big.death_drop_value();
+        let palette = FoodPalette {
+            colors: vec![3, 9],
+            ..Default::default()
+        };
+        big.kill(2, &palette, &mut rng);
+        let big_value: u32 = big.foods_spawned.iter().map(|f| f.value(2) as u32).sum();
+
+        assert!((big_value as f32 - expected as f32).abs() <= expected as f32 * 0.02);
+        assert!(big.foods_spawned.len() <= big.body.len());
+        assert!(big.foods_spawned.iter().any(|f| f.size > snake_consts::DEATH_FOOD_MIN_SIZE));
+        assert!(big.foods_spawned.iter().all(|f| palette.colors.contains(&f.color)));
+
+        let mass_ratio = big.mass() as f32 / small.mass() as f32;
+        let value_ratio = big_value as f32 / small_value as f32;
+        assert!((value_ratio / mass_ratio - 1.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn test_long_snakes_turn_slower() {
+        let short = Snake::new(1, 5000.0, 5000.0, "Short".to_string(), 0, 10, 0);
+        let long = Snake::new(2, 5000.0, 5000.0, "Long".to_string(), 0, 400, 0);
+        assert!(long.max_turn_per_step() < short.max_turn_per_step());
+
+        let turn_once = |mut snake: Snake| {
+            snake.angle = 0.0;
+            snake.target_angle = PI / 2.0;
+            snake.tick(snake_consts::ROT_STEP_INTERVAL_MS, 21600.0, BoundaryBehavior::Kill);
+            snake.angle
+        };
+        assert!(turn_once(long) < turn_once(short));
+
+        assert!((turn_factor_for_scale(1.0) - 1.0).abs() < 1e-6);
+        assert!(turn_factor_for_scale(snake_consts::MAX_SCALE) > snake_consts::TURN_FACTOR_MIN);
+    }
+
+    #[test]
+    fn test_radius_matches_client_reference() {
+        let cases = [
+            (2, 1.0, 14.5),
+            (10, 1.0755, 15.59),
+            (108, 2.0, 29.0),
+            (320, 4.0, 58.0),
+            (2000, 6.0, 87.0),
+        ];
+
+        for (parts, scale, radius) in cases {
+            let snake = Snake::new(1, 1000.0, 1000.0, "Test".to_string(), 0, parts, 0);
+            assert!((snake.scale() - scale).abs() < 0.001, "scale at {} parts", parts);
+            assert!((snake.body_radius() - radius).abs() < 0.01, "radius at {} parts", parts);
+        }
+    }
+
     #[test]
     fn test_snake_eat_food() {
-        let mut snake = Snake::new(1, 1000.0, 1000.0, "Test".to_string(), 0, 10);
+        let mut snake = Snake::new(1, 1000.0, 1000.0, "Test".to_string(), 0, 10, 0);
         let initial_fullness = snake.fullness;
 
         let food = Food::new(1000, 1000, 10, 0);
-        snake.eat_food(food);
+        snake.eat_food(food, 2, 1.0);
 
         assert!(snake.fullness > initial_fullness);
     }
+
+    #[test]
+    fn test_boost_stops_at_min_length() {
+        let mut floor = Snake::new(1, 5000.0, 5000.0, "Floor".to_string(), 0, 2, 0);
+        floor.min_length = 2;
+        floor.set_accelerating(true);
+        assert!(!floor.accelerating);
+
+        let mut snake = Snake::new(2, 5000.0, 5000.0, "Boost".to_string(), 0, 3, 0);
+        snake.min_length = 2;
+        snake.set_accelerating(true);
+        assert!(snake.accelerating);
+
+        for _ in 0..20 {
+            snake.tick(8, 100_000.0, BoundaryBehavior::Kill);
+            assert!(snake.body.len() >= 2);
+        }
+        assert_eq!(snake.body.len(), 2);
+        assert!(!snake.accelerating);
+        assert!(snake.fullness < snake_consts::BOOST_COST);
+    }
+
+    #[test]
+    fn test_fullness_saturates_instead_of_wrapping() {
+        let mut snake = Snake::new(1, 5000.0, 5000.0, "Glutton".to_string(), 0, 3, 0);
+        snake.fullness = snake_consts::MAX_FULLNESS - 5;
+        for _ in 0..1000 {
+            snake.eat_food(Food::new(5000, 5000, u8::MAX, 0), u16::MAX, 1.0);
+        }
+        assert_eq!(snake.fullness, snake_consts::MAX_FULLNESS);
+        assert_eq!(snake.fam(), 1.0);
+    }
+
+    #[test]
+    fn test_out_of_range_speed_is_clamped() {
+        let mut snake = Snake::new(1, 5000.0, 5000.0, "Fast".to_string(), 0, 3, 0);
+        snake.speed = 100_000.0;
+        snake.tick(8, 5000.0, BoundaryBehavior::Kill);
+        assert_eq!(snake.speed, snake_consts::BOOST_SPEED as f32);
+
+        let mut writer = crate::protocol::writer::PacketWriter::new();
+        writer.write_speed(snake.speed);
+        assert_eq!(writer.as_bytes(), &[252]);
+
+        snake.speed = -10.0;
+        snake.tick(8, 5000.0, BoundaryBehavior::Kill);
+        assert_eq!(snake.speed, snake_consts::BASE_MOVE_SPEED as f32);
+    }
+
+    fn snake_at_edge() -> Snake {
+        let mut snake = Snake::new(1, 1900.0, 1000.0, "Edge".to_string(), 0, 10, 0);
+        snake.angle = 0.0;
+        snake.target_angle = 0.0;
+        snake
+    }
+
+    #[test]
+    fn test_clamp_boundary_keeps_snake_inside() {
+        let mut snake = snake_at_edge();
+        for _ in 0..200 {
+            snake.tick(8, 1000.0, BoundaryBehavior::Clamp);
+            let (hx, hy) = snake.head_pos();
+            let dist = ((hx - 1000.0).powi(2) + (hy - 1000.0).powi(2)).sqrt();
+            assert!(dist <= 980.0 + 0.01, "head escaped to {}", dist);
+        }
+        assert!(!snake.dying);
+        assert!(!snake.dead);
+    }
+
+    #[test]
+    fn test_bounce_boundary_turns_snake_inward() {
+        let mut snake = snake_at_edge();
+        for _ in 0..100 {
+            snake.tick(8, 1000.0, BoundaryBehavior::Bounce);
+        }
+        assert!(!snake.dying);
+        assert!(snake.angle.cos() < 0.0);
+        assert!(snake.head_pos().0 < 1980.0);
+    }
+
+    #[test]
+    fn test_kill_boundary_marks_snake_dying() {
+        let mut snake = snake_at_edge();
+        for _ in 0..100 {
+            snake.tick(8, 1000.0, BoundaryBehavior::Kill);
+        }
+        assert!(snake.dying);
+    }
+
+    #[test]
+    fn test_single_large_step_across_the_boundary_is_caught() {
+        let mut snake = Snake::new(1, 1000.0, 1000.0, "Leap".to_string(), 0, 10, 0);
+        snake.angle = 0.0;
+        snake.target_angle = 0.0;
+        let steps_across = (2000.0 / snake.speed * 1000.0) as u64;
+        snake.tick(steps_across, 1000.0, BoundaryBehavior::Kill);
+        assert!(snake.head_pos().0 > 2000.0);
+        assert!(snake.out_of_bounds && snake.dying);
+    }
+
+    #[test]
+    fn test_boundary_warning_sets_dying_before_the_edge() {
+        let mut snake = snake_at_edge();
+        snake.warn_near_boundary(1000.0, 50.0);
+        assert!(!snake.dying);
+
+        snake.warn_near_boundary(1000.0, 100.0);
+        assert!(snake.dying && snake.changes.is_dying());
+        assert!(!snake.out_of_bounds);
+
+        snake.body.front_mut().unwrap().x = 1500.0;
+        snake.warn_near_boundary(1000.0, 100.0);
+        assert!(!snake.dying);
+    }
 }
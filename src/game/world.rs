@@ -1,14 +1,79 @@
 
 
-use crate::config::GameConfig;
-use crate::game::food::Food;
-use crate::game::math::SimpleRng;
-use crate::game::sector::SectorGrid;
-use crate::game::snake::{random_bot_name, Snake};
+use crate::config::{world_consts, GameConfig, HeadOnRule};
+use crate::game::food::{Food, FoodPalette};
+use crate::game::hall_of_fame::HallOfFame;
+use crate::game::math::{angle_difference, random_point_in_circle, BoundingBox, SimpleRng};
+use crate::game::sector::{SectorGrid, SectorRange};
+#[cfg(feature = "bots")]
+use crate::game::snake::{random_bot_name, unique_bot_name};
+use crate::game::snake::Snake;
 use crate::protocol::types::SnakeId;
 use parking_lot::RwLock;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tracing::{info, warn};
+
+
+const RNG_STREAM_FOOD: u64 = 1;
+const RNG_STREAM_SPAWN: u64 = 2;
+#[cfg(feature = "bots")]
+const RNG_STREAM_AI: u64 = 3;
+
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StepSummary {
+   
+    pub ticks: u32,
+   
+    pub spawned: Vec<SnakeId>,
+   
+    pub died: Vec<SnakeId>,
+   
+    pub eaten: Vec<(SnakeId, Food)>,
+   
+    pub food_spawned: usize,
+}
+
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnakeInfo {
+    pub id: SnakeId,
+    pub name: String,
+    pub skin: u8,
+    pub x: f32,
+    pub y: f32,
+    pub angle: f32,
+    pub speed: f32,
+    pub length: usize,
+    pub score: u32,
+    pub kills: u32,
+    pub is_bot: bool,
+    pub accelerating: bool,
+    pub dead: bool,
+}
+
+impl From<&Snake> for SnakeInfo {
+    fn from(snake: &Snake) -> Self {
+        let (x, y) = snake.head_pos();
+        Self {
+            id: snake.id,
+            name: snake.name.clone(),
+            skin: snake.skin,
+            x,
+            y,
+            angle: snake.angle,
+            speed: snake.speed,
+            length: snake.length(),
+            score: snake.score(),
+            kills: snake.kills,
+            is_bot: snake.is_bot,
+            accelerating: snake.accelerating,
+            dead: snake.dead,
+        }
+    }
+}
 
 
 pub struct World {
@@ -19,141 +84,279 @@ pub struct World {
    
     pub sectors: SectorGrid,
    
-    next_snake_id: SnakeId,
+    next_snake_id: u32,
+   
+    free_snake_ids: VecDeque<SnakeId>,
    
     pub tick_count: u64,
    
     pub frame_count: u32,
    
-    rng: SimpleRng,
+    food_palette: FoodPalette,
+   
+    food_rng: SimpleRng,
+   
+    spawn_rng: SimpleRng,
+   
+    #[cfg(feature = "bots")]
+    ai_rng: SimpleRng,
    
     changed_snakes: Vec<SnakeId>,
    
+    changed_by_sector: HashMap<(u8, u8), Vec<SnakeId>>,
+   
+    extents_by_sector: HashMap<(u8, u8), Vec<SnakeId>>,
+   
     dead_snakes: Vec<SnakeId>,
    
     new_food: Vec<Food>,
    
+    pending_food: Vec<Food>,
+   
     eaten_food: Vec<(SnakeId, Food)>,
+   
+    spawned_snakes: Vec<SnakeId>,
+   
+    processed_deaths: Vec<SnakeId>,
+   
+    corpses: VecDeque<(SnakeId, u64)>,
+   
+    hall_of_fame: HallOfFame,
+   
+    hall_of_fame_dirty: bool,
 }
 
 impl World {
    
     pub fn new(config: GameConfig) -> Self {
-        let sector_count = config.sector_count_along_edge as u8;
+        let sector_count = match config.sector_grid_size() {
+            Ok(count) => count,
+            Err(e) => panic!("invalid world config: {}", e),
+        };
         let sectors = SectorGrid::new(sector_count, config.sector_size, 100);
+        let seed = config.rng_seed;
+        let hall_of_fame = HallOfFame::new(config.hall_of_fame_size);
 
         Self {
+            food_palette: FoodPalette::from_config(&config),
             config,
             snakes: HashMap::new(),
             sectors,
             next_snake_id: 1,
+            free_snake_ids: VecDeque::new(),
             tick_count: 0,
             frame_count: 0,
-            rng: SimpleRng::new(12345),
+            food_rng: SimpleRng::stream(seed, RNG_STREAM_FOOD),
+            spawn_rng: SimpleRng::stream(seed, RNG_STREAM_SPAWN),
+            #[cfg(feature = "bots")]
+            ai_rng: SimpleRng::stream(seed, RNG_STREAM_AI),
             changed_snakes: Vec::new(),
+            changed_by_sector: HashMap::new(),
+            extents_by_sector: HashMap::new(),
             dead_snakes: Vec::new(),
             new_food: Vec::new(),
+            pending_food: Vec::new(),
             eaten_food: Vec::new(),
+            spawned_snakes: Vec::new(),
+            processed_deaths: Vec::new(),
+            corpses: VecDeque::new(),
+            hall_of_fame,
+            hall_of_fame_dirty: false,
         }
     }
 
    
     pub fn init(&mut self) {
+        self.load_hall_of_fame();
         self.spawn_initial_food();
 
        
+        #[cfg(feature = "bots")]
         for _ in 0..self.config.initial_bots {
             self.spawn_bot();
         }
     }
 
    
+    fn load_hall_of_fame(&mut self) {
+        let Some(path) = &self.config.leaderboard_path else {
+            return;
+        };
+        match HallOfFame::load(path, self.config.hall_of_fame_size) {
+            Ok(hall) => {
+                info!("Loaded {} leaderboard entries from {}", hall.entries().len(), path.display());
+                self.hall_of_fame = hall;
+            }
+            Err(e) => warn!("Could not load leaderboard from {}: {}", path.display(), e),
+        }
+    }
+
+   
+    pub fn hall_of_fame(&self) -> &HallOfFame {
+        &self.hall_of_fame
+    }
+
+   
+    pub fn take_hall_of_fame_changes(&mut self) -> Option<HallOfFame> {
+        if !std::mem::take(&mut self.hall_of_fame_dirty) || self.config.leaderboard_path.is_none() {
+            return None;
+        }
+        Some(self.hall_of_fame.clone())
+    }
+
+   
     fn spawn_initial_food(&mut self) {
-        let game_radius = self.config.game_radius as f32;
-        let target_food = self.config.sector_count_along_edge as usize * 50;
+        let target_food = self.config.food_target();
 
         for _ in 0..target_food {
-            let food = Food::random(self.config.game_radius, &mut || self.rng.next_f32());
-            self.sectors.add_food(food);
+            if let Some(food) = self.spaced_random_food() {
+                self.sectors.add_food(food);
+            }
         }
     }
 
    
-    pub fn create_snake(&mut self, name: String, skin: u8) -> SnakeId {
-        let id = self.next_snake_id;
+    fn allocate_snake_id(&mut self) -> Option<SnakeId> {
+        if let Some(id) = self.free_snake_ids.pop_front() {
+            return Some(id);
+        }
+        if self.next_snake_id > SnakeId::MAX as u32 {
+            return None;
+        }
+        let id = self.next_snake_id as SnakeId;
         self.next_snake_id += 1;
+        Some(id)
+    }
 
+   
+    pub fn create_snake(&mut self, name: String, skin: u8) -> Option<SnakeId> {
        
-        let (x, y) = self.find_safe_spawn();
-
-        let start_length = self.config.human_snake_start_score as usize + 5;
-        let mut snake = Snake::new(id, x, y, name, skin, start_length);
+        let (x, y) = self.find_safe_spawn()?;
+        let id = self.allocate_snake_id()?;
+
+        let mut snake = Snake::new(
+            id,
+            x,
+            y,
+            name,
+            skin,
+            self.config.human_start_parts.max(self.config.snake_min_length) as usize,
+            self.config.human_start_fullness,
+        );
+        snake.min_length = self.config.snake_min_length as usize;
+        snake.movement_substep_ms = self.config.movement_substep_ms;
 
        
         self.sectors.add_snake(id, x, y);
 
         self.snakes.insert(id, snake);
-        self.changed_snakes.push(id);
+        self.mark_changed(id, x, y);
+        self.index_extent(id);
 
-        id
+        Some(id)
     }
 
    
-    pub fn spawn_bot(&mut self) -> SnakeId {
-        let id = self.next_snake_id;
-        self.next_snake_id += 1;
-
-        let (x, y) = self.find_safe_spawn();
-        let name = random_bot_name(&mut || self.rng.next_f32());
-        let skin = (self.rng.next_f32() * 9.0) as u8;
-
-        let start_length = self.config.bot_snake_start_score as usize + 5;
-        let mut snake = Snake::new(id, x, y, name, skin, start_length);
+    #[cfg(feature = "bots")]
+    pub fn spawn_bot(&mut self) -> Option<SnakeId> {
+        let (x, y) = self.find_safe_spawn()?;
+        let id = self.allocate_snake_id()?;
+
+        let base = random_bot_name(&mut self.spawn_rng);
+        let name = if self.config.unique_bot_names {
+            unique_bot_name(base, |name| {
+                self.snakes.values().any(|s| s.is_bot && !s.dead && s.name == name)
+            })
+        } else {
+            base.to_string()
+        };
+        let skin = (self.spawn_rng.next_f32() * 9.0) as u8;
+
+        let mut snake = Snake::new(
+            id,
+            x,
+            y,
+            name,
+            skin,
+            self.config.bot_start_parts.max(self.config.snake_min_length) as usize,
+            self.config.bot_start_fullness,
+        );
         snake.is_bot = true;
+        snake.min_length = self.config.snake_min_length as usize;
+        snake.movement_substep_ms = self.config.movement_substep_ms;
 
         self.sectors.add_snake(id, x, y);
         self.snakes.insert(id, snake);
-        self.changed_snakes.push(id);
+        self.mark_changed(id, x, y);
+        self.index_extent(id);
 
-        id
+        Some(id)
     }
 
    
-    fn find_safe_spawn(&mut self) -> (f32, f32) {
-        let game_radius = self.config.game_radius as f32;
-        let spawn_radius = game_radius * 0.8;
-
-        for _ in 0..100 {
-            let angle = self.rng.next_f32() * std::f32::consts::PI * 2.0;
-            let r = self.rng.next_f32().sqrt() * spawn_radius;
+    fn random_playable_point(config: &GameConfig, margin: f32, rng: &mut SimpleRng) -> (f32, f32) {
+        let center = config.game_radius as f32;
+        let radius = config.playable_radius(margin);
+        random_point_in_circle(center, center, radius, &mut || rng.next_f32())
+    }
 
-            let x = game_radius + r * angle.cos();
-            let y = game_radius + r * angle.sin();
+   
+    fn random_food(&mut self) -> Food {
+        let (x, y) =
+            Self::random_playable_point(&self.config, world_consts::FOOD_EDGE_MARGIN, &mut self.food_rng);
+        Food::random_at(x as u16, y as u16, &self.food_palette, &mut || self.food_rng.next_f32())
+    }
 
-            if self.is_location_safe(x, y, 100.0) {
-                return (x, y);
+   
+    fn spaced_random_food(&mut self) -> Option<Food> {
+        let spacing = self.config.min_food_spacing;
+        if spacing <= 0.0 {
+            return Some(self.random_food());
+        }
+        for _ in 0..world_consts::FOOD_SPACING_ATTEMPTS {
+            let food = self.random_food();
+            let (x, y) = (food.x as f32, food.y as f32);
+            let crowded = self.sectors.food_near(x, y, spacing).into_iter().any(|other| {
+                let (dx, dy) = (other.x as f32 - x, other.y as f32 - y);
+                dx * dx + dy * dy < spacing * spacing
+            });
+            if !crowded {
+                return Some(food);
             }
         }
-
-       
-        (game_radius, game_radius)
+        None
     }
 
    
-    fn is_location_safe(&self, x: f32, y: f32, radius: f32) -> bool {
-        let nearby_snakes = self.sectors.snakes_near(x, y, radius * 2.0);
-
-        for &snake_id in &nearby_snakes {
-            if let Some(snake) = self.snakes.get(&snake_id) {
-                let (hx, hy) = snake.head_pos();
-                let dist_sq = (x - hx).powi(2) + (y - hy).powi(2);
-                if dist_sq < radius * radius {
-                    return false;
+    fn find_safe_spawn(&mut self) -> Option<(f32, f32)> {
+        let tiers = [
+            (world_consts::SPAWN_EDGE_MARGIN, world_consts::SPAWN_CLEARANCE),
+            (world_consts::FOOD_EDGE_MARGIN, world_consts::SPAWN_CLEARANCE),
+            (world_consts::FOOD_EDGE_MARGIN, world_consts::MIN_SPAWN_CLEARANCE),
+        ];
+
+        for (margin, clearance) in tiers {
+            for _ in 0..world_consts::SPAWN_ATTEMPTS_PER_TIER {
+                let (x, y) = Self::random_playable_point(&self.config, margin, &mut self.spawn_rng);
+                if self.is_location_safe(x, y, clearance) {
+                    return Some((x, y));
                 }
             }
         }
 
-        true
+       
+        let center = self.config.game_radius as f32;
+        self.is_location_safe(center, center, world_consts::MIN_SPAWN_CLEARANCE)
+            .then_some((center, center))
+    }
+
+   
+    fn is_location_safe(&self, x: f32, y: f32, radius: f32) -> bool {
+        !self
+            .snakes_overlapping(x, y, radius)
+            .into_iter()
+            .flat_map(|snake| snake.body.iter())
+            .any(|part| (x - part.x).powi(2) + (y - part.y).powi(2) < radius * radius)
     }
 
    
@@ -161,6 +364,7 @@ impl World {
         if let Some(snake) = self.snakes.remove(&id) {
             let (hx, hy) = snake.head_pos();
             self.sectors.remove_snake(id, hx, hy);
+            self.free_snake_ids.push_back(id);
         }
     }
 
@@ -175,39 +379,199 @@ impl World {
     }
 
    
+   
+    pub fn visible_snakes(&self, center: (f32, f32), radius: f32) -> impl Iterator<Item = &Snake> + '_ {
+        let mut ids: Vec<_> = self.sectors.snakes_near(center.0, center.1, radius).into_iter().collect();
+        ids.sort_unstable();
+        ids.into_iter().filter_map(|id| self.snakes.get(&id)).filter(move |snake| {
+            let (x, y) = snake.head_pos();
+            !snake.dead && (x - center.0).abs() < radius && (y - center.1).abs() < radius
+        })
+    }
+
+   
     pub fn snakes(&self) -> &HashMap<SnakeId, Snake> {
         &self.snakes
     }
 
    
+    pub fn obstacle_ahead(
+        &self,
+        head_x: f32,
+        head_y: f32,
+        angle: f32,
+        distance: f32,
+        cone_rad: f32,
+    ) -> Option<(SnakeId, f32)> {
+        self.nearest_obstacle(head_x, head_y, angle, distance, cone_rad, None)
+            .map(|(id, dist, _)| (id, dist))
+    }
+
+   
+    fn nearest_obstacle(
+        &self,
+        head_x: f32,
+        head_y: f32,
+        angle: f32,
+        distance: f32,
+        cone_rad: f32,
+        ignore: Option<SnakeId>,
+    ) -> Option<(SnakeId, f32, f32)> {
+        let mut nearest: Option<(SnakeId, f32, f32)> = None;
+        for snake in self.snakes_overlapping(head_x, head_y, distance) {
+            if snake.dead || Some(snake.id) == ignore {
+                continue;
+            }
+            for part in &snake.body {
+                let (dx, dy) = (part.x - head_x, part.y - head_y);
+                let dist = dx.hypot(dy);
+                if dist > distance || nearest.is_some_and(|(_, best, _)| dist >= best) {
+                    continue;
+                }
+                let offset = angle_difference(angle, dy.atan2(dx));
+                if offset.abs() <= cone_rad {
+                    nearest = Some((snake.id, dist, offset));
+                }
+            }
+        }
+        nearest
+    }
+
+   
+    fn snakes_overlapping(&self, x: f32, y: f32, radius: f32) -> Vec<&Snake> {
+        let Some(range) = self.sectors.sector_range_in_viewport(x, y, radius) else {
+            return Vec::new();
+        };
+        let mut ids: Vec<_> = (range.min_y..=range.max_y)
+            .flat_map(|sy| (range.min_x..=range.max_x).map(move |sx| (sx, sy)))
+            .filter_map(|sector| self.extents_by_sector.get(&sector))
+            .flatten()
+            .copied()
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+
+        let reach = BoundingBox::new(x, y, radius);
+        ids.into_iter()
+            .filter_map(|id| self.snakes.get(&id))
+            .filter(|snake| snake.bounding_box.intersects(&reach))
+            .collect()
+    }
+
+   
+    fn index_extent(&mut self, id: SnakeId) {
+        let Some(bounds) = self.snakes.get(&id).map(|snake| snake.bounding_box) else {
+            return;
+        };
+        let Some(range) = self.sectors.sector_range_in_viewport(bounds.x, bounds.y, bounds.radius) else {
+            return;
+        };
+        for sy in range.min_y..=range.max_y {
+            for sx in range.min_x..=range.max_x {
+                self.extents_by_sector.entry((sx, sy)).or_default().push(id);
+            }
+        }
+    }
+
+   
+    fn index_extents(&mut self) {
+        self.extents_by_sector.values_mut().for_each(Vec::clear);
+        let ids: Vec<_> = self.snakes.iter().filter(|(_, s)| !s.dead).map(|(id, _)| *id).collect();
+        for id in ids {
+            self.index_extent(id);
+        }
+    }
+
+   
+    #[cfg(feature = "bots")]
+    fn bot_danger(&self, id: SnakeId) -> Option<f32> {
+        let snake = self.snakes.get(&id).filter(|s| s.is_bot && !s.dead)?;
+        let (x, y) = snake.head_pos();
+        self.nearest_obstacle(
+            x,
+            y,
+            snake.angle,
+            crate::config::snake_consts::BOT_LOOKAHEAD_DISTANCE,
+            crate::config::snake_consts::BOT_LOOKAHEAD_CONE,
+            Some(id),
+        )
+        .map(|(_, _, offset)| offset)
+    }
+
+   
+    pub fn snake_info(&self, id: SnakeId) -> Option<SnakeInfo> {
+        self.snakes.get(&id).map(SnakeInfo::from)
+    }
+
+   
+    pub fn all_snake_info(&self) -> Vec<SnakeInfo> {
+        let mut info: Vec<_> = self.snakes.values().map(SnakeInfo::from).collect();
+        info.sort_unstable_by_key(|snake| snake.id);
+        info
+    }
+
+   
     pub fn snake_count(&self) -> usize {
         self.snakes.len()
     }
 
    
-    pub fn tick(&mut self, dt_ms: u64) {
+    fn begin_tick(&mut self) {
         self.tick_count += 1;
         self.frame_count = self.frame_count.wrapping_add(1);
 
         self.changed_snakes.clear();
+        self.changed_by_sector.values_mut().for_each(Vec::clear);
         self.dead_snakes.clear();
         self.new_food.clear();
+        self.new_food.append(&mut self.pending_food);
         self.eaten_food.clear();
+        self.spawned_snakes.clear();
+        self.processed_deaths.clear();
+        self.index_extents();
+    }
+
+   
+   
+    pub fn tick_paused(&mut self) {
+        self.begin_tick();
+    }
+
+   
+    pub fn tick(&mut self, dt_ms: u64) {
+        self.begin_tick();
+        self.reap_corpses();
 
         let game_radius = self.config.game_radius as f32;
 
        
-        let snake_ids: Vec<_> = self.snakes.keys().copied().collect();
+        let mut snake_ids: Vec<_> = self.snakes.keys().copied().collect();
+        snake_ids.sort_unstable();
         for id in snake_ids {
+            #[cfg(feature = "bots")]
+            let danger = self.bot_danger(id);
             if let Some(snake) = self.snakes.get_mut(&id) {
                 let (old_x, old_y) = snake.head_pos();
 
                
-                snake.tick(dt_ms, game_radius);
+                snake.tick(dt_ms, game_radius, self.config.boundary_behavior);
+                if snake.out_of_bounds && !snake.dead {
+                    snake.kill(
+                        self.config.food_value_multiplier,
+                        &self.food_palette,
+                        &mut || self.food_rng.next_f32(),
+                    );
+                } else if self.config.boundary_warning_distance > 0.0 {
+                    snake.warn_near_boundary(game_radius, self.config.boundary_warning_distance);
+                }
 
                
+                #[cfg(feature = "bots")]
                 if snake.is_bot {
-                    snake.tick_ai(dt_ms);
+                    snake.tick_ai(dt_ms, &mut || self.ai_rng.next_f32());
+                    if let Some(bearing_offset) = danger {
+                        snake.steer_away(bearing_offset);
+                    }
                 }
 
                 let (new_x, new_y) = snake.head_pos();
@@ -220,6 +584,8 @@ impl World {
                
                 if snake.changes.0 != 0 {
                     self.changed_snakes.push(id);
+                    let sector = self.sectors.world_to_sector(new_x, new_y);
+                    self.changed_by_sector.entry(sector).or_default().push(id);
                 }
 
                
@@ -242,6 +608,7 @@ impl World {
         self.process_dead_snakes();
 
        
+        #[cfg(feature = "bots")]
         if self.config.bot_respawn {
             self.respawn_bots();
         }
@@ -249,7 +616,8 @@ impl World {
 
    
     fn check_collisions(&mut self) {
-        let snake_ids: Vec<_> = self.snakes.keys().copied().collect();
+        let mut snake_ids: Vec<_> = self.snakes.keys().copied().collect();
+        snake_ids.sort_unstable();
 
         for i in 0..snake_ids.len() {
             let id1 = snake_ids[i];
@@ -258,8 +626,7 @@ impl World {
                 let id2 = snake_ids[j];
 
                
-                let collides_1_with_2;
-                let collides_2_with_1;
+                let (mut dies_1, mut dies_2);
 
                 {
                     let snake1 = self.snakes.get(&id1).unwrap();
@@ -269,41 +636,58 @@ impl World {
                         continue;
                     }
 
-                    collides_1_with_2 = snake1.collides_with(snake2);
-                    collides_2_with_1 = snake2.collides_with(snake1);
-                }
-
-               
-                if collides_1_with_2 {
-                    if let Some(snake) = self.snakes.get_mut(&id1) {
-                        snake.kill(&mut || self.rng.next_f32());
-                        self.dead_snakes.push(id1);
+                    dies_1 = snake1.collides_with(snake2);
+                    dies_2 = snake2.collides_with(snake1);
+
+                    if snake1.head_on_with(snake2) {
+                        (dies_1, dies_2) = match self.config.head_on_rule {
+                            HeadOnRule::BothDie => (true, true),
+                            HeadOnRule::LongerSurvives => {
+                                let (len1, len2) = (snake1.length(), snake2.length());
+                                (len1 <= len2, len2 <= len1)
+                            }
+                        };
                     }
+                }
 
-                   
-                    if let Some(killer) = self.snakes.get_mut(&id2) {
-                        killer.kills += 1;
-                    }
+                if dies_1 {
+                    self.kill_snake(id1, id2);
+                }
+                if dies_2 {
+                    self.kill_snake(id2, id1);
                 }
+            }
+        }
+    }
 
-                if collides_2_with_1 {
-                    if let Some(snake) = self.snakes.get_mut(&id2) {
-                        snake.kill(&mut || self.rng.next_f32());
-                        self.dead_snakes.push(id2);
-                    }
+   
+    fn kill_snake(&mut self, victim: SnakeId, killer: SnakeId) {
+        let value_multiplier = if self.config.kill_transfer_replaces_drop && self.config.kill_mass_transfer > 0.0 {
+            0
+        } else {
+            self.config.food_value_multiplier
+        };
+        let mut absorbed = 0.0;
+        if let Some(snake) = self.snakes.get_mut(&victim) {
+            absorbed = snake.fullness as f32 * self.config.kill_mass_transfer;
+            snake.kill(value_multiplier, &self.food_palette, &mut || self.food_rng.next_f32());
+            self.dead_snakes.push(victim);
+        }
 
-                   
-                    if let Some(killer) = self.snakes.get_mut(&id1) {
-                        killer.kills += 1;
-                    }
-                }
+       
+        if let Some(killer) = self.snakes.get_mut(&killer) {
+            killer.kills += 1;
+            if !killer.dead {
+                let growth_factor = self.config.growth_factor(killer.length());
+                killer.absorb((absorbed * growth_factor).round() as u32);
             }
         }
     }
 
    
     fn process_eating(&mut self) {
-        let snake_ids: Vec<_> = self.snakes.keys().copied().collect();
+        let mut snake_ids: Vec<_> = self.snakes.keys().copied().collect();
+        snake_ids.sort_unstable();
 
         for id in snake_ids {
             if let Some(snake) = self.snakes.get(&id) {
@@ -312,7 +696,7 @@ impl World {
                 }
 
                 let (hx, hy) = snake.head_pos();
-                let eat_radius = snake.body_radius() + 10.0;
+                let eat_radius = snake.eat_radius(self.config.eat_radius_multiplier);
 
                
                 let foods_to_eat: Vec<_> = self
@@ -330,7 +714,8 @@ impl World {
                 for food in foods_to_eat {
                     if let Some(removed) = self.sectors.remove_food(food.x, food.y) {
                         if let Some(snake) = self.snakes.get_mut(&id) {
-                            snake.eat_food(removed);
+                            let growth_factor = self.config.growth_factor(snake.length());
+                            snake.eat_food(removed, self.config.food_value_multiplier, growth_factor);
                             self.eaten_food.push((id, removed));
                         }
                     }
@@ -342,13 +727,15 @@ impl World {
    
     fn spawn_food(&mut self) {
         let current_food = self.sectors.total_food();
-        let target_food = self.config.sector_count_along_edge as usize * 50;
+        let target_food = self.config.food_target();
 
         if current_food < target_food {
             let spawn_count = self.config.food_spawn_rate.min((target_food - current_food) as u16);
 
             for _ in 0..spawn_count {
-                let food = Food::random(self.config.game_radius, &mut || self.rng.next_f32());
+                let Some(food) = self.spaced_random_food() else {
+                    continue;
+                };
                 if self.sectors.add_food(food) {
                     self.new_food.push(food);
                 }
@@ -357,37 +744,125 @@ impl World {
     }
 
    
+    pub fn spawn_food_near(&mut self, x: f32, y: f32, count: u16) -> usize {
+        let mut spawned = 0;
+        for _ in 0..count.min(world_consts::MAX_FEED_COUNT) {
+            let food = Food::near(
+                x as u16,
+                y as u16,
+                world_consts::FEED_RADIUS,
+                &self.food_palette,
+                &mut || self.food_rng.next_f32(),
+            );
+            if self.sectors.add_food(food) {
+                self.pending_food.push(food);
+                spawned += 1;
+            }
+        }
+        spawned
+    }
+
+   
     fn process_dead_snakes(&mut self) {
         let dead_ids: Vec<_> = self.dead_snakes.drain(..).collect();
-
         for id in dead_ids {
-            if let Some(snake) = self.snakes.get(&id) {
-               
-                for food in &snake.foods_spawned {
-                    if self.sectors.add_food(*food) {
-                        self.new_food.push(*food);
-                    }
+            let Some(snake) = self.snakes.get_mut(&id) else {
+                continue;
+            };
+            if snake.death_processed {
+                continue;
+            }
+            snake.death_processed = true;
+            self.processed_deaths.push(id);
+            self.corpses.push_back((id, self.tick_count));
+
+           
+            for food in &snake.foods_spawned {
+                if self.sectors.add_food(*food) {
+                    self.new_food.push(*food);
                 }
             }
+
+            if !snake.is_bot {
+                let timestamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0);
+                self.hall_of_fame_dirty |= self.hall_of_fame.record(&snake.name, snake.score(), timestamp);
+            }
+        }
+    }
+
+   
+    fn reap_corpses(&mut self) {
+        while let Some(&(id, died_at)) = self.corpses.front() {
+            if self.tick_count - died_at < world_consts::DEATH_ANIMATION_TICKS {
+                break;
+            }
+            self.corpses.pop_front();
+            if self.snakes.get(&id).is_some_and(|s| s.dead) {
+                self.remove_snake(id);
+            }
         }
     }
 
    
+    #[cfg(feature = "bots")]
     fn respawn_bots(&mut self) {
         let bot_count = self.snakes.values().filter(|s| s.is_bot && !s.dead).count();
         let target = self.config.initial_bots as usize;
 
-        if bot_count < target {
-            self.spawn_bot();
+        if bot_count >= target {
+            return;
+        }
+        if let Some(id) = self.spawn_bot() {
+            self.spawned_snakes.push(id);
         }
     }
 
    
+    pub fn step(&mut self, n: u32) -> StepSummary {
+        let dt_ms = self.config.frame_time_ms;
+        let mut summary = StepSummary::default();
+
+        for _ in 0..n {
+            self.tick(dt_ms);
+            summary.ticks += 1;
+            summary.spawned.extend_from_slice(&self.spawned_snakes);
+            summary.died.extend_from_slice(&self.processed_deaths);
+            summary.eaten.extend_from_slice(&self.eaten_food);
+            summary.food_spawned += self.new_food.len();
+        }
+
+        summary
+    }
+
+   
+    pub fn died_snakes(&self) -> &[SnakeId] {
+        &self.processed_deaths
+    }
+
+   
     pub fn changed_snakes(&self) -> &[SnakeId] {
         &self.changed_snakes
     }
 
    
+    pub fn changed_snakes_in(&self, range: SectorRange) -> impl Iterator<Item = SnakeId> + '_ {
+        (range.min_y..=range.max_y)
+            .flat_map(move |y| (range.min_x..=range.max_x).map(move |x| (x, y)))
+            .filter_map(|sector| self.changed_by_sector.get(&sector))
+            .flatten()
+            .copied()
+    }
+
+    fn mark_changed(&mut self, id: SnakeId, x: f32, y: f32) {
+        self.changed_snakes.push(id);
+        let sector = self.sectors.world_to_sector(x, y);
+        self.changed_by_sector.entry(sector).or_default().push(id);
+    }
+
+   
     pub fn new_food(&self) -> &[Food] {
         &self.new_food
     }
@@ -431,6 +906,16 @@ impl World {
         let cell_size = game_diameter / grid_size as u32;
 
         let mut data = vec![0u8; (grid_size * grid_size / 8) as usize + 1];
+        let cell_index = |x: f32, y: f32| {
+            let grid_x = (x as u32 / cell_size).min(grid_size as u32 - 1) as usize;
+            let grid_y = (y as u32 / cell_size).min(grid_size as u32 - 1) as usize;
+            grid_y * grid_size as usize + grid_x
+        };
+        let mut mark = |bit_index: usize| {
+            if let Some(byte) = data.get_mut(bit_index / 8) {
+                *byte |= 1 << (bit_index % 8);
+            }
+        };
 
         for snake in self.snakes.values() {
             if snake.dead {
@@ -438,15 +923,26 @@ impl World {
             }
 
             let (hx, hy) = snake.head_pos();
-            let grid_x = (hx as u32 / cell_size).min(grid_size as u32 - 1) as u16;
-            let grid_y = (hy as u32 / cell_size).min(grid_size as u32 - 1) as u16;
+            mark(cell_index(hx, hy));
+        }
 
-            let bit_index = (grid_y * grid_size + grid_x) as usize;
-            let byte_index = bit_index / 8;
-            let bit_offset = bit_index % 8;
+        if self.config.minimap_food_hotspots {
+            let mut food_per_cell = vec![0usize; grid_size as usize * grid_size as usize];
+            for sector in self.sectors.iter() {
+                if sector.food.is_empty() {
+                    continue;
+                }
+                let (cx, cy) = sector.center(self.sectors.sector_size);
+                if let Some(count) = food_per_cell.get_mut(cell_index(cx, cy)) {
+                    *count += sector.food.len();
+                }
+            }
 
-            if byte_index < data.len() {
-                data[byte_index] |= 1 << bit_offset;
+            let threshold = self.config.minimap_food_threshold as usize;
+            for (bit_index, &count) in food_per_cell.iter().enumerate() {
+                if count >= threshold {
+                    mark(bit_index);
+                }
             }
         }
 
@@ -475,16 +971,183 @@ mod tests {
         assert_eq!(world.snake_count(), 0);
     }
 
+    #[test]
+    #[should_panic(expected = "sector_count_along_edge")]
+    fn test_world_rejects_oversized_sector_count() {
+        let config = GameConfig {
+            sector_count_along_edge: 300,
+            ..Default::default()
+        };
+        World::new(config);
+    }
+
+    #[test]
+    fn test_food_target_density_scales_initial_food() {
+        let mut sparse = World::new(GameConfig {
+            food_per_edge_sector: 10,
+            ..Default::default()
+        });
+        let mut rich = World::new(GameConfig {
+            food_per_edge_sector: 30,
+            ..Default::default()
+        });
+        sparse.init();
+        rich.init();
+
+        let sparse_food = sparse.sectors.total_food();
+        let rich_food = rich.sectors.total_food();
+        assert!(sparse_food > 0);
+        assert!(sparse_food <= sparse.config.food_target());
+        assert!(rich_food <= rich.config.food_target());
+
+        let ratio = rich_food as f32 / sparse_food as f32;
+        assert!((ratio - 3.0).abs() < 0.1, "ratio was {}", ratio);
+    }
+
+    #[test]
+    fn test_food_spawns_inside_death_radius() {
+        let mut world = World::new(GameConfig::default());
+        let center = world.config.game_radius as f32;
+        let death_radius = world.config.death_radius as f32;
+
+        for _ in 0..10_000 {
+            let food = world.random_food();
+            let dx = food.x as f32 - center;
+            let dy = food.y as f32 - center;
+            assert!((dx * dx + dy * dy).sqrt() < death_radius);
+        }
+    }
+
+    #[cfg(feature = "bots")]
+    #[test]
+    fn test_unique_bot_names_never_repeat_among_live_bots() {
+        let mut world = World::new(GameConfig {
+            unique_bot_names: true,
+            ..Default::default()
+        });
+        let count = crate::game::snake::BOT_NAMES.len() * 2;
+        for _ in 0..count {
+            world.spawn_bot().unwrap();
+        }
+
+        let names: std::collections::HashSet<_> = world.snakes.values().map(|s| s.name.clone()).collect();
+        assert_eq!(names.len(), count);
+    }
+
+    #[test]
+    fn test_min_food_spacing_keeps_spawned_food_apart() {
+        let spacing = 40.0;
+        let mut world = World::new(GameConfig {
+            min_food_spacing: spacing,
+            initial_bots: 0,
+            ..Default::default()
+        });
+        world.init();
+        for _ in 0..20 {
+            world.tick(world.config.frame_time_ms);
+        }
+
+        let foods: Vec<_> = world.sectors.iter().flat_map(|s| s.food.iter()).collect();
+        assert!(!foods.is_empty());
+        for food in foods {
+            let (x, y) = (food.x as f32, food.y as f32);
+            for other in world.sectors.food_near(x, y, spacing) {
+                if std::ptr::eq(food, other) {
+                    continue;
+                }
+                let (dx, dy) = (other.x as f32 - x, other.y as f32 - y);
+                assert!(dx * dx + dy * dy >= spacing * spacing, "{:?} and {:?} are too close", food, other);
+            }
+        }
+        assert!(GameConfig::builder().min_food_spacing(-1.0).build().is_err());
+    }
+
+    #[cfg(feature = "bots")]
+    #[test]
+    fn test_food_draws_do_not_shift_spawn_positions() {
+        let mut plain = World::new(GameConfig::default());
+        let mut with_food = World::new(GameConfig::default());
+
+        for _ in 0..25 {
+            with_food.random_food();
+        }
+
+        let a = plain.spawn_bot().unwrap();
+        let b = with_food.spawn_bot().unwrap();
+        assert_eq!(plain.get_snake(a).unwrap().head_pos(), with_food.get_snake(b).unwrap().head_pos());
+        assert_eq!(plain.get_snake(a).unwrap().name, with_food.get_snake(b).unwrap().name);
+
+        let mut reseeded = World::new(GameConfig {
+            rng_seed: 99,
+            ..Default::default()
+        });
+        let c = reseeded.spawn_bot().unwrap();
+        assert_ne!(plain.get_snake(a).unwrap().head_pos(), reseeded.get_snake(c).unwrap().head_pos());
+    }
+
+    #[cfg(feature = "bots")]
+    #[test]
+    fn test_start_parts_and_fullness_set_initial_score() {
+        let mut world = World::new(GameConfig {
+            human_start_parts: 20,
+            human_start_fullness: 16_777_215,
+            bot_start_parts: 4,
+            ..Default::default()
+        });
+
+        let human = world.create_snake("Human".to_string(), 0).unwrap();
+        let human = world.get_snake(human).unwrap();
+        assert_eq!(human.length(), 20);
+        assert_eq!(human.fullness, 16_777_215);
+       
+        assert_eq!(human.score(), 87);
+
+        let bot = world.spawn_bot().unwrap();
+        let bot = world.get_snake(bot).unwrap();
+        assert_eq!(bot.length(), 4);
+        assert_eq!(bot.fullness, 0);
+        assert_eq!(bot.score(), 7);
+    }
+
     #[test]
     fn test_snake_creation() {
         let config = GameConfig::default();
         let mut world = World::new(config);
 
-        let id = world.create_snake("Test".to_string(), 0);
+        let id = world.create_snake("Test".to_string(), 0).unwrap();
         assert!(world.get_snake(id).is_some());
         assert_eq!(world.snake_count(), 1);
     }
 
+    #[test]
+    fn test_snake_ids_are_recycled() {
+        let mut world = World::new(GameConfig::default());
+
+        let first = world.create_snake("A".to_string(), 0).unwrap();
+        let second = world.create_snake("B".to_string(), 0).unwrap();
+        world.remove_snake(first);
+
+        let reused = world.create_snake("C".to_string(), 0).unwrap();
+        assert_eq!(reused, first);
+        assert_ne!(reused, second);
+    }
+
+    #[cfg(feature = "bots")]
+    #[test]
+    fn test_snake_id_exhaustion_is_reported() {
+        let mut world = World::new(GameConfig::default());
+        world.next_snake_id = SnakeId::MAX as u32;
+
+        let last = world.create_snake("Last".to_string(), 0).unwrap();
+        assert_eq!(last, SnakeId::MAX);
+        assert!(world.create_snake("Overflow".to_string(), 0).is_none());
+        assert!(world.spawn_bot().is_none());
+        assert!(world.get_snake(0).is_none());
+
+        world.remove_snake(last);
+        assert_eq!(world.create_snake("Again".to_string(), 0), Some(last));
+    }
+
     #[test]
     fn test_world_tick() {
         let config = GameConfig::default();
@@ -496,4 +1159,427 @@ mod tests {
 
         assert!(world.tick_count > 0);
     }
+
+    fn packed_world(spacing: f32) -> World {
+        let config = GameConfig {
+            game_radius: 2000,
+            death_radius: 1900,
+            sector_count_along_edge: 9,
+            ..Default::default()
+        };
+        let mut world = World::new(config);
+        let mut next_id: SnakeId = 1000;
+        let mut y = 0.0;
+        while y <= 4000.0 {
+            let mut x = 0.0;
+            while x <= 4000.0 {
+                world.snakes.insert(next_id, Snake::new(next_id, x, y, String::new(), 0, 1, 0));
+                world.sectors.add_snake(next_id, x, y);
+                next_id += 1;
+                x += spacing;
+            }
+            y += spacing;
+        }
+        world.index_extents();
+        world
+    }
+
+    #[test]
+    fn test_spawn_relaxes_search_in_crowded_world() {
+        let mut world = packed_world(140.0);
+        let id = world.create_snake("Late".to_string(), 0).unwrap();
+        let (x, y) = world.get_snake(id).unwrap().head_pos();
+
+        let nearest = world
+            .snakes
+            .values()
+            .filter(|s| s.id != id)
+            .flat_map(|s| s.body.iter())
+            .map(|p| ((p.x - x).powi(2) + (p.y - y).powi(2)).sqrt())
+            .fold(f32::MAX, f32::min);
+        assert!(nearest >= world_consts::MIN_SPAWN_CLEARANCE, "spawned {} from a snake", nearest);
+    }
+
+    #[cfg(feature = "bots")]
+    #[test]
+    fn test_spawn_fails_when_no_room_left() {
+        let mut world = packed_world(40.0);
+        let before = world.snake_count();
+        assert!(world.create_snake("Late".to_string(), 0).is_none());
+        assert!(world.spawn_bot().is_none());
+        assert_eq!(world.snake_count(), before);
+        assert_eq!(world.allocate_snake_id(), Some(1));
+    }
+
+    #[test]
+    fn test_deaths_update_persistent_leaderboard() {
+        let path = std::env::temp_dir().join(format!("slither_world_hof_{}.json", std::process::id()));
+        let config = GameConfig {
+            leaderboard_path: Some(path.clone()),
+            hall_of_fame_size: 2,
+            ..Default::default()
+        };
+        let mut world = World::new(config.clone());
+
+        for (name, parts) in [("short", 12), ("long", 40), ("mid", 25)] {
+            let id = world.create_snake(name.to_string(), 0).unwrap();
+            let snake = world.get_snake_mut(id).unwrap();
+            while snake.length() < parts {
+                snake.body.push_back(snake.body.back().copied().unwrap());
+            }
+            snake.kill(0, &FoodPalette::default(), &mut || 0.5);
+            world.dead_snakes.push(id);
+            world.dead_snakes.push(id);
+            world.process_dead_snakes();
+        }
+
+        assert!(!path.exists());
+        let changes = world.take_hall_of_fame_changes().unwrap();
+        assert!(world.take_hall_of_fame_changes().is_none());
+        changes.save(&path).unwrap();
+
+        let mut reloaded = World::new(config);
+        reloaded.load_hall_of_fame();
+        std::fs::remove_file(&path).unwrap();
+
+        let names: Vec<_> = reloaded.hall_of_fame().entries().iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["long", "mid"]);
+        assert_eq!(reloaded.hall_of_fame(), world.hall_of_fame());
+    }
+
+    #[test]
+    fn test_crossing_the_kill_boundary_kills_the_snake() {
+        let mut world = World::new(GameConfig {
+            boundary_warning_distance: 300.0,
+            ..Default::default()
+        });
+        let id = world.create_snake("edge".to_string(), 0).unwrap();
+        let center = world.config.game_radius as f32;
+        let limit = center * 0.98;
+        let snake = world.get_snake_mut(id).unwrap();
+        let (old_x, old_y) = snake.head_pos();
+        for (i, part) in snake.body.iter_mut().enumerate() {
+            part.x = center + limit - 200.0 - i as f32 * 10.0;
+            part.y = center;
+        }
+        snake.angle = 0.0;
+        snake.target_angle = 0.0;
+        let (new_x, new_y) = snake.head_pos();
+        world.sectors.remove_snake(id, old_x, old_y);
+        world.sectors.add_snake(id, new_x, new_y);
+
+        world.tick(8);
+        let snake = world.get_snake(id).unwrap();
+        assert!(snake.dying && !snake.dead);
+
+        for _ in 0..500 {
+            world.tick(8);
+            if world.get_snake(id).is_none_or(|snake| snake.dead) {
+                return;
+            }
+        }
+        panic!("snake survived crossing the boundary");
+    }
+
+    #[cfg(not(feature = "bots"))]
+    #[test]
+    fn test_no_bot_build_never_spawns_or_steers_bots() {
+        let mut world = World::new(GameConfig {
+            initial_bots: 10,
+            bot_respawn: true,
+            ..Default::default()
+        });
+        world.init();
+        assert_eq!(world.snake_count(), 0);
+
+        let id = world.create_snake("pvp".to_string(), 0).unwrap();
+        let snake = world.get_snake_mut(id).unwrap();
+        snake.is_bot = true;
+        let target = snake.target_angle;
+        world.step(500);
+
+        assert_eq!(world.snake_count(), 1);
+        assert_eq!(world.get_snake(id).unwrap().target_angle, target);
+    }
+
+    #[cfg(feature = "bots")]
+    #[test]
+    fn test_seeded_step_is_deterministic() {
+        let run = || {
+            let mut world = World::new(GameConfig {
+                game_radius: 1500,
+                death_radius: 1400,
+                sector_size: 300,
+                sector_count_along_edge: 10,
+                food_per_edge_sector: 1000,
+                initial_bots: 10,
+                rng_seed: 4242,
+                ..Default::default()
+            });
+            world.init();
+            let summary = world.step(100);
+            let heads: Vec<_> = {
+                let mut ids: Vec<_> = world.snakes().keys().copied().collect();
+                ids.sort_unstable();
+                ids.iter().map(|id| world.get_snake(*id).unwrap().head_pos_u16()).collect()
+            };
+            (summary, heads)
+        };
+
+        let (summary, heads) = run();
+        assert_eq!(summary.ticks, 100);
+        assert!(!summary.eaten.is_empty());
+        assert_eq!(run(), (summary, heads));
+    }
+
+    #[test]
+    fn test_changed_snakes_are_grouped_by_sector() {
+        let mut world = World::new(GameConfig::default());
+        let a = world.create_snake("A".to_string(), 0).unwrap();
+        let b = world.create_snake("B".to_string(), 0).unwrap();
+        world.tick(8);
+
+        let range_of = |world: &World, id| {
+            let (x, y) = world.get_snake(id).unwrap().head_pos();
+            world.sectors.sector_range_in_viewport(x, y, 1.0).unwrap()
+        };
+        let in_a: Vec<_> = world.changed_snakes_in(range_of(&world, a)).collect();
+        let in_b: Vec<_> = world.changed_snakes_in(range_of(&world, b)).collect();
+        assert!(in_a.contains(&a) && !in_a.contains(&b));
+        assert!(in_b.contains(&b) && !in_b.contains(&a));
+    }
+
+    #[test]
+    fn test_restricted_food_palette_limits_colors_and_sizes() {
+        let mut world = World::new(GameConfig {
+            food_colors: vec![3, 7, 11],
+            food_min_size: 8,
+            food_max_size: 10,
+            ..Default::default()
+        });
+        world.init();
+        for _ in 0..20 {
+            world.tick(world.config.frame_time_ms);
+        }
+
+        let foods: Vec<_> = world.sectors.iter().flat_map(|s| s.food.iter()).collect();
+        assert!(!foods.is_empty());
+        assert!(foods.iter().all(|f| [3, 7, 11].contains(&f.color)));
+        assert!(foods.iter().all(|f| (8..=10).contains(&f.size)));
+    }
+
+    #[test]
+    fn test_minimap_marks_food_hotspots_when_enabled() {
+        let config = GameConfig {
+            food_per_edge_sector: 0,
+            ..Default::default()
+        };
+        let mut plain = World::new(config.clone());
+        let mut hotspots = World::new(GameConfig {
+            minimap_food_hotspots: true,
+            ..config
+        });
+
+        let (x, y) = (10_000u16, 12_000u16);
+        for world in [&mut plain, &mut hotspots] {
+            for i in 0..world.config.minimap_food_threshold {
+                world.sectors.add_food(Food::new(x + i * 4, y, 5, 0));
+            }
+        }
+
+        let cell_size = hotspots.config.game_radius * 2 / 80;
+        let (sx, sy) = hotspots.sectors.world_to_sector(x as f32, y as f32);
+        let (cx, cy) = hotspots.sectors.get(sx, sy).unwrap().center(hotspots.sectors.sector_size);
+        let bit = (cy as u32 / cell_size * 80 + cx as u32 / cell_size) as usize;
+
+        let marked = hotspots.minimap_data(80);
+        assert_ne!(marked[bit / 8] & (1 << (bit % 8)), 0);
+        assert_eq!(marked.iter().map(|b| b.count_ones()).sum::<u32>(), 1);
+        assert!(plain.minimap_data(80).iter().all(|&b| b == 0));
+    }
+
+    #[test]
+    fn test_visible_snakes_uses_the_viewport_radius() {
+        let mut world = World::new(GameConfig::default());
+        let positions = [(10_000.0, 10_000.0), (11_500.0, 9_000.0), (12_100.0, 10_000.0), (10_000.0, 7_900.0)];
+        for (id, &(x, y)) in (1..).zip(positions.iter()) {
+            world.snakes.insert(id, Snake::new(id, x, y, String::new(), 0, 1, 0));
+            world.sectors.add_snake(id, x, y);
+        }
+        world.snakes.insert(5, Snake::new(5, 10_100.0, 10_000.0, String::new(), 0, 1, 0));
+        world.sectors.add_snake(5, 10_100.0, 10_000.0);
+        world.get_snake_mut(5).unwrap().dead = true;
+
+        let ids: Vec<_> = world.visible_snakes((10_000.0, 10_000.0), 2000.0).map(|s| s.id).collect();
+        assert_eq!(ids, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_obstacle_ahead_finds_the_nearest_body_in_the_cone() {
+        let mut world = World::new(GameConfig::default());
+        for (id, x) in [(1, 10_150.0), (2, 10_100.0), (3, 9_900.0)] {
+            world.snakes.insert(id, Snake::new(id, x, 10_000.0, String::new(), 0, 1, 0));
+            world.sectors.add_snake(id, x, 10_000.0);
+        }
+        world.index_extents();
+
+        let (id, dist) = world.obstacle_ahead(10_000.0, 10_000.0, 0.0, 200.0, 0.3).unwrap();
+        assert_eq!(id, 2);
+        assert!((dist - 100.0).abs() < 1e-3);
+        assert!(world.obstacle_ahead(10_000.0, 10_000.0, 0.0, 50.0, 0.3).is_none());
+        assert!(world.obstacle_ahead(10_000.0, 10_000.0, std::f32::consts::FRAC_PI_2, 200.0, 0.3).is_none());
+        assert!(world.obstacle_ahead(10_200.0, 10_000.0, 0.0, 500.0, 0.3).is_none());
+
+        assert_eq!(world.obstacle_ahead(10_000.0, 10_000.0, std::f32::consts::PI, 200.0, 0.3).unwrap().0, 3);
+        world.get_snake_mut(3).unwrap().dead = true;
+        assert!(world.obstacle_ahead(10_000.0, 10_000.0, std::f32::consts::PI, 200.0, 0.3).is_none());
+    }
+
+    #[test]
+    fn test_spawn_point_on_a_body_far_from_its_head_is_unsafe() {
+        let mut world = World::new(GameConfig::default());
+        let mut snake = Snake::new(1, 10_000.0, 10_000.0, String::new(), 0, 300, 0);
+        for (i, part) in snake.body.iter_mut().enumerate() {
+            part.x = 10_000.0;
+            part.y = 7_000.0 + i as f32 * 10.0;
+        }
+        snake.tick(0, world.config.game_radius as f32, world.config.boundary_behavior);
+        let (hx, hy) = snake.head_pos();
+        world.sectors.add_snake(1, hx, hy);
+        world.snakes.insert(1, snake);
+        world.index_extents();
+
+        assert!(world.sectors.snakes_near(10_000.0, 9_500.0, 200.0).is_empty());
+        assert!(!world.is_location_safe(10_000.0, 9_500.0, 100.0));
+        assert!(world.is_location_safe(10_500.0, 9_500.0, 100.0));
+    }
+
+    #[test]
+    fn test_obstacle_ahead_sees_bodies_whose_head_is_sectors_away() {
+        let mut world = World::new(GameConfig::default());
+        let mut snake = Snake::new(1, 10_000.0, 10_000.0, String::new(), 0, 300, 0);
+        for (i, part) in snake.body.iter_mut().enumerate() {
+            part.x = 10_000.0;
+            part.y = 7_000.0 + i as f32 * 10.0;
+        }
+        snake.tick(0, world.config.game_radius as f32, world.config.boundary_behavior);
+        let (hx, hy) = snake.head_pos();
+        world.sectors.add_snake(1, hx, hy);
+        world.snakes.insert(1, snake);
+        world.begin_tick();
+
+        let head_sector = world.sectors.world_to_sector(hx, hy);
+        assert_ne!(head_sector, world.sectors.world_to_sector(9_900.0, 9_000.0));
+        let (id, dist) = world.obstacle_ahead(9_900.0, 9_000.0, 0.0, 200.0, 0.3).unwrap();
+        assert_eq!(id, 1);
+        assert!((dist - 100.0).abs() < 10.0, "dist {}", dist);
+        assert!(world.obstacle_ahead(9_000.0, 4_000.0, 0.0, 200.0, 0.3).is_none());
+    }
+
+    fn head_on_world(rule: HeadOnRule, len1: usize, len2: usize) -> World {
+        let mut world = World::new(GameConfig {
+            head_on_rule: rule,
+            ..Default::default()
+        });
+        for (id, x, len) in [(1, 10_000.0, len1), (2, 10_010.0, len2)] {
+            world.snakes.insert(id, Snake::new(id, x, 10_000.0, String::new(), 0, len, 0));
+            world.sectors.add_snake(id, x, 10_000.0);
+        }
+        world.check_collisions();
+        world
+    }
+
+    fn outcome(world: &World, id: SnakeId) -> (bool, u32) {
+        let snake = world.get_snake(id).unwrap();
+        (snake.dead, snake.kills)
+    }
+
+    #[test]
+    fn test_head_on_both_die_credits_each_kill_once() {
+        let world = head_on_world(HeadOnRule::BothDie, 10, 20);
+        assert_eq!(outcome(&world, 1), (true, 1));
+        assert_eq!(outcome(&world, 2), (true, 1));
+        assert_eq!(world.dead_snakes, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_head_on_longer_survives() {
+        let world = head_on_world(HeadOnRule::LongerSurvives, 10, 20);
+        assert_eq!(outcome(&world, 1), (true, 0));
+        assert_eq!(outcome(&world, 2), (false, 1));
+        assert_eq!(world.dead_snakes, vec![1]);
+
+        let tied = head_on_world(HeadOnRule::LongerSurvives, 15, 15);
+        assert_eq!(outcome(&tied, 1), (true, 1));
+        assert_eq!(outcome(&tied, 2), (true, 1));
+    }
+
+    #[test]
+    fn test_snake_info_reports_public_state() {
+        let mut world = World::new(GameConfig::default());
+        world.snakes.insert(7, Snake::new(7, 5_000.0, 6_000.0, "Info".to_string(), 3, 12, 0));
+        world.snakes.insert(2, Snake::new(2, 9_000.0, 9_000.0, String::new(), 0, 10, 0));
+        world.get_snake_mut(7).unwrap().kills = 4;
+
+        let info = world.snake_info(7).unwrap();
+        assert_eq!((info.x, info.y), (5_000.0, 6_000.0));
+        assert_eq!(info.length, 12);
+        assert_eq!((info.name.as_str(), info.skin, info.kills), ("Info", 3, 4));
+        assert_eq!(info.score, world.get_snake(7).unwrap().score());
+        assert!(!info.is_bot && !info.dead);
+
+        assert!(world.snake_info(99).is_none());
+        let ids: Vec<_> = world.all_snake_info().iter().map(|snake| snake.id).collect();
+        assert_eq!(ids, [2, 7]);
+    }
+
+    #[test]
+    fn test_killer_absorbs_a_fraction_of_the_victims_fullness() {
+        for replaces_drop in [false, true] {
+            let mut world = World::new(GameConfig {
+                food_per_edge_sector: 0,
+                kill_mass_transfer: 0.25,
+                kill_transfer_replaces_drop: replaces_drop,
+                ..Default::default()
+            });
+            for (id, fullness) in [(1, 40), (2, 1000)] {
+                let (x, y) = (5_000.0 + id as f32 * 1_000.0, 10_000.0);
+                world.snakes.insert(id, Snake::new(id, x, y, String::new(), 0, 10, fullness));
+                world.sectors.add_snake(id, x, y);
+            }
+
+            world.kill_snake(2, 1);
+
+            let killer = world.get_snake(1).unwrap();
+            assert_eq!(killer.fullness, 40 + 250);
+            assert!(killer.changes.has_fullness());
+            assert_eq!(world.get_snake(2).unwrap().foods_spawned.is_empty(), replaces_drop);
+        }
+    }
+
+    #[test]
+    fn test_fullness_gains_shrink_near_the_length_soft_cap() {
+        let mut world = World::new(GameConfig {
+            food_per_edge_sector: 0,
+            length_soft_cap_start: 20,
+            length_soft_cap: 60,
+            ..Default::default()
+        });
+
+        let mut gains = Vec::new();
+        for (id, parts) in [(1, 10), (2, 30), (3, 50), (4, 60)] {
+            let (x, y) = (5_000.0 + id as f32 * 1_000.0, 10_000.0);
+            world.snakes.insert(id, Snake::new(id, x, y, String::new(), 0, parts, 0));
+            world.sectors.add_snake(id, x, y);
+            world.sectors.add_food(Food::new(x as u16, y as u16, 10, 0));
+        }
+        world.process_eating();
+        for id in 1..=4 {
+            gains.push(world.get_snake(id).unwrap().fullness);
+        }
+
+        assert_eq!(gains[0], Food::new(0, 0, 10, 0).value(world.config.food_value_multiplier) as u32);
+        assert!(gains.windows(2).all(|w| w[0] > w[1]), "gains {:?}", gains);
+        assert_eq!(gains[3], 0);
+    }
 }
@@ -5,6 +5,7 @@
 
 
 pub mod config;
+pub mod logging;
 pub mod game;
 pub mod protocol;
 pub mod server;
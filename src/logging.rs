@@ -0,0 +1,31 @@
+
+
+use crate::config::LogFormat;
+use tracing::{Level, Subscriber};
+use tracing_subscriber::FmtSubscriber;
+
+
+pub fn build_subscriber(format: LogFormat, level: Level) -> Box<dyn Subscriber + Send + Sync> {
+    let builder = FmtSubscriber::builder()
+        .with_max_level(level)
+        .with_target(false)
+        .with_thread_ids(false);
+
+    match format {
+        LogFormat::Compact => Box::new(builder.compact().finish()),
+        LogFormat::Json => Box::new(builder.json().flatten_event(true).finish()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_subscriber_builds_and_logs() {
+        let subscriber = build_subscriber(LogFormat::Json, Level::DEBUG);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(session_id = 7u64, snake_id = 3u16, "Snake died");
+        });
+    }
+}
@@ -4,10 +4,10 @@
 
 
 use clap::Parser;
-use rust_slither::config::{GameConfig, ServerArgs};
-use rust_slither::server::run_server;
+use rust_slither::config::{GameConfig, ServerArgs, SkinRegistry};
+use rust_slither::logging::build_subscriber;
+use rust_slither::server::run_server_on;
 use tracing::{info, Level};
-use tracing_subscriber::FmtSubscriber;
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -21,31 +21,90 @@ async fn main() -> anyhow::Result<()> {
         Level::INFO
     };
 
-    let subscriber = FmtSubscriber::builder()
-        .with_max_level(log_level)
-        .with_target(false)
-        .with_thread_ids(false)
-        .compact()
-        .init();
+    tracing::subscriber::set_global_default(build_subscriber(args.log_format, log_level))?;
 
    
-    let mut config = GameConfig::default();
-    config.initial_bots = args.bots;
-    config.bot_respawn = args.bot_respawn;
+    let skin_registry = match &args.skins_path {
+        Some(path) => match std::fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| SkinRegistry::from_json(&text))
+        {
+            Ok(registry) => registry,
+            Err(e) => anyhow::bail!("Failed to load skins from {}: {}", path.display(), e),
+        },
+        None => SkinRegistry::default(),
+    };
+
+    #[cfg(not(feature = "bots"))]
+    if args.bots > 0 {
+        tracing::warn!("Built without the bots feature, ignoring --bots {}", args.bots);
+    }
+
+    let config = match GameConfig::builder()
+        .initial_bots(if cfg!(feature = "bots") { args.bots } else { 0 })
+        .bot_respawn(args.bot_respawn)
+        .unique_bot_names(args.unique_bot_names)
+        .player_respawn(args.player_respawn)
+        .max_players(args.max_players)
+        .leaderboard_path(args.leaderboard_path.clone())
+        .minimap_food_hotspots(args.minimap_food)
+        .boundary_behavior(args.boundary)
+        .boundary_warning_distance(args.boundary_warning)
+        .min_food_spacing(args.min_food_spacing)
+        .head_on_rule(args.head_on)
+        .tick_catch_up(args.tick_catch_up)
+        .absolute_rotation_from(args.absolute_rotation_from)
+        .kill_mass_transfer(args.kill_mass_transfer)
+        .kill_transfer_replaces_drop(args.kill_transfer_replaces_drop)
+        .input_buffer_ms(args.input_buffer_ms)
+        .write_timeout_ms(args.write_timeout_ms)
+        .max_name_chars(args.max_name_chars)
+        .sector_enter_padding(args.sector_enter_padding)
+        .sector_leave_padding(args.sector_leave_padding)
+        .verify_checksum(args.verify_checksum)
+        .allowed_origins(args.allowed_origins.clone())
+        .announcements(args.announcements.clone())
+        .handshake_secret(args.handshake_secret.clone().map(String::into_bytes))
+        .ban_file(args.ban_file.clone())
+        .real_ip_header(args.real_ip_header.clone())
+        .trusted_proxies(args.trusted_proxies.clone())
+        .admin_token(args.admin_token.clone())
+        .skin_registry(skin_registry)
+        .debug_overlay(args.debug_overlay)
+        .debug_text(args.debug_text)
+        .build()
+    {
+        Ok(config) => config,
+        Err(e) => anyhow::bail!("Invalid configuration: {}", e),
+    };
 
     info!("===========================================");
     info!("    Rust Slither.io Server v0.1.0");
     info!("===========================================");
     info!("");
     info!("Configuration:");
-    info!("  Port: {}", args.port);
+    let listeners = args.listeners();
+    for listener in &listeners {
+        let scheme = if listener.tls.is_some() { "wss" } else { "ws" };
+        info!("  Listener: {}://0.0.0.0:{}", scheme, listener.port);
+    }
     info!("  Game radius: {}", config.game_radius);
     info!("  Sector size: {}", config.sector_size);
     info!("  Protocol version: {}", config.protocol_version);
     info!("  Initial bots: {}", config.initial_bots);
     info!("  Bot respawn: {}", config.bot_respawn);
+    info!("  Max players: {}", config.max_players);
+    if config.debug_overlay {
+        info!("  Debug overlay: enabled");
+    }
+    if config.debug_text {
+        info!("  Debug text protocol: enabled");
+    }
+    if config.admin_token.is_some() {
+        info!("  Admin HTTP API: enabled");
+    }
     info!("");
 
    
-    run_server(args.port, config).await
+    run_server_on(&listeners, config).await
 }
@@ -0,0 +1,101 @@
+
+
+
+use crate::protocol::types::OutgoingPacketType;
+use crate::protocol::writer::PacketWriter;
+use std::f32::consts::PI;
+
+
+pub const CLIENT_PROTOCOL_VERSION: u8 = 14;
+
+
+pub fn start_login_frame() -> Vec<u8> {
+    vec![b'c']
+}
+
+
+pub fn identity_frame(skin: u8, name: &str) -> Vec<u8> {
+    let mut writer = PacketWriter::with_capacity(4 + name.len());
+    writer.write_u8(b's');
+    writer.write_u8(CLIENT_PROTOCOL_VERSION);
+    writer.write_u8(skin);
+    writer.write_string(name);
+    writer.as_bytes().to_vec()
+}
+
+
+pub fn angle_frame(radians: f32) -> Vec<u8> {
+    vec![radians_to_angle_byte(radians)]
+}
+
+
+pub fn boost_frame(enabled: bool) -> Vec<u8> {
+    vec![if enabled { 253 } else { 254 }]
+}
+
+
+pub fn ping_frame() -> Vec<u8> {
+    vec![251]
+}
+
+
+pub fn resync_frame() -> Vec<u8> {
+    vec![crate::protocol::incoming::RESYNC_OPCODE, 0]
+}
+
+
+pub fn spectate_frame() -> Vec<u8> {
+    vec![crate::protocol::incoming::SPECTATE_OPCODE, 0]
+}
+
+
+pub fn radians_to_angle_byte(radians: f32) -> u8 {
+    let turns = radians.rem_euclid(2.0 * PI) / (2.0 * PI);
+    ((turns * 250.0).round() as u16 % 250) as u8
+}
+
+
+pub fn single_packet_type(frame: &[u8], has_etm: bool) -> Option<u8> {
+    let offset = if has_etm { 2 } else { 0 };
+    frame.get(offset).copied()
+}
+
+
+pub fn is_init_frame(frame: &[u8], has_etm: bool) -> bool {
+    single_packet_type(frame, has_etm) == Some(OutgoingPacketType::Init.into())
+}
+
+
+pub fn is_pre_init_frame(frame: &[u8], has_etm: bool) -> bool {
+    single_packet_type(frame, has_etm) == Some(OutgoingPacketType::PreInit.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::incoming::{parse_incoming_packet, IncomingPacket};
+
+    #[test]
+    fn test_client_frames_parse_on_server() {
+        let packet = parse_incoming_packet(&identity_frame(9, "Bot"), 14).unwrap();
+        if let IncomingPacket::SetIdentity(id) = packet {
+            assert_eq!(id.skin, 9);
+            assert_eq!(id.nickname, "Bot");
+        } else {
+            panic!("Expected identity packet");
+        }
+
+        assert!(matches!(
+            parse_incoming_packet(&start_login_frame(), 14).unwrap(),
+            IncomingPacket::StartLogin
+        ));
+        assert!(matches!(
+            parse_incoming_packet(&angle_frame(PI), 14).unwrap(),
+            IncomingPacket::Angle(_)
+        ));
+        assert!(matches!(
+            parse_incoming_packet(&boost_frame(true), 14).unwrap(),
+            IncomingPacket::StartAcceleration
+        ));
+    }
+}
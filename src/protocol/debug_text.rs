@@ -0,0 +1,194 @@
+
+
+
+use crate::protocol::client::radians_to_angle_byte;
+use crate::protocol::error::ProtocolError;
+use crate::protocol::incoming::{
+    AnglePacket, IncomingPacket, RotationPacket, SetIdentityPacket, MAX_ROTATION_INTENSITY,
+};
+use serde_json::Value;
+
+
+pub fn parse_debug_text(text: &str) -> Result<IncomingPacket, ProtocolError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(ProtocolError::Empty);
+    }
+
+    let value: Value = serde_json::from_str(text).map_err(|_| ProtocolError::InvalidCommand)?;
+    let cmd = value
+        .get("cmd")
+        .and_then(Value::as_str)
+        .ok_or(ProtocolError::InvalidCommand)?;
+
+    match cmd {
+        "mode" => Ok(IncomingPacket::ProtocolMode {
+            want_etm: bool_field(&value, "etm").unwrap_or(false),
+        }),
+        "login" => Ok(IncomingPacket::StartLogin),
+        "identity" => Ok(IncomingPacket::SetIdentity(SetIdentityPacket {
+            protocol_version: u8_field(&value, "protocol")?.unwrap_or(14),
+            skin: u8_field(&value, "skin")?.unwrap_or(0),
+            nickname: str_field(&value, "name").unwrap_or_default().to_string(),
+            custom_skin: None,
+        })),
+        "angle" => {
+            let radians = value
+                .get("value")
+                .and_then(Value::as_f64)
+                .ok_or(ProtocolError::InvalidCommand)? as f32;
+            Ok(IncomingPacket::Angle(AnglePacket {
+                angle: radians_to_angle_byte(radians),
+            }))
+        }
+        "rotate" => {
+            let intensity = u8_field(&value, "value")?
+                .unwrap_or(MAX_ROTATION_INTENSITY)
+                .min(MAX_ROTATION_INTENSITY);
+            let clockwise = bool_field(&value, "clockwise").unwrap_or(false);
+            Ok(IncomingPacket::Rotation(RotationPacket {
+                value: if clockwise { intensity + 128 } else { intensity },
+                is_legacy_left: false,
+                is_legacy_right: false,
+            }))
+        }
+        "boost" => {
+            if bool_field(&value, "value").unwrap_or(true) {
+                Ok(IncomingPacket::StartAcceleration)
+            } else {
+                Ok(IncomingPacket::StopAcceleration)
+            }
+        }
+        "ping" => Ok(IncomingPacket::Ping),
+        "spectate" => Ok(IncomingPacket::Spectate),
+        "victory" => Ok(IncomingPacket::VictoryMessage(
+            str_field(&value, "message").unwrap_or_default().to_string(),
+        )),
+        "feed" => Ok(IncomingPacket::Feed {
+            session_id: u64_field(&value, "session")?,
+            count: match value.get("count") {
+                None => 1,
+                Some(v) => v
+                    .as_u64()
+                    .and_then(|n| u16::try_from(n).ok())
+                    .ok_or(ProtocolError::InvalidCommand)?,
+            },
+        }),
+        _ => Err(ProtocolError::InvalidCommand),
+    }
+}
+
+fn bool_field(value: &Value, key: &str) -> Option<bool> {
+    value.get(key).and_then(Value::as_bool)
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(Value::as_str)
+}
+
+fn u64_field(value: &Value, key: &str) -> Result<Option<u64>, ProtocolError> {
+    match value.get(key) {
+        None => Ok(None),
+        Some(v) => v.as_u64().map(Some).ok_or(ProtocolError::InvalidCommand),
+    }
+}
+
+fn u8_field(value: &Value, key: &str) -> Result<Option<u8>, ProtocolError> {
+    match value.get(key) {
+        None => Ok(None),
+        Some(v) => v
+            .as_u64()
+            .and_then(|n| u8::try_from(n).ok())
+            .map(Some)
+            .ok_or(ProtocolError::InvalidCommand),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_angle_command() {
+        let packet = parse_debug_text(r#"{"cmd":"angle","value":1.57}"#).unwrap();
+        if let IncomingPacket::Angle(ang) = packet {
+            assert_eq!(ang.angle, 62);
+            assert!((ang.to_radians() - 1.57).abs() < 0.02);
+        } else {
+            panic!("Expected angle packet");
+        }
+
+        let packet = parse_debug_text(r#"{"cmd":"angle","value":-0.0001}"#).unwrap();
+        assert!(matches!(packet, IncomingPacket::Angle(AnglePacket { angle: 0 })));
+    }
+
+    #[test]
+    fn test_parse_login_and_identity() {
+        assert!(matches!(
+            parse_debug_text(r#" {"cmd":"login"} "#).unwrap(),
+            IncomingPacket::StartLogin
+        ));
+
+        let packet = parse_debug_text(r#"{"cmd":"identity","name":"Tester","skin":7}"#).unwrap();
+        if let IncomingPacket::SetIdentity(id) = packet {
+            assert_eq!(id.nickname, "Tester");
+            assert_eq!(id.skin, 7);
+            assert_eq!(id.protocol_version, 14);
+        } else {
+            panic!("Expected identity packet");
+        }
+    }
+
+    #[test]
+    fn test_parse_rotate_and_boost() {
+        let packet = parse_debug_text(r#"{"cmd":"rotate","value":40,"clockwise":true}"#).unwrap();
+        if let IncomingPacket::Rotation(rot) = packet {
+            assert!(rot.is_clockwise());
+            assert_eq!(rot.intensity(), 40);
+        } else {
+            panic!("Expected rotation packet");
+        }
+
+        assert!(matches!(
+            parse_debug_text(r#"{"cmd":"boost"}"#).unwrap(),
+            IncomingPacket::StartAcceleration
+        ));
+        assert!(matches!(
+            parse_debug_text(r#"{"cmd":"boost","value":false}"#).unwrap(),
+            IncomingPacket::StopAcceleration
+        ));
+    }
+
+    #[test]
+    fn test_parse_feed_command() {
+        assert!(matches!(
+            parse_debug_text(r#"{"cmd":"feed","session":3,"count":20}"#).unwrap(),
+            IncomingPacket::Feed { session_id: Some(3), count: 20 }
+        ));
+        assert!(matches!(
+            parse_debug_text(r#"{"cmd":"feed"}"#).unwrap(),
+            IncomingPacket::Feed { session_id: None, count: 1 }
+        ));
+        assert_eq!(
+            parse_debug_text(r#"{"cmd":"feed","count":70000}"#).unwrap_err(),
+            ProtocolError::InvalidCommand
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_commands() {
+        assert_eq!(parse_debug_text("").unwrap_err(), ProtocolError::Empty);
+        assert_eq!(parse_debug_text("c").unwrap_err(), ProtocolError::InvalidCommand);
+        for cmd in ["teleport", "say", "pause", "ban", "unban"] {
+            assert_eq!(
+                parse_debug_text(&format!(r#"{{"cmd":"{cmd}","ip":"10.0.0.7","message":"hi"}}"#)).unwrap_err(),
+                ProtocolError::InvalidCommand
+            );
+        }
+        assert_eq!(
+            parse_debug_text(r#"{"cmd":"identity","skin":300}"#).unwrap_err(),
+            ProtocolError::InvalidCommand
+        );
+        assert!(!ProtocolError::InvalidCommand.is_fatal());
+    }
+}
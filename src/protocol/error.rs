@@ -0,0 +1,41 @@
+
+
+use std::io;
+use thiserror::Error;
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum ProtocolError {
+    #[error("packet truncated")]
+    Truncated,
+    #[error("unknown opcode {0}")]
+    UnknownOpcode(u8),
+    #[error("invalid UTF-8 in string field")]
+    InvalidUtf8,
+    #[error("empty packet")]
+    Empty,
+    #[error("packet exceeds maximum size")]
+    TooLarge,
+    #[error("invalid debug text command")]
+    InvalidCommand,
+    #[error("custom skin is {len} bytes, at most {max} are allowed")]
+    CustomSkinTooLong { len: usize, max: usize },
+    #[error("custom skin contains invalid color index {0}")]
+    InvalidSkinColor(u8),
+}
+
+impl ProtocolError {
+   
+    pub fn is_fatal(&self) -> bool {
+        !matches!(self, Self::UnknownOpcode(_) | Self::Empty | Self::InvalidCommand)
+    }
+}
+
+impl From<io::Error> for ProtocolError {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::InvalidData => Self::InvalidUtf8,
+            _ => Self::Truncated,
+        }
+    }
+}
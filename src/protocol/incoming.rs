@@ -1,7 +1,7 @@
 
 
-use crate::protocol::reader::PacketReader;
-use std::io;
+use crate::protocol::error::ProtocolError;
+use crate::protocol::packet::{is_modern_protocol, MAX_PACKET_SIZE};
 
 
 #[derive(Debug, Clone)]
@@ -28,10 +28,46 @@ pub enum IncomingPacket {
    
     VictoryMessage(String),
    
-    Unknown(u8, Vec<u8>),
+   
+    HandshakeAnswer([u8; HANDSHAKE_ANSWER_LEN]),
+   
+    Resync,
+   
+    Spectate,
+   
+   
+    Feed { session_id: Option<u64>, count: u16 },
+}
+
+impl IncomingPacket {
+   
+    pub fn custom_skin(&self) -> Option<&[u8]> {
+        match self {
+            Self::Login(login) => login.custom_skin.as_deref(),
+            Self::SetIdentity(identity) => identity.custom_skin.as_deref(),
+            _ => None,
+        }
+    }
+
+   
+    pub fn is_movement(&self) -> bool {
+        matches!(
+            self,
+            Self::Rotation(_) | Self::Angle(_) | Self::StartAcceleration | Self::StopAcceleration
+        )
+    }
 }
 
 
+pub const HANDSHAKE_ANSWER_LEN: usize = 24;
+
+
+pub const RESYNC_OPCODE: u8 = b'R';
+
+
+pub const SPECTATE_OPCODE: u8 = b'S';
+
+
 #[derive(Debug, Clone)]
 pub struct LoginPacket {
    
@@ -45,7 +81,7 @@ pub struct LoginPacket {
    
     pub nickname: String,
    
-    pub custom_skin: Option<String>,
+    pub custom_skin: Option<Vec<u8>>,
 }
 
 
@@ -58,7 +94,7 @@ pub struct SetIdentityPacket {
    
     pub nickname: String,
    
-    pub custom_skin: Option<String>,
+    pub custom_skin: Option<Vec<u8>>,
 }
 
 
@@ -88,17 +124,42 @@ impl RotationPacket {
 
    
     pub fn intensity(&self) -> u8 {
-        if self.is_legacy_left || self.is_legacy_right {
+        let raw = if self.is_legacy_left || self.is_legacy_right {
             self.value
         } else if self.value >= 128 {
             self.value - 128
         } else {
             self.value
+        };
+        raw.min(MAX_ROTATION_INTENSITY)
+    }
+
+   
+    pub fn angle_delta(&self) -> f32 {
+        let magnitude =
+            std::f32::consts::PI * self.intensity() as f32 / MAX_ROTATION_INTENSITY as f32;
+        if self.is_clockwise() {
+            -magnitude
+        } else {
+            magnitude
         }
     }
+
+   
+    pub fn is_legacy(&self) -> bool {
+        self.is_legacy_left || self.is_legacy_right
+    }
+
+   
+    pub fn absolute_angle(&self) -> f32 {
+        2.0 * std::f32::consts::PI * self.value as f32 / 256.0
+    }
 }
 
 
+pub const MAX_ROTATION_INTENSITY: u8 = 127;
+
+
 #[derive(Debug, Clone)]
 pub struct AnglePacket {
    
@@ -113,20 +174,26 @@ impl AnglePacket {
 }
 
 
-pub fn parse_incoming_packet(data: &[u8], _protocol_version: u8) -> io::Result<IncomingPacket> {
+pub fn parse_handshake_answer(data: &[u8]) -> Option<[u8; HANDSHAKE_ANSWER_LEN]> {
+    if matches!(data.first(), Some(b'c' | b's')) {
+        return None;
+    }
+    data.try_into().ok()
+}
+
+pub fn parse_incoming_packet(data: &[u8], _protocol_version: u8) -> Result<IncomingPacket, ProtocolError> {
     if data.is_empty() {
-        return Err(io::Error::new(io::ErrorKind::InvalidData, "empty packet"));
+        return Err(ProtocolError::Empty);
+    }
+
+    if data.len() > MAX_PACKET_SIZE {
+        return Err(ProtocolError::TooLarge);
     }
 
     let len = data.len();
     let cmd = data[0];
 
    
-    if len == 24 {
-        return Ok(IncomingPacket::Unknown(cmd, data[1..].to_vec()));
-    }
-
-   
     if cmd == b'c' {
         return Ok(IncomingPacket::StartLogin);
     }
@@ -188,6 +255,11 @@ pub fn parse_incoming_packet(data: &[u8], _protocol_version: u8) -> io::Result<I
         }
 
        
+       
+        RESYNC_OPCODE => Ok(IncomingPacket::Resync),
+        SPECTATE_OPCODE => Ok(IncomingPacket::Spectate),
+
+       
         253 => Ok(IncomingPacket::StartAcceleration),
 
        
@@ -209,7 +281,7 @@ pub fn parse_incoming_packet(data: &[u8], _protocol_version: u8) -> io::Result<I
         }
 
        
-        _ => Ok(IncomingPacket::Unknown(cmd, data[1..].to_vec())),
+        _ => Err(ProtocolError::UnknownOpcode(cmd)),
     }
 }
 
@@ -217,217 +289,55 @@ pub fn parse_incoming_packet(data: &[u8], _protocol_version: u8) -> io::Result<I
 
 
 
-fn parse_username_packet(data: &[u8]) -> io::Result<IncomingPacket> {
-    if data.is_empty() {
-        return Err(io::Error::new(io::ErrorKind::InvalidData, "username packet empty"));
-    }
-
-    let mut pos = 0;
-
-   
-    let client_protocol = data[pos];
-    pos += 1;
-
-   
-   
-   
-   
-   
-    let looks_like_official = client_protocol >= 25 && data.len() >= 1 + 2 + 20 + 1 + 1;
-
-    if looks_like_official {
-       
-        if pos + 2 > data.len() {
-            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "missing client_version"));
-        }
-        let version = ((data[pos] as u16) << 8) | data[pos + 1] as u16;
-        pos += 2;
+// The protocol byte alone picks the framing: versions below VERSION_MODERN send
+// [protocol, skin, name_len, name, custom_skin..]; newer clients insert a u16
+// client version and a 20-byte checksum after the protocol byte.
+fn parse_username_packet(data: &[u8]) -> Result<IncomingPacket, ProtocolError> {
+    let (&client_protocol, rest) = data.split_first().ok_or(ProtocolError::Truncated)?;
 
-       
-        if pos + 20 > data.len() {
-            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "missing checksum"));
-        }
-        let mut checksum = [0u8; 20];
-        checksum.copy_from_slice(&data[pos..pos + 20]);
-        pos += 20;
-
-       
-        if pos >= data.len() {
-            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "missing skin"));
-        }
-        let skin = data[pos];
-        pos += 1;
-
-       
-        if pos >= data.len() {
-            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "missing name length"));
-        }
-        let name_len = data[pos] as usize;
-        pos += 1;
-
-       
-        if pos + name_len > data.len() {
-            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "name truncated"));
-        }
-        let nickname = String::from_utf8_lossy(&data[pos..pos + name_len]).to_string();
-        pos += name_len;
-
-       
-       
-       
-       
-        Ok(IncomingPacket::Login(LoginPacket {
-            protocol_version: client_protocol,
-            version,
-            checksum,
-            skin,
-            nickname,
-            custom_skin: None,
-        }))
-    } else {
-       
-        if data.len() < pos + 2 {
-            return Err(io::Error::new(
-                io::ErrorKind::InvalidData,
-                format!("identity packet too short: {} bytes", data.len()),
-            ));
-        }
-
-        let skin = data[pos];
-        pos += 1;
-
-        let name_len = data[pos] as usize;
-        pos += 1;
-
-        let name_end = (pos + name_len).min(data.len());
-        let actual_name_len = name_end - pos;
-        let nickname = if actual_name_len > 0 {
-            String::from_utf8_lossy(&data[pos..name_end]).to_string()
-        } else {
-            String::new()
-        };
-        pos = name_end;
-
-       
-        let custom_skin = if pos < data.len() {
-            Some(String::from_utf8_lossy(&data[pos..]).to_string())
-        } else {
-            None
-        };
-
-        Ok(IncomingPacket::SetIdentity(SetIdentityPacket {
+    if !is_modern_protocol(client_protocol) {
+        let (skin, nickname, custom_skin) = parse_skin_and_name(rest)?;
+        return Ok(IncomingPacket::SetIdentity(SetIdentityPacket {
             protocol_version: client_protocol,
             skin,
             nickname,
             custom_skin,
-        }))
+        }));
     }
-}
-fn parse_identity_packet(data: &[u8]) -> io::Result<IncomingPacket> {
-    if data.len() < 2 {
-        return Err(io::Error::new(
-            io::ErrorKind::InvalidData,
-            format!("identity packet too short: {} bytes", data.len()),
-        ));
-    }
-
-    let mut pos = 0;
-    let protocol_version = data[pos];
-    pos += 1;
 
-   
-   
-   
-    if data.len() >= 27 {
-        let mut p = pos;
-
-       
-        if p + 2 <= data.len() {
-            let version = u16::from_be_bytes([data[p], data[p + 1]]);
-            p += 2;
-
-           
-            if p + 20 <= data.len() {
-                let mut checksum = [0u8; 20];
-                checksum.copy_from_slice(&data[p..p + 20]);
-                p += 20;
-
-               
-                if p + 2 <= data.len() {
-                    let skin = data[p];
-                    p += 1;
-                    let name_len = data[p] as usize;
-                    p += 1;
-
-                   
-                    if p + name_len + 2 <= data.len() {
-                        let nickname = if name_len > 0 {
-                            String::from_utf8_lossy(&data[p..p + name_len]).to_string()
-                        } else {
-                            String::new()
-                        };
-                        p += name_len;
-
-                       
-                        if p + 2 <= data.len() {
-                            p += 2;
-                        }
-
-                        let custom_skin = if p < data.len() {
-                            Some(String::from_utf8_lossy(&data[p..]).to_string())
-                        } else {
-                            None
-                        };
-
-                        return Ok(IncomingPacket::Login(LoginPacket {
-                            protocol_version,
-                            version,
-                            checksum,
-                            skin,
-                            nickname,
-                            custom_skin,
-                        }));
-                    }
-                }
-            }
-        }
-       
-    }
-
-   
-   
-    if pos + 2 > data.len() {
-        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "missing skin/name_len"));
+    if rest.len() < 2 + 20 {
+        return Err(ProtocolError::Truncated);
     }
-    let skin = data[pos];
-    pos += 1;
+    let version = u16::from_be_bytes([rest[0], rest[1]]);
+    let mut checksum = [0u8; 20];
+    checksum.copy_from_slice(&rest[2..22]);
+    let (skin, nickname, custom_skin) = parse_skin_and_name(&rest[22..])?;
+
+    Ok(IncomingPacket::Login(LoginPacket {
+        protocol_version: client_protocol,
+        version,
+        checksum,
+        skin,
+        nickname,
+        custom_skin,
+    }))
+}
 
-    let name_len = data[pos] as usize;
-    pos += 1;
 
-    if pos + name_len > data.len() {
-        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "nickname out of range"));
+fn parse_skin_and_name(data: &[u8]) -> Result<(u8, String, Option<Vec<u8>>), ProtocolError> {
+    if data.len() < 2 {
+        return Err(ProtocolError::Truncated);
     }
+    let skin = data[0];
+    let name_len = data[1] as usize;
+    let name = data.get(2..2 + name_len).ok_or(ProtocolError::Truncated)?;
+    let custom_skin = &data[2 + name_len..];
 
-    let nickname = if name_len > 0 {
-        String::from_utf8_lossy(&data[pos..pos + name_len]).to_string()
-    } else {
-        String::new()
-    };
-    pos += name_len;
-
-    let custom_skin = if pos < data.len() {
-        Some(String::from_utf8_lossy(&data[pos..]).to_string())
-    } else {
-        None
-    };
-
-    Ok(IncomingPacket::SetIdentity(SetIdentityPacket {
-        protocol_version,
+    Ok((
         skin,
-        nickname,
-        custom_skin,
-    }))
+        String::from_utf8_lossy(name).to_string(),
+        (!custom_skin.is_empty()).then(|| custom_skin.to_vec()),
+    ))
 }
 
 
@@ -443,6 +353,8 @@ pub struct ProtocolState {
     pub protocol_version: u8,
    
     pub handshake_complete: bool,
+   
+    pub challenge_answered: bool,
 }
 
 impl ProtocolState {
@@ -478,6 +390,39 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_rotation_delta_symmetry() {
+        for magnitude in [0u8, 1, 32, 64, 127] {
+            let left = match parse_incoming_packet(&[252, magnitude], 14).unwrap() {
+                IncomingPacket::Rotation(rot) => rot,
+                _ => panic!("Expected rotation packet"),
+            };
+            let right = match parse_incoming_packet(&[252, 128 + magnitude], 14).unwrap() {
+                IncomingPacket::Rotation(rot) => rot,
+                _ => panic!("Expected rotation packet"),
+            };
+            assert_eq!(left.angle_delta(), -right.angle_delta());
+            assert!(left.angle_delta() >= 0.0);
+        }
+
+        let full = match parse_incoming_packet(&[252, 127], 14).unwrap() {
+            IncomingPacket::Rotation(rot) => rot,
+            _ => panic!("Expected rotation packet"),
+        };
+        assert!((full.angle_delta() - std::f32::consts::PI).abs() < 1e-6);
+
+        let legacy_left = match parse_incoming_packet(&[b'l', 200], 14).unwrap() {
+            IncomingPacket::Rotation(rot) => rot,
+            _ => panic!("Expected rotation packet"),
+        };
+        let legacy_right = match parse_incoming_packet(&[b'r', 200], 14).unwrap() {
+            IncomingPacket::Rotation(rot) => rot,
+            _ => panic!("Expected rotation packet"),
+        };
+        assert_eq!(legacy_left.angle_delta(), full.angle_delta());
+        assert_eq!(legacy_right.angle_delta(), -full.angle_delta());
+    }
+
     #[test]
     fn test_parse_identity() {
        
@@ -493,6 +438,96 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_oversized_name_len_is_truncated_in_both_forms() {
+        let legacy = [14, 3, 10, b'a', b'b'];
+        let mut official = vec![31, 0, 1];
+        official.extend_from_slice(&[0; 20]);
+        official.extend_from_slice(&[3, 10, b'a', b'b']);
+
+        assert_eq!(parse_username_packet(&legacy).unwrap_err(), ProtocolError::Truncated);
+        assert_eq!(parse_username_packet(&official).unwrap_err(), ProtocolError::Truncated);
+    }
+
+    #[test]
+    fn test_protocol_byte_alone_selects_login_framing() {
+        let mut frame = vec![0, 0, 1];
+        frame.extend_from_slice(&[0; 20]);
+        frame.extend_from_slice(&[3, 1, b'a']);
+        assert_eq!(frame.len(), 26);
+
+        for len in [26, 27] {
+            frame.resize(len, 9);
+            frame[0] = 24;
+            assert!(matches!(parse_username_packet(&frame), Ok(IncomingPacket::SetIdentity(_))));
+            frame[0] = 25;
+            match parse_username_packet(&frame).unwrap() {
+                IncomingPacket::Login(login) => {
+                    assert_eq!((login.skin, login.nickname.as_str()), (3, "a"));
+                    assert_eq!(login.custom_skin.is_some(), len == 27);
+                }
+                other => panic!("expected login, got {:?}", other),
+            }
+        }
+
+        let legacy = [24, 3, 1, b'a', 7, 7];
+        match parse_username_packet(&legacy).unwrap() {
+            IncomingPacket::SetIdentity(identity) => assert_eq!(identity.custom_skin, Some(vec![7, 7])),
+            other => panic!("expected identity, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_username_framing_fuzz() {
+        let mut rng = crate::game::math::SimpleRng::new(2121);
+        for _ in 0..5_000 {
+            let len = rng.range(1, 40) as usize;
+            let mut frame: Vec<u8> = (0..len).map(|_| rng.range(0, 256) as u8).collect();
+            frame[0] = rng.range(20, 30) as u8;
+
+            match parse_username_packet(&frame) {
+                Ok(IncomingPacket::Login(login)) => assert!(login.protocol_version >= 25),
+                Ok(IncomingPacket::SetIdentity(identity)) => assert!(identity.protocol_version < 25),
+                Ok(other) => panic!("unexpected packet {:?}", other),
+                Err(e) => assert_eq!(e, ProtocolError::Truncated),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_incoming_packet(&[], 14).unwrap_err(), ProtocolError::Empty);
+        assert_eq!(
+            parse_incoming_packet(&[b'x', 1], 14).unwrap_err(),
+            ProtocolError::UnknownOpcode(b'x')
+        );
+        assert_eq!(
+            parse_incoming_packet(&[b's', 14, 3], 14).unwrap_err(),
+            ProtocolError::Truncated
+        );
+        assert_eq!(
+            parse_incoming_packet(&vec![253; MAX_PACKET_SIZE + 1], 14).unwrap_err(),
+            ProtocolError::TooLarge
+        );
+        assert!(!ProtocolError::UnknownOpcode(0).is_fatal());
+        assert!(ProtocolError::Truncated.is_fatal());
+    }
+
+    #[test]
+    fn test_parse_handshake_answer() {
+        let frame = *b"kqbhbrvzcfkstfwoyuxqxsmd";
+        assert_eq!(frame.len(), HANDSHAKE_ANSWER_LEN);
+
+        assert_eq!(parse_handshake_answer(&frame), Some(frame));
+        assert_eq!(parse_handshake_answer(&frame[1..]), None);
+
+        let mut login = vec![b's', 14, 0, 20];
+        login.extend_from_slice(b"twenty_char_nickname");
+        assert_eq!(login.len(), HANDSHAKE_ANSWER_LEN);
+        assert_eq!(parse_handshake_answer(&login), None);
+        assert!(matches!(parse_incoming_packet(&login, 14).unwrap(), IncomingPacket::SetIdentity(_)));
+    }
+
     #[test]
     fn test_parse_angle() {
         let data = [125];
@@ -504,4 +539,20 @@ mod tests {
             panic!("Expected angle packet");
         }
     }
+
+    #[test]
+    fn test_parse_resync() {
+        assert!(matches!(
+            parse_incoming_packet(&crate::protocol::client::resync_frame(), 14).unwrap(),
+            IncomingPacket::Resync
+        ));
+        assert!(matches!(
+            parse_incoming_packet(&[RESYNC_OPCODE], 14).unwrap(),
+            IncomingPacket::Angle(_)
+        ));
+        assert!(matches!(
+            parse_incoming_packet(&crate::protocol::client::spectate_frame(), 14).unwrap(),
+            IncomingPacket::Spectate
+        ));
+    }
 }
@@ -4,13 +4,17 @@
 
 
 pub mod types;
+pub mod error;
 pub mod packet;
 pub mod reader;
 pub mod writer;
 pub mod incoming;
 pub mod outgoing;
+pub mod debug_text;
+pub mod client;
 
 pub use types::*;
+pub use error::ProtocolError;
 pub use packet::*;
 pub use reader::PacketReader;
 pub use writer::PacketWriter;
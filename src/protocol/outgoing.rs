@@ -1,6 +1,6 @@
 
 
-use crate::protocol::packet::{PacketSerialize, HANDSHAKE_SECRET};
+use crate::protocol::packet::{is_modern_protocol, PacketSerialize, HANDSHAKE_SECRET};
 use crate::protocol::types::*;
 use crate::protocol::writer::PacketWriter;
 use bytes::BytesMut;
@@ -10,19 +10,27 @@ use bytes::BytesMut;
 
 
 #[derive(Debug, Clone)]
-pub struct PacketPreInit;
+pub struct PacketPreInit<'a> {
+    pub secret: &'a [u8],
+}
+
+impl Default for PacketPreInit<'_> {
+    fn default() -> Self {
+        Self { secret: HANDSHAKE_SECRET }
+    }
+}
 
-impl PacketSerialize for PacketPreInit {
+impl PacketSerialize for PacketPreInit<'_> {
     fn serialize(&self, buf: &mut BytesMut) {
        
-        let mut writer = PacketWriter::with_capacity(1 + HANDSHAKE_SECRET.len());
+        let mut writer = PacketWriter::with_capacity(1 + self.secret.len());
         writer.write_u8(b'6');
-        writer.write_bytes(HANDSHAKE_SECRET);
+        writer.write_bytes(self.secret);
         buf.extend_from_slice(writer.as_bytes());
     }
 
     fn estimated_size(&self) -> usize {
-        1 + HANDSHAKE_SECRET.len()
+        1 + self.secret.len()
     }
 }
 
@@ -111,14 +119,20 @@ impl PacketSerialize for PacketInit {
         writer.write_u8(self.protocol_version);
 
        
-        writer.write_u8(self.default_msl);
-        writer.write_u16(self.snake_id);
+        if is_modern_protocol(self.protocol_version) {
+            writer.write_u8(self.default_msl);
+            writer.write_u16(self.snake_id);
+        }
 
         buf.extend_from_slice(writer.as_bytes());
     }
 
     fn estimated_size(&self) -> usize {
-        27
+        if is_modern_protocol(self.protocol_version) {
+            27
+        } else {
+            24
+        }
     }
 }
 
@@ -149,23 +163,53 @@ pub struct PacketRotation {
     pub clockwise: bool,
 }
 
+impl PacketRotation {
+   
+   
+   
+    pub fn for_changes(
+        snake_id: SnakeId,
+        angle: f32,
+        target_angle: f32,
+        speed: f32,
+        clockwise: bool,
+        changes: SnakeChanges,
+    ) -> Option<Self> {
+        if !(changes.has_angle() || changes.has_wangle() || changes.has_speed()) {
+            return None;
+        }
+
+        let include_angle = changes.has_angle() || !changes.has_wangle();
+        let include_target = changes.has_wangle() || (clockwise && include_angle);
+
+        Some(Self {
+            snake_id,
+            angle,
+            target_angle,
+            speed,
+            include_angle,
+            include_target,
+            clockwise,
+        })
+    }
+
+   
+    pub fn packet_type(&self) -> OutgoingPacketType {
+        match (self.clockwise, self.include_angle, self.include_target) {
+            (true, true, _) => OutgoingPacketType::RotCwSpeed,
+            (true, false, _) => OutgoingPacketType::RotCw,
+            (false, true, true) => OutgoingPacketType::RotCcw,
+            (false, true, false) => OutgoingPacketType::RotCcwSpeed,
+            (false, false, _) => OutgoingPacketType::RotCcwNoAng,
+        }
+    }
+}
+
 impl PacketSerialize for PacketRotation {
     fn serialize(&self, buf: &mut BytesMut) {
-        let packet_type = if self.clockwise {
-            if self.include_angle {
-                b'4'
-            } else {
-                b'5'
-            }
-        } else if self.include_angle && self.include_target {
-            b'e'
-        } else if self.include_angle {
-            b'3'
-        } else {
-            b'E'
-        };
+        let packet_type = self.packet_type() as u8;
 
-        let mut writer = PacketWriter::with_capacity(6);
+        let mut writer = PacketWriter::with_capacity(self.estimated_size());
         writer.write_u8(packet_type);
         writer.write_u16(self.snake_id);
 
@@ -182,11 +226,21 @@ impl PacketSerialize for PacketRotation {
     }
 
     fn estimated_size(&self) -> usize {
-        6
+        4 + self.include_angle as usize + self.include_target as usize
     }
 }
 
 
+// 'g' and 'G' carry no flag saying whose snake moved: the client treats the
+// shorter form as its own snake and the longer one, which leads with a snake
+// id, as somebody else's. Any field added to one of these packets must keep
+// the four lengths below distinct per opcode, or the client will misread it.
+pub const MOVE_LEN: usize = 7;
+pub const MOVE_OWN_LEN: usize = 5;
+pub const MOVE_REL_LEN: usize = 5;
+pub const MOVE_REL_OWN_LEN: usize = 3;
+
+
 #[derive(Debug, Clone)]
 pub struct PacketMove {
     pub snake_id: SnakeId,
@@ -196,7 +250,7 @@ pub struct PacketMove {
 
 impl PacketSerialize for PacketMove {
     fn serialize(&self, buf: &mut BytesMut) {
-        let mut writer = PacketWriter::with_capacity(7);
+        let mut writer = PacketWriter::with_capacity(MOVE_LEN);
         writer.write_u8(b'g');
         writer.write_u16(self.snake_id);
         writer.write_u16(self.x);
@@ -205,7 +259,7 @@ impl PacketSerialize for PacketMove {
     }
 
     fn estimated_size(&self) -> usize {
-        7
+        MOVE_LEN
     }
 }
 
@@ -219,7 +273,7 @@ pub struct PacketMoveOwn {
 
 impl PacketSerialize for PacketMoveOwn {
     fn serialize(&self, buf: &mut BytesMut) {
-        let mut writer = PacketWriter::with_capacity(5);
+        let mut writer = PacketWriter::with_capacity(MOVE_OWN_LEN);
         writer.write_u8(b'g');
         writer.write_u16(self.x);
         writer.write_u16(self.y);
@@ -227,7 +281,7 @@ impl PacketSerialize for PacketMoveOwn {
     }
 
     fn estimated_size(&self) -> usize {
-        5
+        MOVE_OWN_LEN
     }
 }
 
@@ -241,7 +295,7 @@ pub struct PacketMoveRel {
 
 impl PacketSerialize for PacketMoveRel {
     fn serialize(&self, buf: &mut BytesMut) {
-        let mut writer = PacketWriter::with_capacity(5);
+        let mut writer = PacketWriter::with_capacity(MOVE_REL_LEN);
         writer.write_u8(b'G');
         writer.write_u16(self.snake_id);
         writer.write_relative_coord(self.dx);
@@ -250,7 +304,7 @@ impl PacketSerialize for PacketMoveRel {
     }
 
     fn estimated_size(&self) -> usize {
-        5
+        MOVE_REL_LEN
     }
 }
 
@@ -264,7 +318,7 @@ pub struct PacketMoveRelOwn {
 
 impl PacketSerialize for PacketMoveRelOwn {
     fn serialize(&self, buf: &mut BytesMut) {
-        let mut writer = PacketWriter::with_capacity(3);
+        let mut writer = PacketWriter::with_capacity(MOVE_REL_OWN_LEN);
         writer.write_u8(b'G');
         writer.write_relative_coord(self.dx);
         writer.write_relative_coord(self.dy);
@@ -272,7 +326,7 @@ impl PacketSerialize for PacketMoveRelOwn {
     }
 
     fn estimated_size(&self) -> usize {
-        3
+        MOVE_REL_OWN_LEN
     }
 }
 
@@ -387,6 +441,31 @@ impl PacketSerialize for PacketRemovePart {
 
 
 
+pub const MAX_BODY_PART_DELTA: f32 = 127.0 / 2.0;
+
+
+pub fn decimate_body_parts(parts: &[(f32, f32)], stride: usize) -> Vec<(f32, f32)> {
+    if stride <= 1 || parts.len() <= 2 {
+        return parts.to_vec();
+    }
+
+    let reachable = |(ax, ay): (f32, f32), (bx, by): (f32, f32)| {
+        (bx - ax).abs() <= MAX_BODY_PART_DELTA && (by - ay).abs() <= MAX_BODY_PART_DELTA
+    };
+
+    let last = parts.len() - 1;
+    let mut kept = vec![parts[0]];
+    for i in 1..last {
+        let previous = *kept.last().unwrap();
+        if i % stride == 0 || !reachable(previous, parts[i + 1]) {
+            kept.push(parts[i]);
+        }
+    }
+    kept.push(parts[last]);
+    kept
+}
+
+
 #[derive(Debug, Clone)]
 pub struct PacketAddSnake {
     pub snake_id: SnakeId,
@@ -425,7 +504,7 @@ impl PacketSerialize for PacketAddSnake {
        
        
        
-        writer.write_u16((self.speed * 1000.0 / 32.0) as u16);
+        writer.write_speed16(self.speed);
 
        
        
@@ -443,8 +522,9 @@ impl PacketSerialize for PacketAddSnake {
 
        
         if let Some(ref custom) = self.custom_skin {
-            writer.write_u8(custom.len().min(255) as u8);
-            writer.write_bytes(&custom[..custom.len().min(255)]);
+            let custom = &custom[..custom.len().min(u8::MAX as usize)];
+            writer.write_u8(custom.len() as u8);
+            writer.write_bytes(custom);
         } else {
             writer.write_u8(0);
         }
@@ -485,7 +565,7 @@ impl PacketSerialize for PacketAddSnake {
 
     fn estimated_size(&self) -> usize {
        
-        25 + self.name.len() + self.custom_skin.as_ref().map_or(0, |s| s.len())
+        25 + self.name.len() + self.custom_skin.as_ref().map_or(0, |s| s.len().min(u8::MAX as usize))
             + if self.body_parts.is_empty() { 0 } else { 6 + (self.body_parts.len() - 1) * 2 }
     }
 }
@@ -741,37 +821,55 @@ pub struct PacketEatFood {
     pub sector_size: u16,
 }
 
+fn write_eat_food(buf: &mut BytesMut, food_x: u16, food_y: u16, sector_size: u16, eater: Option<SnakeId>) {
+    let mut writer = PacketWriter::with_capacity(7);
+    writer.write_u8(if eater.is_some() { b'<' } else { b'c' });
+
+   
+    let sx = (food_x / sector_size) as u8;
+    let sy = (food_y / sector_size) as u8;
+    let base_x = sx as u32 * sector_size as u32;
+    let base_y = sy as u32 * sector_size as u32;
+    let rx = ((food_x as u32).saturating_sub(base_x) * 256 / sector_size as u32).min(255) as u8;
+    let ry = ((food_y as u32).saturating_sub(base_y) * 256 / sector_size as u32).min(255) as u8;
+
+    writer.write_u8(sx);
+    writer.write_u8(sy);
+    writer.write_u8(rx);
+    writer.write_u8(ry);
+    if let Some(snake_id) = eater {
+        writer.write_u16(snake_id);
+    }
+
+    buf.extend_from_slice(writer.as_bytes());
+}
+
 impl PacketSerialize for PacketEatFood {
     fn serialize(&self, buf: &mut BytesMut) {
-       
-        let cmd = if self.snake_id > 0 { b'<' } else { b'c' };
+        write_eat_food(buf, self.food_x, self.food_y, self.sector_size, Some(self.snake_id));
+    }
 
-        let mut writer = PacketWriter::with_capacity(7);
-        writer.write_u8(cmd);
+    fn estimated_size(&self) -> usize {
+        7
+    }
+}
 
-       
-        let sx = (self.food_x / self.sector_size) as u8;
-        let sy = (self.food_y / self.sector_size) as u8;
-        let base_x = sx as u32 * self.sector_size as u32;
-        let base_y = sy as u32 * self.sector_size as u32;
-        let rx = ((self.food_x as u32).saturating_sub(base_x) * 256 / self.sector_size as u32).min(255) as u8;
-        let ry = ((self.food_y as u32).saturating_sub(base_y) * 256 / self.sector_size as u32).min(255) as u8;
 
-        writer.write_u8(sx);
-        writer.write_u8(sy);
-        writer.write_u8(rx);
-        writer.write_u8(ry);
 
-       
-        if self.snake_id > 0 {
-            writer.write_u16(self.snake_id);
-        }
+#[derive(Debug, Clone)]
+pub struct PacketEatFoodOwn {
+    pub food_x: u16,
+    pub food_y: u16,
+    pub sector_size: u16,
+}
 
-        buf.extend_from_slice(writer.as_bytes());
+impl PacketSerialize for PacketEatFoodOwn {
+    fn serialize(&self, buf: &mut BytesMut) {
+        write_eat_food(buf, self.food_x, self.food_y, self.sector_size, None);
     }
 
     fn estimated_size(&self) -> usize {
-        7
+        5
     }
 }
 
@@ -869,9 +967,70 @@ impl PacketSerialize for PacketMinimap {
     }
 }
 
+#[derive(Debug, Clone)]
+pub struct PacketDebugReset;
+
+impl PacketSerialize for PacketDebugReset {
+    fn serialize(&self, buf: &mut BytesMut) {
+        buf.extend_from_slice(&[OutgoingPacketType::DebugReset as u8]);
+    }
+
+    fn estimated_size(&self) -> usize {
+        1
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DebugShape {
+    Circle { x: u16, y: u16, radius: u16, color: u8 },
+    Rect { x: u16, y: u16, width: u16, height: u16, color: u8 },
+}
+
+
+
+#[derive(Debug, Clone)]
+pub struct PacketDebugDraw {
+    pub shapes: Vec<DebugShape>,
+}
+
+impl PacketSerialize for PacketDebugDraw {
+    fn serialize(&self, buf: &mut BytesMut) {
+        let mut writer = PacketWriter::with_capacity(self.estimated_size());
+        writer.write_u8(OutgoingPacketType::DebugDraw as u8);
+
+        for shape in &self.shapes {
+            match *shape {
+                DebugShape::Circle { x, y, radius, color } => {
+                    writer.write_u8(b'c');
+                    writer.write_u16(x);
+                    writer.write_u16(y);
+                    writer.write_u16(radius);
+                    writer.write_u8(color);
+                }
+                DebugShape::Rect { x, y, width, height, color } => {
+                    writer.write_u8(b'r');
+                    writer.write_u16(x);
+                    writer.write_u16(y);
+                    writer.write_u16(width);
+                    writer.write_u16(height);
+                    writer.write_u8(color);
+                }
+            }
+        }
+
+        buf.extend_from_slice(writer.as_bytes());
+    }
+
+    fn estimated_size(&self) -> usize {
+        1 + self.shapes.len() * 10
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::protocol::writer::{SPEED16_PER_UNIT, SPEED8_PER_UNIT, SPEED_SCALE};
 
     #[test]
     fn test_packet_pong() {
@@ -890,6 +1049,104 @@ mod tests {
        
         assert_eq!(bytes.len(), 24);
         assert_eq!(bytes[0], b'a');
+
+        let modern = PacketInit {
+            protocol_version: 25,
+            snake_id: 0x1234,
+            ..Default::default()
+        };
+        let bytes = modern.to_bytes();
+        assert_eq!(bytes.len(), 27);
+        assert_eq!(bytes[23], 25);
+        assert_eq!(&bytes[25..], &[0x12, 0x34]);
+    }
+
+    #[test]
+    fn test_rotation_opcode_for_change_flags() {
+        let cases = [
+            (false, true, true, false, b'e'),
+            (false, true, false, false, b'3'),
+            (false, false, true, false, b'E'),
+            (false, false, false, true, b'3'),
+            (false, false, true, true, b'E'),
+            (true, true, true, false, b'4'),
+            (true, true, false, false, b'4'),
+            (true, false, true, false, b'5'),
+            (true, false, false, true, b'4'),
+            (true, false, true, true, b'5'),
+        ];
+
+        for (clockwise, angle, wangle, speed, expected) in cases {
+            let mut changes = SnakeChanges::default();
+            if angle {
+                changes.set_angle();
+            }
+            if wangle {
+                changes.set_wangle();
+            }
+            if speed {
+                changes.set_speed();
+            }
+
+            let packet = PacketRotation::for_changes(7, 1.0, 2.0, 5.0, clockwise, changes).unwrap();
+            let bytes = packet.to_bytes();
+            assert_eq!(bytes[0], expected, "flags {:?}", (clockwise, angle, wangle, speed));
+            assert_eq!(bytes.len(), packet.estimated_size());
+        }
+
+        let mut unrelated = SnakeChanges::default();
+        unrelated.set_pos();
+        assert!(PacketRotation::for_changes(7, 1.0, 2.0, 5.0, false, unrelated).is_none());
+    }
+
+    #[test]
+    fn test_speed_decodes_the_same_in_add_and_rotation() {
+        for speed in [172.0, 300.0, 448.0] {
+            let add = PacketAddSnake {
+                snake_id: 7,
+                skin: 0,
+                angle: 0.0,
+                target_angle: 0.0,
+                speed,
+                fullness: 0.0,
+                head_x: 100.0,
+                head_y: 100.0,
+                name: String::new(),
+                custom_skin: None,
+                body_parts: vec![(90.0, 100.0)],
+            }
+            .to_bytes();
+            let added = u16::from_be_bytes([add[10], add[11]]) as f32 / SPEED16_PER_UNIT;
+
+            let mut changes = SnakeChanges::default();
+            changes.set_speed();
+            let rot = PacketRotation::for_changes(7, 0.0, 0.0, speed, false, changes).unwrap().to_bytes();
+            let rotated = *rot.last().unwrap() as f32 / SPEED8_PER_UNIT;
+
+            assert!((added - speed / SPEED_SCALE).abs() < 0.01, "speed {}", speed);
+            assert!((added - rotated).abs() <= 1.0 / SPEED8_PER_UNIT, "speed {}", speed);
+        }
+    }
+
+    #[test]
+    fn test_add_snake_truncates_long_custom_skin() {
+        let add = |custom_skin: Vec<u8>| {
+            PacketAddSnake {
+                snake_id: 7,
+                skin: 0,
+                angle: 0.0,
+                target_angle: 0.0,
+                speed: 172.0,
+                fullness: 0.0,
+                head_x: 100.0,
+                head_y: 100.0,
+                name: String::new(),
+                custom_skin: Some(custom_skin),
+                body_parts: vec![(90.0, 100.0)],
+            }
+            .to_bytes()
+        };
+        assert_eq!(add(vec![3; 300]), add(vec![3; 255]));
     }
 
     #[test]
@@ -904,4 +1161,61 @@ mod tests {
         assert_eq!(bytes.len(), 7);
         assert_eq!(bytes[0], b'g');
     }
+
+    #[test]
+    fn test_move_packets_are_told_apart_by_length() {
+        let packets: [(Box<dyn PacketSerialize>, u8, usize); 4] = [
+            (Box::new(PacketMove { snake_id: 3, x: 1, y: 2 }), b'g', MOVE_LEN),
+            (Box::new(PacketMoveOwn { x: 1, y: 2 }), b'g', MOVE_OWN_LEN),
+            (Box::new(PacketMoveRel { snake_id: 3, dx: -1, dy: 1 }), b'G', MOVE_REL_LEN),
+            (Box::new(PacketMoveRelOwn { dx: -1, dy: 1 }), b'G', MOVE_REL_OWN_LEN),
+        ];
+        for (packet, opcode, len) in &packets {
+            let bytes = packet.to_bytes();
+            assert_eq!((bytes[0], bytes.len()), (*opcode, *len));
+            assert_eq!(packet.estimated_size(), *len);
+        }
+
+        assert_eq!(&PacketMove { snake_id: 3, x: 1, y: 2 }.to_bytes()[..], &[b'g', 0, 3, 0, 1, 0, 2]);
+        assert_eq!(&PacketMoveOwn { x: 1, y: 2 }.to_bytes()[..], &[b'g', 0, 1, 0, 2]);
+        assert_eq!(&PacketMoveRel { snake_id: 3, dx: -1, dy: 1 }.to_bytes()[..], &[b'G', 0, 3, 127, 129]);
+        assert_eq!(&PacketMoveRelOwn { dx: -1, dy: 1 }.to_bytes()[..], &[b'G', 127, 129]);
+    }
+
+    #[test]
+    fn test_packet_eat_food_own_and_other() {
+        let own = PacketEatFoodOwn {
+            food_x: 1000,
+            food_y: 2000,
+            sector_size: 480,
+        }
+        .to_bytes();
+        assert_eq!(&own[..], &[b'c', 2, 4, 21, 42]);
+
+        let other = PacketEatFood {
+            snake_id: 7,
+            food_x: 1000,
+            food_y: 2000,
+            sector_size: 480,
+        }
+        .to_bytes();
+        assert_eq!(&other[..], &[b'<', 2, 4, 21, 42, 0, 7]);
+    }
+
+    #[test]
+    fn test_debug_packets() {
+        let reset = PacketDebugReset.to_bytes();
+        assert_eq!(&reset[..], &[b'0']);
+
+        let draw = PacketDebugDraw {
+            shapes: vec![
+                DebugShape::Circle { x: 100, y: 200, radius: 50, color: 1 },
+                DebugShape::Rect { x: 0, y: 480, width: 480, height: 480, color: 2 },
+            ],
+        }
+        .to_bytes();
+        assert_eq!(draw[0], b'!');
+        assert_eq!(&draw[1..9], &[b'c', 0, 100, 0, 200, 0, 50, 1]);
+        assert_eq!(&draw[9..], &[b'r', 0, 0, 1, 224, 1, 224, 1, 224, 2]);
+    }
 }
@@ -57,6 +57,43 @@ impl PacketHeader {
 pub const HANDSHAKE_SECRET: &[u8] = b"dakrtywcilopuhgrmzwsdolitualksrrarjsrzyjhrnzvfdfkrsyahjvuobhjkmzwvgoppxaagiwvscjlqoualghnuvdedozuwcdjosrcnhjprwlkfqbyegkorwtepmlstcfhksxakilruwdhhouwdchnsqecngvqpcz";
 
 
+
+pub fn decode_handshake_secret(secret: &[u8]) -> Option<[u8; 24]> {
+    if secret.len() < 18 + 2 * 23 + 1 {
+        return None;
+    }
+
+    let letter = |byte: u8, base: i32, i: i32| {
+        let byte = if byte <= 96 { byte + 32 } else { byte } as i32;
+        (byte - base - i * 34).rem_euclid(26)
+    };
+
+    let mut answer = [0u8; 24];
+    let mut carry = 0;
+    for (i, out) in answer.iter_mut().enumerate() {
+        let high = letter(secret[17 + i * 2], 98, i as i32);
+        let low = letter(secret[18 + i * 2], 115, i as i32);
+        let mut value = (high << 4) | low;
+        let offset = if value >= 97 { 97 } else { 65 };
+        value -= offset;
+        if i == 0 {
+            carry = 2 + value;
+        }
+        *out = ((value + carry).rem_euclid(26) + offset) as u8;
+        carry += 3 + value;
+    }
+    Some(answer)
+}
+
+
+pub fn expected_login_checksum(secret: &[u8]) -> [u8; 20] {
+    let answer = decode_handshake_secret(secret).unwrap_or([0; 24]);
+    let mut checksum = [0u8; 20];
+    checksum.copy_from_slice(&answer[..20]);
+    checksum
+}
+
+
 pub mod protocol {
    
     pub const VERSION_LEGACY: u8 = 14;
@@ -76,3 +113,29 @@ pub const MAX_PACKET_SIZE: usize = 65536;
 
 
 pub const MIN_PACKET_SIZE: usize = PacketHeader::SIZE;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::incoming::{parse_incoming_packet, IncomingPacket};
+
+    #[test]
+    fn test_decode_builtin_secret_known_answer() {
+        assert_eq!(&decode_handshake_secret(HANDSHAKE_SECRET).unwrap(), b"mnefrtecJcenubcesqfpspff");
+        assert_eq!(&expected_login_checksum(HANDSHAKE_SECRET), b"mnefrtecJcenubcesqfp");
+        assert_eq!(decode_handshake_secret(&HANDSHAKE_SECRET[..64]), None);
+    }
+
+    #[test]
+    fn test_login_fixture_carries_the_expected_checksum() {
+        let frame = b"s\x1f\x01\x2cmnefrtecJcenubcesqfp\x09\x05Alice";
+        let Ok(IncomingPacket::Login(login)) = parse_incoming_packet(frame, 31) else {
+            panic!("expected a login packet");
+        };
+        assert_eq!(login.protocol_version, 31);
+        assert_eq!(login.version, 300);
+        assert_eq!(login.skin, 9);
+        assert_eq!(login.nickname, "Alice");
+        assert_eq!(login.checksum, expected_login_checksum(HANDSHAKE_SECRET));
+    }
+}
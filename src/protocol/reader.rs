@@ -1,6 +1,7 @@
 
 
-use std::io::{self, Cursor, Read};
+use crate::protocol::error::ProtocolError;
+use std::io::{Cursor, Read};
 use byteorder::{BigEndian, ReadBytesExt};
 
 
@@ -32,37 +33,37 @@ impl<'a> PacketReader<'a> {
     }
 
    
-    pub fn skip(&mut self, n: usize) -> io::Result<()> {
+    pub fn skip(&mut self, n: usize) -> Result<(), ProtocolError> {
         let new_pos = self.cursor.position() + n as u64;
         if new_pos > self.cursor.get_ref().len() as u64 {
-            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "skip past end"));
+            return Err(ProtocolError::Truncated);
         }
         self.cursor.set_position(new_pos);
         Ok(())
     }
 
    
-    pub fn read_u8(&mut self) -> io::Result<u8> {
-        self.cursor.read_u8()
+    pub fn read_u8(&mut self) -> Result<u8, ProtocolError> {
+        Ok(self.cursor.read_u8()?)
     }
 
    
-    pub fn read_i8(&mut self) -> io::Result<i8> {
-        self.cursor.read_i8()
+    pub fn read_i8(&mut self) -> Result<i8, ProtocolError> {
+        Ok(self.cursor.read_i8()?)
     }
 
    
-    pub fn read_u16(&mut self) -> io::Result<u16> {
-        self.cursor.read_u16::<BigEndian>()
+    pub fn read_u16(&mut self) -> Result<u16, ProtocolError> {
+        Ok(self.cursor.read_u16::<BigEndian>()?)
     }
 
    
-    pub fn read_i16(&mut self) -> io::Result<i16> {
-        self.cursor.read_i16::<BigEndian>()
+    pub fn read_i16(&mut self) -> Result<i16, ProtocolError> {
+        Ok(self.cursor.read_i16::<BigEndian>()?)
     }
 
    
-    pub fn read_u24(&mut self) -> io::Result<u32> {
+    pub fn read_u24(&mut self) -> Result<u32, ProtocolError> {
         let b1 = self.read_u8()? as u32;
         let b2 = self.read_u8()? as u32;
         let b3 = self.read_u8()? as u32;
@@ -70,74 +71,74 @@ impl<'a> PacketReader<'a> {
     }
 
    
-    pub fn read_u32(&mut self) -> io::Result<u32> {
-        self.cursor.read_u32::<BigEndian>()
+    pub fn read_u32(&mut self) -> Result<u32, ProtocolError> {
+        Ok(self.cursor.read_u32::<BigEndian>()?)
     }
 
    
-    pub fn read_fp8(&mut self) -> io::Result<f32> {
+    pub fn read_fp8(&mut self) -> Result<f32, ProtocolError> {
         let v = self.read_i8()?;
         Ok(v as f32 / 10.0)
     }
 
    
-    pub fn read_fp16(&mut self, precision: u8) -> io::Result<f32> {
+    pub fn read_fp16(&mut self, precision: u8) -> Result<f32, ProtocolError> {
         let v = self.read_i16()?;
         let divisor = 10_i32.pow(precision as u32) as f32;
         Ok(v as f32 / divisor)
     }
 
    
-    pub fn read_fp24(&mut self) -> io::Result<f32> {
+    pub fn read_fp24(&mut self) -> Result<f32, ProtocolError> {
         let v = self.read_u24()?;
         Ok(v as f32 / 16777215.0)
     }
 
    
-    pub fn read_angle8(&mut self) -> io::Result<f32> {
+    pub fn read_angle8(&mut self) -> Result<f32, ProtocolError> {
         let v = self.read_u8()?;
         Ok(v as f32 * std::f32::consts::PI * 2.0 / 256.0)
     }
 
    
-    pub fn read_angle24(&mut self) -> io::Result<f32> {
+    pub fn read_angle24(&mut self) -> Result<f32, ProtocolError> {
         let v = self.read_u24()?;
         Ok(v as f32 * std::f32::consts::PI * 2.0 / 0xFFFFFF as f32)
     }
 
    
-    pub fn read_string(&mut self) -> io::Result<String> {
+    pub fn read_string(&mut self) -> Result<String, ProtocolError> {
         let len = self.read_u8()? as usize;
         let mut buf = vec![0u8; len];
         self.cursor.read_exact(&mut buf)?;
-        String::from_utf8(buf).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+        String::from_utf8(buf).map_err(|_| ProtocolError::InvalidUtf8)
     }
 
    
-    pub fn read_bytes(&mut self, len: usize) -> io::Result<Vec<u8>> {
+    pub fn read_bytes(&mut self, len: usize) -> Result<Vec<u8>, ProtocolError> {
         let mut buf = vec![0u8; len];
         self.cursor.read_exact(&mut buf)?;
         Ok(buf)
     }
 
    
-    pub fn read_relative_coord(&mut self) -> io::Result<i16> {
+    pub fn read_relative_coord(&mut self) -> Result<i16, ProtocolError> {
         let v = self.read_u8()?;
         Ok(v as i16 - 128)
     }
 
    
-    pub fn read_remaining(&mut self) -> io::Result<Vec<u8>> {
+    pub fn read_remaining(&mut self) -> Result<Vec<u8>, ProtocolError> {
         let remaining = self.remaining();
         self.read_bytes(remaining)
     }
 
    
-    pub fn peek_u8(&self) -> io::Result<u8> {
+    pub fn peek_u8(&self) -> Result<u8, ProtocolError> {
         let pos = self.cursor.position() as usize;
         let data = self.cursor.get_ref();
         if pos >= data.len() {
-            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "peek past end"));
+            return Err(ProtocolError::Truncated);
         }
         Ok(data[pos])
     }
@@ -218,6 +219,22 @@ mod tests {
         assert_eq!(reader.read_u24().unwrap(), 0x123456);
     }
 
+    #[test]
+    fn test_reader_errors() {
+        let mut reader = PacketReader::new(&[0x12]);
+        assert_eq!(reader.read_u16(), Err(ProtocolError::Truncated));
+
+        let mut reader = PacketReader::new(&[5, b'a', b'b']);
+        assert_eq!(reader.read_string(), Err(ProtocolError::Truncated));
+
+        let mut reader = PacketReader::new(&[2, 0xff, 0xfe]);
+        assert_eq!(reader.read_string(), Err(ProtocolError::InvalidUtf8));
+
+        let mut reader = PacketReader::new(&[]);
+        assert_eq!(reader.skip(1), Err(ProtocolError::Truncated));
+        assert_eq!(reader.peek_u8(), Err(ProtocolError::Truncated));
+    }
+
     #[test]
     fn test_parse_stacked_packets() {
        
@@ -1,11 +1,57 @@
 
 
+use crate::protocol::error::ProtocolError;
 use std::f32::consts::PI;
 
 
 pub type SnakeId = u16;
 
 
+pub const SKIN_COUNT: u8 = 66;
+
+
+pub fn normalize_skin(skin: u8) -> u8 {
+    skin % SKIN_COUNT
+}
+
+
+pub const CUSTOM_SKIN_COLOR_COUNT: u8 = 40;
+
+
+pub fn validate_custom_skin(skin: &[u8], max_len: usize) -> Result<(), ProtocolError> {
+    if skin.len() > max_len {
+        return Err(ProtocolError::CustomSkinTooLong {
+            len: skin.len(),
+            max: max_len,
+        });
+    }
+    match skin.iter().find(|&&color| color >= CUSTOM_SKIN_COLOR_COUNT) {
+        Some(&color) => Err(ProtocolError::InvalidSkinColor(color)),
+        None => Ok(()),
+    }
+}
+
+
+pub const MAX_NICKNAME_CHARS: usize = 24;
+
+
+pub const MAX_VICTORY_MESSAGE_CHARS: usize = 140;
+
+
+pub fn sanitize_text(text: &str, max_chars: usize) -> String {
+    let cleaned: String = text.chars().filter(|c| !c.is_control()).take(max_chars).collect();
+    cleaned.trim().to_string()
+}
+
+
+pub const FULLNESS_FP24_SCALE: f32 = 16777215.0;
+
+
+pub fn fullness_to_fam(fullness: u32) -> f32 {
+    (fullness as f32 / FULLNESS_FP24_SCALE).min(1.0)
+}
+
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(u8)]
 pub enum IncomingPacketType {
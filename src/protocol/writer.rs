@@ -139,7 +139,7 @@ impl PacketWriter {
 
    
     pub fn write_relative_coord(&mut self, v: i16) -> &mut Self {
-        let encoded = (v + 128).clamp(0, 255) as u8;
+        let encoded = (v.clamp(*RELATIVE_COORD_RANGE.start(), *RELATIVE_COORD_RANGE.end()) + 128) as u8;
         self.buf.put_u8(encoded);
         self
     }
@@ -153,7 +153,15 @@ impl PacketWriter {
 
    
     pub fn write_speed(&mut self, speed: f32) -> &mut Self {
-        self.buf.put_u8((speed / 18.0) as u8);
+        let encoded = speed / SPEED_SCALE * SPEED8_PER_UNIT;
+        self.buf.put_u8(encoded.clamp(0.0, u8::MAX as f32) as u8);
+        self
+    }
+
+   
+    pub fn write_speed16(&mut self, speed: f32) -> &mut Self {
+        let encoded = speed / SPEED_SCALE * SPEED16_PER_UNIT;
+        self.buf.put_u16(encoded.clamp(0.0, u16::MAX as f32) as u16);
         self
     }
 }
@@ -183,6 +191,37 @@ pub fn create_packet(client_time: u16, packet_type: u8, payload_capacity: usize)
     writer
 }
 
+
+pub const SPEED_SCALE: f32 = 32.0;
+
+
+pub const SPEED8_PER_UNIT: f32 = 18.0;
+
+
+pub const SPEED16_PER_UNIT: f32 = 1000.0;
+
+
+pub const RELATIVE_COORD_RANGE: std::ops::RangeInclusive<i16> = -128..=127;
+
+
+pub const MAX_STACKED_PACKET_LEN: usize = (32 << 8) - 1;
+
+
+
+pub fn append_stacked_packet(frame: &mut Vec<u8>, packet: &[u8]) -> bool {
+    let len = packet.len();
+    if len + 32 <= u8::MAX as usize {
+        frame.push((len + 32) as u8);
+    } else if len <= MAX_STACKED_PACKET_LEN {
+        frame.push((len >> 8) as u8);
+        frame.push(len as u8);
+    } else {
+        return false;
+    }
+    frame.extend_from_slice(packet);
+    true
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -229,4 +268,45 @@ mod tests {
         writer.write_relative_coord(127);
         assert_eq!(writer.as_bytes()[0], 255);
     }
+
+    #[test]
+    fn test_relative_range_round_trips_exactly() {
+        for v in -1000i16..=1000 {
+            let mut writer = PacketWriter::new();
+            writer.write_relative_coord(v);
+            let decoded = crate::protocol::reader::PacketReader::new(writer.as_bytes())
+                .read_relative_coord()
+                .unwrap();
+            assert_eq!(decoded == v, RELATIVE_COORD_RANGE.contains(&v), "v = {}", v);
+        }
+    }
+
+    #[test]
+    fn test_write_speed_saturates() {
+        let mut writer = PacketWriter::new();
+        writer.write_speed(1.0e6).write_speed(-50.0).write_speed(448.0);
+        assert_eq!(writer.as_bytes(), &[255, 0, 252]);
+    }
+
+    #[test]
+    fn test_stacked_packets_round_trip() {
+        let small = [b'G', 0, 1, 128, 130];
+        let large = vec![b'F'; 300];
+
+        let mut frame = Vec::new();
+        assert!(append_stacked_packet(&mut frame, &small));
+        assert!(append_stacked_packet(&mut frame, &large));
+        assert_eq!(frame[0], 32 + 5);
+        assert_eq!(&frame[6..8], &[1, 44]);
+
+        let packets = crate::protocol::reader::parse_stacked_packets(&frame, 0);
+        assert_eq!(packets, vec![&small[..], &large[..]]);
+    }
+
+    #[test]
+    fn test_stacked_packet_too_large() {
+        let mut frame = Vec::new();
+        assert!(!append_stacked_packet(&mut frame, &vec![0; MAX_STACKED_PACKET_LEN + 1]));
+        assert!(frame.is_empty());
+    }
 }
@@ -0,0 +1,159 @@
+
+
+
+use parking_lot::RwLock;
+use std::collections::BTreeSet;
+use std::io;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+
+#[derive(Debug, Error)]
+pub enum BanListError {
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    #[error("line {line}: {text:?} is not an IP address")]
+    Malformed { line: usize, text: String },
+}
+
+
+#[derive(Debug, Default)]
+pub struct BanList {
+    path: Option<PathBuf>,
+    banned: RwLock<BTreeSet<IpAddr>>,
+}
+
+impl BanList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+   
+    pub fn parse(text: &str) -> Result<BTreeSet<IpAddr>, BanListError> {
+        let mut banned = BTreeSet::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if line.is_empty() {
+                continue;
+            }
+            let ip = line.parse().map_err(|_| BanListError::Malformed {
+                line: i + 1,
+                text: line.to_string(),
+            })?;
+            banned.insert(ip);
+        }
+        Ok(banned)
+    }
+
+   
+    pub fn load(path: &Path) -> Result<Self, BanListError> {
+        let banned = match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text)?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => BTreeSet::new(),
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Self {
+            path: Some(path.to_path_buf()),
+            banned: RwLock::new(banned),
+        })
+    }
+
+    pub fn is_banned(&self, ip: IpAddr) -> bool {
+        self.banned.read().contains(&ip)
+    }
+
+    pub fn len(&self) -> usize {
+        self.banned.read().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.banned.read().is_empty()
+    }
+
+   
+    pub fn ban(&self, ip: IpAddr) -> Result<bool, BanListError> {
+        let mut banned = self.banned.write();
+        if !banned.insert(ip) {
+            return Ok(false);
+        }
+        self.save(&banned)?;
+        Ok(true)
+    }
+
+   
+    pub fn unban(&self, ip: IpAddr) -> Result<bool, BanListError> {
+        let mut banned = self.banned.write();
+        if !banned.remove(&ip) {
+            return Ok(false);
+        }
+        self.save(&banned)?;
+        Ok(true)
+    }
+
+    fn save(&self, banned: &BTreeSet<IpAddr>) -> Result<(), BanListError> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let text: String = banned.iter().map(|ip| format!("{}\n", ip)).collect();
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, text)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+
+
+pub fn client_ip(peer: IpAddr, forwarded: Option<&str>, trusted_proxies: &[IpAddr]) -> IpAddr {
+    if !trusted_proxies.contains(&peer) {
+        return peer;
+    }
+    forwarded
+        .and_then(|value| value.rsplit(',').next())
+        .and_then(|last| last.trim().parse().ok())
+        .unwrap_or(peer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ban_list_persists_changes() {
+        let path = std::env::temp_dir().join(format!("slither_bans_{}.txt", std::process::id()));
+        std::fs::write(&path, "# moderation\n10.0.0.1\n\n::1 # loopback\n").unwrap();
+
+        let bans = BanList::load(&path).unwrap();
+        let (a, b): (IpAddr, IpAddr) = ("10.0.0.1".parse().unwrap(), "10.0.0.2".parse().unwrap());
+        assert!(bans.is_banned(a) && bans.is_banned("::1".parse().unwrap()));
+        assert!(!bans.is_banned(b));
+
+        assert!(bans.ban(b).unwrap());
+        assert!(!bans.ban(b).unwrap());
+        assert!(bans.unban(a).unwrap());
+        assert!(!bans.unban(a).unwrap());
+
+        let reloaded = BanList::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(reloaded.is_banned(b) && !reloaded.is_banned(a));
+        assert_eq!(reloaded.len(), 2);
+
+        assert!(matches!(
+            BanList::parse("10.0.0.1\nnot-an-ip\n"),
+            Err(BanListError::Malformed { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_client_ip_only_trusts_configured_proxies() {
+        let proxy: IpAddr = "127.0.0.1".parse().unwrap();
+        let other: IpAddr = "192.168.1.5".parse().unwrap();
+        let forwarded = Some("1.2.3.4, 10.0.0.9");
+
+        assert_eq!(client_ip(proxy, forwarded, &[proxy]), "10.0.0.9".parse::<IpAddr>().unwrap());
+        assert_eq!(client_ip(other, forwarded, &[proxy]), other);
+        assert_eq!(client_ip(proxy, Some("garbage"), &[proxy]), proxy);
+        assert_eq!(client_ip(proxy, None, &[proxy]), proxy);
+    }
+}
@@ -1,21 +1,141 @@
 
 
 use crate::config::{timing, GameConfig};
-use crate::game::sector::SectorEvent;
-use crate::game::world::SharedWorld;
-use crate::game::Snake;
-use crate::protocol::incoming::{parse_incoming_packet, IncomingPacket, LoginPacket};
+use crate::game::sector::{SectorEvent, SectorRange};
+use crate::game::world::{SharedWorld, World};
+use crate::game::{Food, HallOfFame, Snake};
+use crate::protocol::incoming::{
+    parse_handshake_answer, parse_incoming_packet, IncomingPacket, LoginPacket, RotationPacket,
+    SetIdentityPacket,
+};
+use crate::protocol::debug_text::parse_debug_text;
+use crate::protocol::error::ProtocolError;
 use crate::protocol::outgoing::*;
-use crate::protocol::packet::PacketSerialize;
-use crate::protocol::types::SnakeId;
-use crate::server::session::{SessionId, SessionManager, SessionState, SharedSessionManager};
+use crate::protocol::packet::{expected_login_checksum, is_modern_protocol, PacketSerialize};
+use crate::protocol::reader::parse_protocol14_header;
+use crate::protocol::types::{
+    fullness_to_fam, normalize_skin, sanitize_text, validate_custom_skin,
+    GameEndStatus, SnakeChanges, SnakeId, SnakeRemoveStatus, MAX_VICTORY_MESSAGE_CHARS,
+};
+use crate::protocol::writer::RELATIVE_COORD_RANGE;
+use crate::server::bans::BanList;
+use crate::server::session::{
+    Session, SessionId, SessionManager, SessionState, SharedSessionManager,
+};
 use bytes::BytesMut;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::Instant;
 use tokio::sync::mpsc;
 use tracing::{debug, info, warn};
 
 
+
+struct SnakeFrame {
+    id: SnakeId,
+    head: (f32, f32),
+    head_u16: (u16, u16),
+    head_delta: (i16, i16),
+    angle: f32,
+    target_angle: f32,
+    speed: f32,
+    fullness: u32,
+    changes: SnakeChanges,
+}
+
+
+struct PlayerFrame {
+    session_id: SessionId,
+    snake_id: SnakeId,
+    pos: (f32, f32),
+    enter_range: Option<SectorRange>,
+    leave_range: Option<SectorRange>,
+    changed: Vec<usize>,
+}
+
+
+struct FrameSnapshot {
+    sector_size: u16,
+    snakes: Vec<SnakeFrame>,
+    eaten_food: Vec<(SnakeId, Food)>,
+    new_food: Vec<(Food, (u8, u8))>,
+    died: Vec<(SnakeId, (f32, f32))>,
+    players: Vec<PlayerFrame>,
+}
+
+impl FrameSnapshot {
+    fn capture(world: &World, players: &[(SessionId, SnakeId)], view_radius: f32) -> Self {
+        let snakes: Vec<SnakeFrame> = world
+            .changed_snakes()
+            .iter()
+            .filter_map(|id| world.get_snake(*id))
+            .filter(|snake| !snake.dead)
+            .map(|snake| SnakeFrame {
+                id: snake.id,
+                head: snake.head_pos(),
+                head_u16: snake.head_pos_u16(),
+                head_delta: snake.head_delta(),
+                angle: snake.angle,
+                target_angle: snake.target_angle,
+                speed: snake.speed,
+                fullness: snake.fullness,
+                changes: snake.changes,
+            })
+            .collect();
+
+        let died = world
+            .died_snakes()
+            .iter()
+            .filter_map(|id| Some((*id, world.get_snake(*id)?.head_pos())))
+            .collect();
+
+        let index: HashMap<SnakeId, usize> =
+            snakes.iter().enumerate().map(|(i, snake)| (snake.id, i)).collect();
+
+        let players = players
+            .iter()
+            .filter_map(|&(session_id, snake_id)| {
+                let pos = world.get_snake(snake_id)?.head_pos();
+                let sector_range = world.sectors.sector_range_in_viewport(pos.0, pos.1, view_radius);
+                let (enter_radius, leave_radius) = world.config.sector_view_radii(view_radius);
+                let enter_range = world.sectors.sector_range_in_viewport(pos.0, pos.1, enter_radius);
+                let leave_range = world.sectors.sector_range_in_viewport(pos.0, pos.1, leave_radius);
+                let mut changed: Vec<usize> = sector_range
+                    .map(|range| {
+                        world.changed_snakes_in(range).filter_map(|id| index.get(&id).copied()).collect()
+                    })
+                    .unwrap_or_default();
+                changed.sort_unstable();
+                changed.dedup();
+                Some(PlayerFrame {
+                    session_id,
+                    snake_id,
+                    pos,
+                    enter_range,
+                    leave_range,
+                    changed,
+                })
+            })
+            .collect();
+
+        Self {
+            sector_size: world.config.sector_size,
+            snakes,
+            eaten_food: world.eaten_food().to_vec(),
+            new_food: world
+                .new_food()
+                .iter()
+                .map(|&food| (food, world.sectors.world_to_sector(food.x as f32, food.y as f32)))
+                .collect(),
+            died,
+            players,
+        }
+    }
+}
+
+
 pub struct GameHandler {
    
     world: SharedWorld,
@@ -24,32 +144,110 @@ pub struct GameHandler {
    
     config: GameConfig,
    
-    last_leaderboard: Instant,
+    timers: Mutex<PeriodicTimers>,
+   
+    record_length: AtomicUsize,
+   
+    paused: AtomicBool,
+   
+    bans: BanList,
+}
+
+
+fn load_ban_list(config: &GameConfig) -> BanList {
+    let Some(path) = &config.ban_file else {
+        return BanList::new();
+    };
+    match BanList::load(path) {
+        Ok(bans) => {
+            info!("Loaded {} banned IPs from {}", bans.len(), path.display());
+            bans
+        }
+        Err(e) => {
+            warn!("Could not load ban list from {}: {}", path.display(), e);
+            BanList::new()
+        }
+    }
+}
+
+
+struct PeriodicTimers {
+    leaderboard: Instant,
+    minimap: Instant,
+    resync: Instant,
+    announcements: Vec<Instant>,
+}
+
+impl PeriodicTimers {
+    fn new(now: Instant, announcement_count: usize) -> Self {
+        Self {
+            leaderboard: now,
+            minimap: now,
+            resync: now,
+            announcements: vec![now; announcement_count],
+        }
+    }
+
    
-    last_minimap: Instant,
+    fn take_due(last: &mut Instant, now: Instant, interval_ms: u64) -> bool {
+        if interval_ms == 0 || (now.duration_since(*last).as_millis() as u64) < interval_ms {
+            return false;
+        }
+        *last = now;
+        true
+    }
 }
 
 impl GameHandler {
    
     pub fn new(world: SharedWorld, sessions: SharedSessionManager, config: GameConfig) -> Self {
         Self {
+            timers: Mutex::new(PeriodicTimers::new(Instant::now(), config.announcements.len())),
             world,
             sessions,
+            record_length: AtomicUsize::new(0),
+            paused: AtomicBool::new(false),
+            bans: load_ban_list(&config),
             config,
-            last_leaderboard: Instant::now(),
-            last_minimap: Instant::now(),
         }
     }
 
    
+    pub fn config(&self) -> &GameConfig {
+        &self.config
+    }
+
+   
+    pub fn bans(&self) -> &BanList {
+        &self.bans
+    }
+
+   
+    pub fn take_hall_of_fame_changes(&self) -> Option<HallOfFame> {
+        self.world.write().take_hall_of_fame_changes()
+    }
+
+   
+    pub fn set_paused(&self, paused: bool) {
+        if self.paused.swap(paused, Ordering::Relaxed) != paused {
+            info!(paused, "Simulation pause toggled");
+        }
+    }
+
+   
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+   
     pub fn on_connect(&self, session_id: SessionId) {
-        info!("New connection: session {}", session_id);
+        info!(session_id, "New connection");
        
     }
 
    
     pub fn on_disconnect(&self, session_id: SessionId) {
-        debug!("Disconnection: session {}", session_id);
+        debug!(session_id, "Disconnection");
 
        
         let snake_id = self
@@ -68,39 +266,131 @@ impl GameHandler {
     }
 
    
-    pub fn on_packet(&self, session_id: SessionId, data: &[u8]) {
+    pub fn on_packet(&self, session_id: SessionId, data: &[u8]) -> Result<(), ProtocolError> {
        
         if let Some(mut session) = self.sessions.get_mut(session_id) {
             session.touch();
         }
 
        
-        if !data.is_empty() {
-            let hex_preview: String = data.iter()
-                .take(32)
-                .map(|b| format!("{:02x}", b))
-                .collect::<Vec<_>>()
-                .join(" ");
-            debug!("Packet from session {}: len={} cmd={} data=[{}]",
-                   session_id, data.len(), data[0], hex_preview);
+        if data.is_empty() {
+            return Ok(());
         }
 
        
-        let protocol_version = self
+        let hex_preview: String = data.iter()
+            .take(32)
+            .map(|b| format!("{:02x}", b))
+            .collect::<Vec<_>>()
+            .join(" ");
+        debug!(session_id, len = data.len(), cmd = data[0], data = %hex_preview, "Packet received");
+
+        let awaiting_answer = self
             .sessions
             .get(session_id)
-            .map(|s| s.protocol.protocol_version)
-            .unwrap_or(14);
+            .is_some_and(|s| s.state == SessionState::Handshake && !s.protocol.challenge_answered);
+        if let Some(answer) = parse_handshake_answer(data).filter(|_| awaiting_answer) {
+            self.handle_packet(session_id, IncomingPacket::HandshakeAnswer(answer));
+            return Ok(());
+        }
+
+       
+        let (protocol_version, buffered) = self
+            .sessions
+            .get(session_id)
+            .map(|s| {
+                let buffered = self.config.input_buffer_ms > 0 && s.is_playing() && s.protocol.want_etm;
+                (s.protocol.protocol_version, buffered)
+            })
+            .unwrap_or((14, false));
+
+        let (etm, data) = match buffered.then(|| parse_protocol14_header(data, false, true)) {
+            Some((_, Some(etm), offset)) if data.len() > offset => (Some(etm), &data[offset..]),
+            _ => (None, data),
+        };
 
        
-        match parse_incoming_packet(data, protocol_version) {
+        match parse_incoming_packet(data, protocol_version).and_then(|p| self.validate_packet(p)) {
+            Ok(packet) => {
+                debug!(session_id, ?packet, "Parsed packet");
+                match etm {
+                    Some(etm) if packet.is_movement() => self.buffer_input(session_id, packet, etm),
+                    _ => self.handle_packet(session_id, packet),
+                }
+                Ok(())
+            }
+            Err(e) if !e.is_fatal() => {
+                if let Some(suppressed) = self.parse_error_log(session_id) {
+                    debug!(session_id, len = data.len(), error = %e, suppressed, "Ignoring packet");
+                }
+                Ok(())
+            }
+            Err(e) => {
+                warn!(session_id, len = data.len(), error = %e, "Failed to parse packet");
+                Err(e)
+            }
+        }
+    }
+
+   
+    fn parse_error_log(&self, session_id: SessionId) -> Option<u32> {
+        self.sessions.get_mut(session_id).and_then(|mut s| s.take_parse_error_log())
+    }
+
+   
+    fn buffer_input(&self, session_id: SessionId, packet: IncomingPacket, etm: u16) {
+        let now_tick = self.world.read().tick_count;
+        let frame_time_ms = self.config.frame_time_ms;
+        let max_ticks = self.config.input_buffer_ms / frame_time_ms.max(1);
+        if let Some(mut session) = self.sessions.get_mut(session_id) {
+            session.input_buffer.push(packet, etm, now_tick, frame_time_ms, max_ticks);
+        }
+    }
+
+   
+    fn apply_buffered_inputs(&self) {
+        let tick = self.world.read().tick_count;
+        for (session_id, packet) in self.sessions.drain_due_inputs(tick) {
+            self.handle_packet(session_id, packet);
+        }
+    }
+
+   
+    fn validate_packet(&self, packet: IncomingPacket) -> Result<IncomingPacket, ProtocolError> {
+        if let Some(skin) = packet.custom_skin() {
+            validate_custom_skin(skin, self.config.max_custom_skin_len)?;
+        }
+        Ok(packet)
+    }
+
+   
+    pub fn on_text(&self, session_id: SessionId, text: &str) -> Result<(), ProtocolError> {
+        if text.trim().is_empty() {
+            if let Some(mut session) = self.sessions.get_mut(session_id) {
+                session.touch();
+            }
+            return Ok(());
+        }
+
+        if !self.config.debug_text {
+            return self.on_packet(session_id, text.as_bytes());
+        }
+
+        if let Some(mut session) = self.sessions.get_mut(session_id) {
+            session.touch();
+        }
+
+        match parse_debug_text(text) {
             Ok(packet) => {
-                debug!("Parsed packet: {:?}", packet);
+                debug!(session_id, ?packet, "Parsed debug text command");
                 self.handle_packet(session_id, packet);
+                Ok(())
             }
             Err(e) => {
-                warn!("Failed to parse packet from session {}: {} (data len={})",
-                      session_id, e, data.len());
+                if let Some(suppressed) = self.parse_error_log(session_id) {
+                    debug!(session_id, error = %e, text, suppressed, "Ignoring debug text");
+                }
+                Ok(())
             }
         }
     }
@@ -113,8 +403,13 @@ impl GameHandler {
             }
             IncomingPacket::StartLogin => self.handle_start_login(session_id),
             IncomingPacket::Login(login) => self.handle_login(session_id, login),
-            IncomingPacket::SetIdentity(identity) => {
-                self.handle_identity(session_id, identity.skin, identity.nickname, identity.protocol_version)
+            IncomingPacket::SetIdentity(_) if self.config.verify_checksum => {
+                info!(session_id, "Identity without checksum, rejecting");
+                self.reject_login(session_id);
+            }
+            IncomingPacket::SetIdentity(identity) => self.handle_identity(session_id, identity),
+            IncomingPacket::Rotation(rot) if self.rotation_is_absolute(session_id, &rot) => {
+                self.handle_angle(session_id, rot.absolute_angle())
             }
             IncomingPacket::Rotation(rot) => self.handle_rotation(session_id, rot),
             IncomingPacket::Angle(ang) => self.handle_angle(session_id, ang.to_radians()),
@@ -122,15 +417,20 @@ impl GameHandler {
             IncomingPacket::StopAcceleration => self.handle_acceleration(session_id, false),
             IncomingPacket::Ping => self.handle_ping(session_id),
             IncomingPacket::VictoryMessage(msg) => self.handle_victory_message(session_id, msg),
-            IncomingPacket::Unknown(cmd, data) => {
-                debug!("Unknown packet cmd={} len={} from session {}", cmd, data.len(), session_id);
+            IncomingPacket::HandshakeAnswer(_) => self.handle_handshake_answer(session_id),
+            IncomingPacket::Resync => self.handle_resync(session_id),
+            IncomingPacket::Spectate => {
+                self.spectate(session_id);
+            }
+            IncomingPacket::Feed { session_id: target, count } => {
+                self.handle_feed(target.unwrap_or(session_id), count)
             }
         }
     }
 
    
     fn handle_protocol_mode(&self, session_id: SessionId, want_etm: bool) {
-        info!("ProtocolMode from session {}: want_etm={}", session_id, want_etm);
+        info!(session_id, want_etm, "ProtocolMode");
         if let Some(mut session) = self.sessions.get_mut(session_id) {
             session.protocol.want_etm = want_etm;
             session.protocol.handshake_complete = true;
@@ -138,35 +438,80 @@ impl GameHandler {
     }
 
     fn handle_start_login(&self, session_id: SessionId) {
-        info!("StartLogin from session {}, sending PreInit", session_id);
+        info!(session_id, "StartLogin, sending PreInit");
 
        
        
-        if let Some(mut session) = self.sessions.get_mut(session_id) {
-            if !session.protocol.handshake_complete {
-                session.protocol.want_etm = true;
+        match self.sessions.get_mut(session_id) {
+            Some(session) if session.state == SessionState::Dead && !self.config.player_respawn => {
+                debug!(session_id, "Respawn disabled, ignoring StartLogin");
+                return;
             }
-            session.protocol.handshake_complete = true;
-            session.state = SessionState::Handshake;
+            Some(mut session) => {
+                if !session.protocol.handshake_complete {
+                    session.protocol.want_etm = true;
+                }
+                session.protocol.handshake_complete = true;
+                session.state = SessionState::Handshake;
+                session.handshake_started_at = Instant::now();
+            }
+            None => return,
         }
 
-        let packet = PacketPreInit;
+        let packet = PacketPreInit {
+            secret: self.config.preinit_secret(),
+        };
         self.send_packet(session_id, &packet);
     }
 
+    fn handle_handshake_answer(&self, session_id: SessionId) {
+        if let Some(mut session) = self.sessions.get_mut(session_id) {
+            if session.state == SessionState::Handshake {
+                debug!(session_id, "Handshake answer");
+                session.protocol.challenge_answered = true;
+            } else {
+                debug!(session_id, state = ?session.state, "Ignoring handshake answer");
+            }
+        }
+    }
+
     fn handle_login(&self, session_id: SessionId, login: LoginPacket) {
         info!(
-            "Login from session {}: name={}, skin={}, version={}",
-            session_id, login.nickname, login.skin, login.protocol_version
+            session_id,
+            name = %login.nickname,
+            skin = login.skin,
+            protocol_version = login.protocol_version,
+            "Login"
         );
 
-        self.handle_identity(session_id, login.skin, login.nickname, login.protocol_version);
+        if self.config.verify_checksum && login.checksum != expected_login_checksum(self.config.preinit_secret()) {
+            info!(session_id, "Login checksum mismatch, rejecting");
+            self.reject_login(session_id);
+            return;
+        }
+
+        self.handle_identity(
+            session_id,
+            SetIdentityPacket {
+                protocol_version: login.protocol_version,
+                skin: login.skin,
+                nickname: login.nickname,
+                custom_skin: login.custom_skin,
+            },
+        );
     }
 
    
-    fn handle_identity(&self, session_id: SessionId, skin: u8, name: String, protocol_version: u8) {
-        info!("Identity setup for session {}: name={}, skin={}, protocol={}",
-              session_id, name, skin, protocol_version);
+    fn handle_identity(&self, session_id: SessionId, identity: SetIdentityPacket) {
+        let requested = normalize_skin(identity.skin);
+        let skin = self.config.skin_registry.resolve(requested);
+        if skin != requested {
+            info!(session_id, requested, skin, "Locked skin requested, remapping");
+        }
+        let name = sanitize_text(&identity.nickname, self.config.max_name_chars);
+        let protocol_version = identity.protocol_version;
+        let custom_skin = identity.custom_skin.filter(|s| !s.is_empty());
+        info!(session_id, name = %name, skin, protocol_version, "Identity setup");
 
        
         {
@@ -174,36 +519,85 @@ impl GameHandler {
                 Some(s) => s,
                 None => return,
             };
+            if session.snake_id.is_some() {
+                debug!(session_id, "Already playing, ignoring identity");
+                return;
+            }
+            if session.state == SessionState::Dead {
+                if !self.config.player_respawn {
+                    debug!(session_id, "Respawn disabled, ignoring identity");
+                    return;
+                }
+                info!(session_id, "Respawning");
+                session.death_time = None;
+            }
+            if !session.protocol.handshake_complete {
+                info!(session_id, "Identity before handshake, assuming default framing");
+                session.protocol.want_etm = true;
+                session.protocol.handshake_complete = true;
+            }
+            session.sector_tracker.clear();
+            session.input_buffer.clear();
             session.name = name.clone();
             session.skin = skin;
-            session.protocol.protocol_version = self.config.protocol_version;
-            session.is_modern_protocol = self.config.protocol_version >= 25;
+            session.custom_skin = custom_skin.clone();
+            let negotiated = self.config.negotiate_protocol_version(protocol_version);
+            session.protocol.protocol_version = negotiated;
+            session.is_modern_protocol = is_modern_protocol(negotiated);
         }
 
        
-        let snake_id = {
-            let mut world = self.world.write();
-            world.create_snake(name, skin)
-        };
+        if !self.sessions.reserve_player_slot(self.config.max_players as usize) {
+            info!(session_id, max_players = self.config.max_players, "Server at capacity, rejecting");
+            self.reject_login(session_id);
+            return;
+        }
 
        
-        self.sessions.set_snake(session_id, snake_id);
+        let created = {
+            let mut world = self.world.write();
+            let created = world.create_snake(name, skin);
+            if let Some(snake) = created.and_then(|id| world.get_snake_mut(id)) {
+                snake.custom_skin = custom_skin;
+            }
+            created
+        };
+        let Some(snake_id) = created else {
+            warn!(session_id, "No room to spawn");
+            self.sessions.release_player_slot();
+            self.reject_login(session_id);
+            return;
+        };
 
        
         self.send_init(session_id, snake_id);
 
        
         self.send_initial_state(session_id, snake_id);
+
+       
+       
+        self.sessions.claim_player_slot(session_id, snake_id);
+        let alive = self.world.read().get_snake(snake_id).is_some_and(|s| !s.dead);
+        if !alive {
+            self.end_game(snake_id);
+        }
     }
 
    
     fn send_init(&self, session_id: SessionId, snake_id: SnakeId) {
+        let protocol_version = self
+            .sessions
+            .get(session_id)
+            .map(|s| s.protocol.protocol_version)
+            .unwrap_or(self.config.protocol_version);
+
         let packet = PacketInit {
             game_radius: self.config.game_radius,
             max_snake_parts: self.config.max_snake_parts,
             sector_size: self.config.sector_size,
             sector_count_along_edge: self.config.sector_count_along_edge,
-            protocol_version: self.config.protocol_version,
+            protocol_version,
             snake_id, 
             ..Default::default()
         };
@@ -225,10 +619,11 @@ impl GameHandler {
 
        
         let view_radius = 2000.0;
-        let sectors = world.sectors.sectors_in_viewport(head_x, head_y, view_radius);
+        let (enter_radius, leave_radius) = self.config.sector_view_radii(view_radius);
+        let sectors = world.sectors.sectors_in_viewport(head_x, head_y, enter_radius);
 
         for (sx, sy) in &sectors {
-            self.send_packet(session_id, &PacketAddSector { x: *sx, y: *sy });
+            self.queue_packet(session_id, &PacketAddSector { x: *sx, y: *sy });
 
            
             if let Some(sector) = world.sectors.get(*sx, *sy) {
@@ -237,7 +632,7 @@ impl GameHandler {
                     .collect();
 
                 if !foods.is_empty() {
-                    self.send_packet(session_id, &PacketSetFood {
+                    self.queue_packet(session_id, &PacketSetFood {
                         sector_x: *sx,
                         sector_y: *sy,
                         sector_size: self.config.sector_size,
@@ -246,16 +641,27 @@ impl GameHandler {
                 }
             }
         }
+        self.flush_packets(session_id);
 
        
         if let Some(mut session) = self.sessions.get_mut(session_id) {
             session
                 .sector_tracker
-                .update(&world.sectors, head_x, head_y, view_radius);
+                .update(&world.sectors, head_x, head_y, enter_radius, leave_radius);
         }
 
+        self.send_snakes_in_view(session_id, &world, player_snake, view_radius);
+
+       
+        self.send_leaderboard(session_id, &world, snake_id);
+    }
+
+   
+    fn send_snakes_in_view(&self, session_id: SessionId, world: &World, player_snake: &Snake, view_radius: f32) {
+        let (head_x, head_y) = player_snake.head_pos();
+
        
-        self.send_snake(session_id, player_snake);
+        self.send_snake(session_id, player_snake, 1);
 
        
         let (hx, hy) = player_snake.head_pos_u16();
@@ -265,84 +671,113 @@ impl GameHandler {
         });
 
        
-        for (id, snake) in world.snakes() {
-            if *id != snake_id && !snake.dead {
-                let (sx, sy) = snake.head_pos();
-                if (sx - head_x).abs() < view_radius && (sy - head_y).abs() < view_radius {
-                    self.send_snake(session_id, snake);
-                }
+        for snake in world.visible_snakes((head_x, head_y), view_radius) {
+            if snake.id == player_snake.id {
+                continue;
             }
+            let (sx, sy) = snake.head_pos();
+            let far = (sx - head_x).hypot(sy - head_y) >= self.config.far_snake_distance;
+            let detail = if far { self.config.far_snake_detail } else { 1 };
+            self.send_snake(session_id, snake, detail);
         }
+    }
 
-       
-        self.send_leaderboard(session_id);
+   
+    fn handle_resync(&self, session_id: SessionId) {
+        let Some(snake_id) = self.sessions.get(session_id).and_then(|s| s.snake_id) else {
+            return;
+        };
+
+        let world = self.world.read();
+        if let Some(snake) = world.get_snake(snake_id).filter(|s| !s.dead) {
+            debug!(session_id, "Resyncing");
+            self.send_snakes_in_view(session_id, &world, snake, 2000.0);
+        }
+    }
+
+   
+    fn resync_all(&self) {
+        for session_id in self.sessions.playing_session_ids() {
+            self.handle_resync(session_id);
+        }
+    }
+
+   
+    fn send_snake(&self, session_id: SessionId, snake: &Snake, detail: usize) {
+        self.send_packet(session_id, &Self::add_snake_packet(snake, detail));
+
+        if let Some(mut session) = self.sessions.get_mut(session_id) {
+            if detail > 1 {
+                session.decimated_snakes.insert(snake.id);
+            } else {
+                session.decimated_snakes.remove(&snake.id);
+            }
+        }
     }
 
    
-    fn send_snake(&self, session_id: SessionId, snake: &Snake) {
+    fn add_snake_packet(snake: &Snake, detail: usize) -> PacketAddSnake {
         let (head_x, head_y) = snake.head_pos();
 
        
         let body_parts: Vec<(f32, f32)> = snake.body.iter()
             .map(|part| (part.x, part.y))
             .collect();
+        let body_parts = decimate_body_parts(&body_parts, detail);
 
-        let packet = PacketAddSnake {
+        PacketAddSnake {
             snake_id: snake.id,
             skin: snake.skin,
             angle: snake.angle,
             target_angle: snake.target_angle,
             speed: snake.speed,
-            fullness: snake.fullness as f32 / 100.0, 
+            fullness: snake.fam(),
             head_x,
             head_y,
             name: snake.name.clone(),
-            custom_skin: snake.custom_skin.as_ref().map(|s| s.as_bytes().to_vec()),
+            custom_skin: snake.custom_skin.clone(),
             body_parts,
-        };
+        }
+    }
 
-        self.send_packet(session_id, &packet);
+   
+    fn playing_snake_id(&self, session_id: SessionId) -> Option<SnakeId> {
+        self.sessions.get(session_id).filter(|s| s.is_playing()).and_then(|s| s.snake_id)
+    }
+
+   
+    fn rotation_is_absolute(&self, session_id: SessionId, rot: &RotationPacket) -> bool {
+        let Some(from) = self.config.absolute_rotation_from else {
+            return false;
+        };
+        !rot.is_legacy()
+            && self
+                .sessions
+                .get(session_id)
+                .is_some_and(|s| s.protocol.protocol_version >= from)
     }
 
    
     fn handle_rotation(
         &self,
         session_id: SessionId,
-        rot: crate::protocol::incoming::RotationPacket,
+        rot: RotationPacket,
     ) {
-        let snake_id = match self.sessions.get(session_id) {
-            Some(s) => match s.snake_id {
-                Some(id) => id,
-                None => return,
-            },
-            None => return,
+        let Some(snake_id) = self.playing_snake_id(session_id) else {
+            return;
         };
 
         let mut world = self.world.write();
         if let Some(snake) = world.get_snake_mut(snake_id) {
-           
-            let intensity = rot.intensity() as f32 / 127.0;
-            let turn_rate = std::f32::consts::PI * intensity;
-
-            let delta = if rot.is_clockwise() {
-                -turn_rate
-            } else {
-                turn_rate
-            };
-
-            let new_angle = snake.angle + delta * 0.1;
+            let new_angle = snake.angle + rot.angle_delta();
             snake.set_target_angle(new_angle);
         }
     }
 
    
     fn handle_angle(&self, session_id: SessionId, angle: f32) {
-        let snake_id = match self.sessions.get(session_id) {
-            Some(s) => match s.snake_id {
-                Some(id) => id,
-                None => return,
-            },
-            None => return,
+        let Some(snake_id) = self.playing_snake_id(session_id) else {
+            return;
         };
 
         let mut world = self.world.write();
@@ -353,12 +788,8 @@ impl GameHandler {
 
    
     fn handle_acceleration(&self, session_id: SessionId, accelerating: bool) {
-        let snake_id = match self.sessions.get(session_id) {
-            Some(s) => match s.snake_id {
-                Some(id) => id,
-                None => return,
-            },
-            None => return,
+        let Some(snake_id) = self.playing_snake_id(session_id) else {
+            return;
         };
 
         let mut world = self.world.write();
@@ -367,88 +798,261 @@ impl GameHandler {
         }
     }
 
+
    
-    fn handle_ping(&self, session_id: SessionId) {
-        self.send_packet(session_id, &PacketPong);
+    fn handle_feed(&self, target: SessionId, count: u16) {
+        let Some(snake_id) = self.sessions.get(target).and_then(|s| s.snake_id) else {
+            debug!(session_id = target, "Feed target has no snake");
+            return;
+        };
+
+        let mut world = self.world.write();
+        let Some((x, y)) = world.get_snake(snake_id).map(|snake| snake.head_pos()) else {
+            return;
+        };
+        let spawned = world.spawn_food_near(x, y, count);
+        debug!(session_id = target, snake_id, spawned, "Spawned debug food");
     }
 
    
-    fn handle_victory_message(&self, session_id: SessionId, message: String) {
-        debug!("Victory message from {}: {}", session_id, message);
-       
+    fn handle_ping(&self, session_id: SessionId) {
+        self.send_packet(session_id, &PacketPong);
     }
 
    
-    pub fn tick(&mut self, dt_ms: u64) {
-       
-        {
-            let mut world = self.world.write();
-            world.tick(dt_ms);
+    fn handle_victory_message(&self, session_id: SessionId, message: String) {
+        if !self.config.victory_messages {
+            debug!(session_id, "Victory messages disabled, ignoring message");
+            return;
         }
 
-       
-        self.broadcast_updates();
-
-       
-        let now = Instant::now();
-
-        if now.duration_since(self.last_leaderboard).as_millis() as u64
-            >= timing::LEADERBOARD_INTERVAL_MS
-        {
-            self.last_leaderboard = now;
-            self.broadcast_leaderboard();
+        let message = sanitize_text(&message, MAX_VICTORY_MESSAGE_CHARS);
+        debug!(session_id, message = %message, "Victory message");
+        if let Some(mut session) = self.sessions.get_mut(session_id) {
+            session.victory_message = (!message.is_empty()).then_some(message);
         }
+    }
 
-        if now.duration_since(self.last_minimap).as_millis() as u64 >= timing::MINIMAP_INTERVAL_MS {
-            self.last_minimap = now;
-            self.broadcast_minimap();
+   
+    pub fn spectate(&self, session_id: SessionId) -> bool {
+        if self.sessions.get(session_id).is_some_and(|s| s.snake_id.is_some()) {
+            debug!(session_id, "Playing session asked to spectate, ignoring");
+            return false;
         }
-
-       
-        let stale = self.sessions.cleanup_stale(timing::PING_TIMEOUT_MS);
-        for session_id in stale {
-            self.on_disconnect(session_id);
+        if self.sessions.start_spectating(session_id, self.config.spectator_limit) {
+            return true;
         }
+        info!(session_id, spectator_limit = self.config.spectator_limit, "Spectator limit reached, rejecting");
+        self.reject_login(session_id);
+        false
     }
 
    
-    fn broadcast_updates(&self) {
-        let world = self.world.read();
+    fn reject_login(&self, session_id: SessionId) {
+        let end = PacketEnd {
+            status: GameEndStatus::Disconnect,
+        };
+        self.send_packet(session_id, &end);
+        if let Some(mut session) = self.sessions.get_mut(session_id) {
+            session.state = SessionState::Disconnected;
+        }
+    }
 
-       
-        for session_id in self.sessions.playing_session_ids() {
-            let snake_id = match self.sessions.get(session_id) {
-                Some(s) => match s.snake_id {
-                    Some(id) => id,
-                    None => continue,
-                },
-                None => continue,
+   
+    fn check_high_score(&self) {
+        let packet = {
+            let world = self.world.read();
+            let Some((leader, _)) = world.leaderboard(1).into_iter().next() else {
+                return;
             };
+            if leader.is_bot || leader.length() <= self.record_length.load(Ordering::Relaxed) {
+                return;
+            }
+            self.record_length.store(leader.length(), Ordering::Relaxed);
 
-           
-            let player_pos = match world.get_snake(snake_id) {
-                Some(s) => s.head_pos(),
-                None => continue,
+            let message = if self.config.victory_messages {
+                self.sessions
+                    .get_by_snake(leader.id)
+                    .and_then(|s| s.victory_message.clone())
+                    .unwrap_or_default()
+            } else {
+                String::new()
             };
 
-            let view_radius = 2000.0;
+            PacketHighScore {
+                snake_length: leader.length() as u32,
+                winner_name: leader.name.clone(),
+                message,
+            }
+        };
 
-           
-            let sector_events = {
-                let mut session = match self.sessions.get_mut(session_id) {
-                    Some(s) => s,
-                    None => continue,
-                };
-                session
-                    .sector_tracker
-                    .update(&world.sectors, player_pos.0, player_pos.1, view_radius)
-            };
+        info!("New length record by {}: {}", packet.winner_name, packet.snake_length);
+        for session_id in self.sessions.playing_session_ids() {
+            self.send_packet(session_id, &packet);
+        }
+    }
 
-           
+   
+    pub fn broadcast_announcement(&self, text: &str) {
+        let packet = PacketHighScore {
+            snake_length: 0,
+            winner_name: String::new(),
+            message: sanitize_text(text, MAX_VICTORY_MESSAGE_CHARS),
+        };
+        info!(message = %packet.message, "Broadcasting announcement");
+        for session_id in self.sessions.playing_session_ids() {
+            self.send_packet(session_id, &packet);
+        }
+    }
+
+   
+    pub fn tick(&self, dt_ms: u64) {
+        if self.config.input_buffer_ms > 0 {
+            self.apply_buffered_inputs();
+        }
+
+       
+        {
+            let mut world = self.world.write();
+            if self.is_paused() {
+                world.tick_paused();
+            } else {
+                world.tick(dt_ms);
+            }
+        }
+
+       
+        self.broadcast_updates();
+
+       
+        let now = Instant::now();
+        let (leaderboard_due, minimap_due, resync_due, announcements_due) = {
+            let mut timers = self.timers.lock();
+            let announcements_due: Vec<_> = timers
+                .announcements
+                .iter_mut()
+                .zip(&self.config.announcements)
+                .filter_map(|(last, a)| PeriodicTimers::take_due(last, now, a.interval_ms).then_some(&a.text))
+                .collect();
+            (
+                PeriodicTimers::take_due(&mut timers.leaderboard, now, timing::LEADERBOARD_INTERVAL_MS),
+                PeriodicTimers::take_due(&mut timers.minimap, now, timing::MINIMAP_INTERVAL_MS),
+                PeriodicTimers::take_due(&mut timers.resync, now, self.config.resync_interval_ms),
+                announcements_due,
+            )
+        };
+
+        if leaderboard_due {
+            self.broadcast_leaderboard();
+            self.check_high_score();
+        }
+
+        if minimap_due {
+            self.broadcast_minimap();
+        }
+
+        if resync_due {
+            self.resync_all();
+        }
+
+        for text in announcements_due {
+            self.broadcast_announcement(text);
+        }
+
+       
+        for session_id in self.sessions.disconnected_ids() {
+            debug!(session_id, "Session channel closed, removing");
+            self.on_disconnect(session_id);
+        }
+
+        for session_id in self.sessions.idle_spectator_ids(self.config.spectator_idle_ms) {
+            debug!(session_id, "Spectator idle, removing");
+            self.on_disconnect(session_id);
+        }
+
+        let stale = self.sessions.cleanup_stale(timing::PING_TIMEOUT_MS);
+        for session_id in stale {
+            self.on_disconnect(session_id);
+        }
+
+        for session_id in self.sessions.handshake_expired_ids(self.config.handshake_timeout_ms) {
+            info!(session_id, "Handshake not finished in time, disconnecting");
+            self.on_disconnect(session_id);
+        }
+    }
+
+   
+    fn broadcast_updates(&self) {
+        let view_radius = 2000.0;
+
+        let players: Vec<_> = self
+            .sessions
+            .playing_session_ids()
+            .into_iter()
+            .filter_map(|session_id| Some((session_id, self.sessions.get(session_id)?.snake_id?)))
+            .collect();
+
+       
+        let frame = FrameSnapshot::capture(&self.world.read(), &players, view_radius);
+
+        for player in &frame.players {
+            self.send_frame(&frame, player, view_radius);
+        }
+
+        for (snake_id, _) in &frame.died {
+            self.end_game(*snake_id);
+        }
+    }
+
+   
+    fn end_game(&self, snake_id: SnakeId) {
+        let Some(session_id) = self.sessions.get_by_snake(snake_id).map(|s| s.id) else {
+            return;
+        };
+
+        info!(session_id, snake_id, "Snake died");
+        self.send_packet(session_id, &PacketEnd { status: GameEndStatus::Normal });
+        self.sessions.clear_snake(snake_id);
+        if let Some(mut session) = self.sessions.get_mut(session_id) {
+            session.mark_dead();
+        }
+    }
+
+   
+    fn send_frame(&self, frame: &FrameSnapshot, player: &PlayerFrame, view_radius: f32) {
+        let session_id = player.session_id;
+        let player_pos = player.pos;
+
+        let in_view = |(sx, sy): (f32, f32)| {
+            (sx - player_pos.0).abs() < view_radius && (sy - player_pos.1).abs() < view_radius
+        };
+
+        let (sector_events, refined) = match self.sessions.get_mut(session_id) {
+            Some(mut session) => {
+                let events = session.sector_tracker.update_ranges(player.enter_range, player.leave_range);
+                let mut refined = Vec::new();
+                for snake in player.changed.iter().map(|&i| &frame.snakes[i]) {
+                    let (sx, sy) = snake.head;
+                    let near = (sx - player_pos.0).hypot(sy - player_pos.1) < self.config.far_snake_distance;
+                    if near && in_view(snake.head) && session.decimated_snakes.remove(&snake.id) {
+                        refined.push(snake.id);
+                    }
+                }
+                for (snake_id, _) in &frame.died {
+                    session.decimated_snakes.remove(snake_id);
+                }
+                (events, refined)
+            }
+            None => return,
+        };
+
+       
+        if !sector_events.is_empty() {
+            let world = self.world.read();
             for event in sector_events {
                 match event {
                     SectorEvent::Entered { x, y } => {
-                        self.send_packet(session_id, &PacketAddSector { x, y });
+                        self.queue_packet(session_id, &PacketAddSector { x, y });
 
                        
                         if let Some(sector) = world.sectors.get(x, y) {
@@ -457,146 +1061,216 @@ impl GameHandler {
                                 .collect();
 
                             if !foods.is_empty() {
-                                self.send_packet(session_id, &PacketSetFood {
+                                self.queue_packet(session_id, &PacketSetFood {
                                     sector_x: x,
                                     sector_y: y,
-                                    sector_size: self.config.sector_size,
+                                    sector_size: frame.sector_size,
                                     foods,
                                 });
                             }
                         }
                     }
                     SectorEvent::Left { x, y } => {
-                        self.send_packet(session_id, &PacketRemoveSector { x, y });
+                        self.queue_packet(session_id, &PacketRemoveSector { x, y });
                     }
                 }
             }
+        }
 
-           
-            for changed_id in world.changed_snakes() {
-                if let Some(snake) = world.get_snake(*changed_id) {
-                    let (sx, sy) = snake.head_pos();
-                    let is_own_snake = snake.id == snake_id;
+        if self.config.debug_overlay {
+            self.send_debug_overlay(session_id, &self.world.read(), player_pos, view_radius);
+        }
 
-                   
-                    if (sx - player_pos.0).abs() < view_radius
-                        && (sy - player_pos.1).abs() < view_radius
-                    {
-                       
-                        if snake.changes.has_pos() {
-                            let (dx, dy) = snake.head_delta();
-                            if dx.abs() < 128 && dy.abs() < 128 {
-                               
-                                if is_own_snake {
-                                    self.send_packet(
-                                        session_id,
-                                        &PacketMoveRelOwn { dx, dy },
-                                    );
-                                } else {
-                                    self.send_packet(
-                                        session_id,
-                                        &PacketMoveRel {
-                                            snake_id: snake.id,
-                                            dx,
-                                            dy,
-                                        },
-                                    );
-                                }
-                            } else {
-                                let (x, y) = snake.head_pos_u16();
-                               
-                                if is_own_snake {
-                                    self.send_packet(
-                                        session_id,
-                                        &PacketMoveOwn { x, y },
-                                    );
-                                } else {
-                                    self.send_packet(
-                                        session_id,
-                                        &PacketMove {
-                                            snake_id: snake.id,
-                                            x,
-                                            y,
-                                        },
-                                    );
-                                }
-                            }
-                        }
+       
+        for snake in player.changed.iter().map(|&i| &frame.snakes[i]) {
+            let is_own_snake = snake.id == player.snake_id;
 
-                        if snake.changes.has_angle() || snake.changes.has_wangle() {
-                            let clockwise = crate::protocol::types::is_clockwise(
-                                snake.angle,
-                                snake.target_angle,
-                            );
-                            self.send_packet(
-                                session_id,
-                                &PacketRotation {
-                                    snake_id: snake.id,
-                                    angle: snake.angle,
-                                    target_angle: snake.target_angle,
-                                    speed: snake.speed,
-                                    include_angle: true,
-                                    include_target: true,
-                                    clockwise,
-                                },
-                            );
-                        }
+           
+            if !in_view(snake.head) || refined.contains(&snake.id) {
+                continue;
+            }
 
-                        if snake.changes.has_fullness() {
-                            self.send_packet(
-                                session_id,
-                                &PacketSetFullness {
-                                    snake_id: snake.id,
-                                    fullness: snake.fullness as f32 / 100.0,
-                                },
-                            );
-                        }
+           
+            if snake.changes.has_pos() {
+                let (dx, dy) = snake.head_delta;
+                if RELATIVE_COORD_RANGE.contains(&dx) && RELATIVE_COORD_RANGE.contains(&dy) {
+                   
+                    if is_own_snake {
+                        self.queue_packet(session_id, &PacketMoveRelOwn { dx, dy });
+                    } else {
+                        self.queue_packet(
+                            session_id,
+                            &PacketMoveRel {
+                                snake_id: snake.id,
+                                dx,
+                                dy,
+                            },
+                        );
+                    }
+                } else {
+                    let (x, y) = snake.head_u16;
+                   
+                    if is_own_snake {
+                        self.queue_packet(session_id, &PacketMoveOwn { x, y });
+                    } else {
+                        self.queue_packet(
+                            session_id,
+                            &PacketMove {
+                                snake_id: snake.id,
+                                x,
+                                y,
+                            },
+                        );
                     }
                 }
             }
 
-           
-            for (eater_id, food) in world.eaten_food() {
-                self.send_packet(
+            let clockwise = crate::protocol::types::is_clockwise(snake.angle, snake.target_angle);
+            if let Some(rotation) = PacketRotation::for_changes(
+                snake.id,
+                snake.angle,
+                snake.target_angle,
+                snake.speed,
+                clockwise,
+                snake.changes,
+            ) {
+                self.queue_packet(session_id, &rotation);
+            }
+
+            if snake.changes.has_fullness() {
+                self.queue_packet(
+                    session_id,
+                    &PacketSetFullness {
+                        snake_id: snake.id,
+                        fullness: fullness_to_fam(snake.fullness),
+                    },
+                );
+            }
+        }
+
+       
+        if !refined.is_empty() {
+            let world = self.world.read();
+            for snake in refined.iter().filter_map(|id| world.get_snake(*id)).filter(|s| !s.dead) {
+                self.queue_packet(
+                    session_id,
+                    &PacketRemoveSnake {
+                        snake_id: snake.id,
+                        status: SnakeRemoveStatus::Left,
+                    },
+                );
+                self.queue_packet(session_id, &Self::add_snake_packet(snake, 1));
+            }
+        }
+
+       
+        for &(snake_id, (sx, sy)) in &frame.died {
+            if snake_id == player.snake_id
+                || (sx - player_pos.0).abs() >= view_radius
+                || (sy - player_pos.1).abs() >= view_radius
+            {
+                continue;
+            }
+            self.queue_packet(
+                session_id,
+                &PacketRemoveSnake {
+                    snake_id,
+                    status: SnakeRemoveStatus::Died,
+                },
+            );
+        }
+
+       
+        for (eater_id, food) in &frame.eaten_food {
+            if *eater_id == player.snake_id {
+                self.queue_packet(
+                    session_id,
+                    &PacketEatFoodOwn {
+                        food_x: food.x,
+                        food_y: food.y,
+                        sector_size: frame.sector_size,
+                    },
+                );
+            } else {
+                self.queue_packet(
                     session_id,
                     &PacketEatFood {
                         snake_id: *eater_id,
                         food_x: food.x,
                         food_y: food.y,
-                        sector_size: world.config.sector_size,
+                        sector_size: frame.sector_size,
                     },
                 );
             }
+        }
 
+       
+        for (food, (sx, sy)) in &frame.new_food {
            
-            for food in world.new_food() {
-                let (sx, sy) = food.sector_coords(world.config.sector_size);
-
-               
-                if let Some(session) = self.sessions.get(session_id) {
-                    if session.sector_tracker.is_visible(sx, sy) {
-                        self.send_packet(
-                            session_id,
-                            &PacketSpawnFood {
-                                food: food.to_packet_data(),
-                                sector_size: world.config.sector_size,
-                            },
-                        );
-                    }
-                }
+            let visible = self
+                .sessions
+                .get(session_id)
+                .is_some_and(|s| s.sector_tracker.is_visible(*sx, *sy));
+            if visible {
+                self.queue_packet(
+                    session_id,
+                    &PacketSpawnFood {
+                        food: food.to_packet_data(),
+                        sector_size: frame.sector_size,
+                    },
+                );
             }
         }
+
+        self.flush_packets(session_id);
     }
 
    
-    fn send_leaderboard(&self, session_id: SessionId) {
-        let world = self.world.read();
+    fn send_debug_overlay(
+        &self,
+        session_id: SessionId,
+        world: &World,
+        player_pos: (f32, f32),
+        view_radius: f32,
+    ) {
+        let sector_size = world.config.sector_size;
+        let mut shapes = Vec::new();
+
+        if let Some(session) = self.sessions.get(session_id) {
+            for &(x, y) in session.sector_tracker.visible_sectors() {
+                shapes.push(DebugShape::Rect {
+                    x: x as u16 * sector_size,
+                    y: y as u16 * sector_size,
+                    width: sector_size,
+                    height: sector_size,
+                    color: 0,
+                });
+            }
+        }
 
-        let snake_id = match self.sessions.get(session_id) {
-            Some(s) => s.snake_id.unwrap_or(0),
-            None => return,
-        };
+        for snake in world.snakes().values() {
+            if snake.dead {
+                continue;
+            }
+            let bb = &snake.bounding_box;
+            if (bb.x - player_pos.0).abs() < view_radius + bb.radius
+                && (bb.y - player_pos.1).abs() < view_radius + bb.radius
+            {
+                shapes.push(DebugShape::Circle {
+                    x: bb.x as u16,
+                    y: bb.y as u16,
+                    radius: bb.radius as u16,
+                    color: 1,
+                });
+            }
+        }
 
+        self.queue_packet(session_id, &PacketDebugReset);
+        self.queue_packet(session_id, &PacketDebugDraw { shapes });
+    }
+
+   
+    fn send_leaderboard(&self, session_id: SessionId, world: &World, snake_id: SnakeId) {
         let player_rank = world.player_rank(snake_id).unwrap_or(0) as u8;
         let leaderboard = world.leaderboard(10);
 
@@ -604,7 +1278,7 @@ impl GameHandler {
             .iter()
             .map(|(snake, _score)| LeaderboardEntry {
                 parts: snake.length() as u16,
-                fullness: snake.fullness as f32 / 100.0,
+                fullness: snake.fam(),
                 font_color: snake.skin,
                 name: snake.name.clone(),
             })
@@ -622,8 +1296,15 @@ impl GameHandler {
 
    
     fn broadcast_leaderboard(&self) {
+        let world = self.world.read();
         for session_id in self.sessions.playing_session_ids() {
-            self.send_leaderboard(session_id);
+            let Some(snake_id) = self.sessions.get(session_id).and_then(|s| s.snake_id) else {
+                continue;
+            };
+            self.send_leaderboard(session_id, &world, snake_id);
+        }
+        for session_id in self.sessions.spectator_ids() {
+            self.send_leaderboard(session_id, &world, 0);
         }
     }
 
@@ -632,7 +1313,8 @@ impl GameHandler {
         let world = self.world.read();
         let minimap_data = world.minimap_data(80);
 
-        for session_id in self.sessions.playing_session_ids() {
+        let recipients = self.sessions.playing_session_ids().into_iter().chain(self.sessions.spectator_ids());
+        for session_id in recipients {
             let use_modern = self
                 .sessions
                 .get(session_id)
@@ -650,25 +1332,1271 @@ impl GameHandler {
     }
 
    
+    fn frame_packet(session: &mut Session, packet_bytes: &[u8]) -> Vec<u8> {
+        let data = if session.protocol.want_etm {
+           
+            let etm = session.time_since_last_sent();
+            let mut framed = Vec::with_capacity(2 + packet_bytes.len());
+            framed.push((etm >> 8) as u8);
+            framed.push((etm & 0xFF) as u8);
+            framed.extend_from_slice(packet_bytes);
+            framed
+        } else {
+            packet_bytes.to_vec()
+        };
+
+        session.update_last_sent();
+        data
+    }
+
    
     fn send_packet<T: PacketSerialize>(&self, session_id: SessionId, packet: &T) {
-        let packet_bytes = packet.to_bytes();
+        if let Some(mut session) = self.sessions.get_mut(session_id) {
+            let packet_bytes = packet.to_bytes();
+            self.sessions.packet_stats().record(&packet_bytes);
+            let data = Self::frame_packet(&mut session, &packet_bytes);
+            session.send(data);
+        }
+    }
 
+   
+    fn queue_packet<T: PacketSerialize>(&self, session_id: SessionId, packet: &T) {
         if let Some(mut session) = self.sessions.get_mut(session_id) {
-            let data = if session.protocol.want_etm {
+            let packet_bytes = packet.to_bytes();
+            self.sessions.packet_stats().record(&packet_bytes);
+            if !session.queue(&packet_bytes) {
                
-                let etm = session.time_since_last_sent();
-                let mut framed = Vec::with_capacity(2 + packet_bytes.len());
-                framed.push((etm >> 8) as u8);
-                framed.push((etm & 0xFF) as u8);
-                framed.extend_from_slice(&packet_bytes);
-                framed
+                session.flush();
+                let data = Self::frame_packet(&mut session, &packet_bytes);
+                session.send(data);
+            }
+        }
+    }
+
+   
+    fn flush_packets(&self, session_id: SessionId) {
+        if let Some(mut session) = self.sessions.get_mut(session_id) {
+            session.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::world_consts;
+    use crate::game::food::FoodPalette;
+    use crate::game::world::create_shared_world;
+    use crate::protocol::types::CUSTOM_SKIN_COLOR_COUNT;
+    use crate::protocol::packet::HANDSHAKE_SECRET;
+    use crate::server::session::{create_session_manager, Frame};
+    use std::net::SocketAddr;
+    use crate::protocol::types::SKIN_COUNT;
+    use tokio::sync::mpsc::UnboundedReceiver;
+
+    fn test_handler(config: GameConfig) -> GameHandler {
+        let world = create_shared_world(config.clone());
+        GameHandler::new(world, create_session_manager(), config)
+    }
+
+    fn connect(handler: &GameHandler) -> (SessionId, UnboundedReceiver<Frame>) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let addr: SocketAddr = "127.0.0.1:9000".parse().unwrap();
+        let session_id = handler.sessions.create_session(addr, tx);
+        handler.on_connect(session_id);
+        (session_id, rx)
+    }
+
+    #[test]
+    fn test_handshake_timeout_reaps_silent_session() {
+        let config = GameConfig {
+            handshake_timeout_ms: 50,
+            ..Default::default()
+        };
+        let handler = test_handler(config);
+        let (silent, _rx1) = connect(&handler);
+        let (playing, _rx2) = connect(&handler);
+        handler.on_packet(playing, &[b's', 14, 0, 4, b'T', b'e', b's', b't']).unwrap();
+
+        handler.tick(8);
+        assert!(handler.sessions.get(silent).is_some());
+
+        std::thread::sleep(std::time::Duration::from_millis(60));
+        handler.tick(8);
+        assert!(handler.sessions.get(silent).is_none());
+        assert!(handler.sessions.get(playing).is_some());
+    }
+
+    #[test]
+    fn test_init_is_the_first_frame_even_while_ticking() {
+        let handler = test_handler(GameConfig::default());
+        let first_opcodes = std::thread::scope(|scope| {
+            scope.spawn(|| {
+                for _ in 0..200 {
+                    handler.tick(8);
+                }
+            });
+            (0..20)
+                .map(|_| {
+                    let (session_id, mut rx) = connect(&handler);
+                    handler.on_packet(session_id, &[b'c']).unwrap();
+                    while rx.try_recv().is_ok() {}
+                    handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+                    rx.try_recv().map(|frame| frame[2]).ok()
+                })
+                .collect::<Vec<_>>()
+        });
+        assert!(first_opcodes.iter().all(|&op| op == Some(b'a')), "{first_opcodes:?}");
+    }
+
+    #[test]
+    fn test_twenty_char_nickname_still_logs_in() {
+        let handler = test_handler(GameConfig::default());
+        let (session_id, _rx) = connect(&handler);
+        handler.on_packet(session_id, &[b'c']).unwrap();
+
+        let mut login = vec![b's', 14, 0, 20];
+        login.extend_from_slice(b"twenty_char_nickname");
+        assert_eq!(login.len(), crate::protocol::incoming::HANDSHAKE_ANSWER_LEN);
+        handler.on_packet(session_id, &login).unwrap();
+
+        let session = handler.sessions.get(session_id).unwrap();
+        assert!(session.snake_id.is_some());
+        assert_eq!(session.name, "twenty_char_nickname");
+        assert!(!session.protocol.challenge_answered);
+    }
+
+    #[test]
+    fn test_handshake_answer_follows_pre_init() {
+        let handler = test_handler(GameConfig::default());
+        let (session_id, _rx) = connect(&handler);
+        let answer = [b'x'; crate::protocol::incoming::HANDSHAKE_ANSWER_LEN];
+
+        handler.on_packet(session_id, &answer).unwrap();
+        assert!(!handler.sessions.get(session_id).unwrap().protocol.challenge_answered);
+
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        handler.on_packet(session_id, &answer).unwrap();
+        assert!(handler.sessions.get(session_id).unwrap().protocol.challenge_answered);
+
+        handler.on_packet(session_id, &[b's', 14, 0, 4, b'T', b'e', b's', b't']).unwrap();
+        assert!(handler.sessions.get(session_id).unwrap().snake_id.is_some());
+    }
+
+    fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+        bytes.iter().fold(hash, |h, &b| (h ^ b as u64).wrapping_mul(0x100000001b3))
+    }
+
+    #[test]
+    fn test_broadcast_output_is_stable() {
+        let mut handler = test_handler(GameConfig::default());
+        let far_future = Instant::now() + std::time::Duration::from_secs(3600);
+        *handler.timers.get_mut() = PeriodicTimers::new(far_future, 0);
+
+        let mut players = Vec::new();
+        for _ in 0..2 {
+            let (session_id, mut rx) = connect(&handler);
+            handler.on_packet(session_id, &[b'c']).unwrap();
+            handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+            while rx.try_recv().is_ok() {}
+            players.push((session_id, rx));
+        }
+
+        let (mut digest, mut total) = (0xcbf29ce484222325u64, 0usize);
+        for tick in 0..600u32 {
+            if tick % 50 == 0 {
+                for (i, (session_id, _)) in players.iter().enumerate() {
+                    let angle = (tick as f32 / 40.0 + i as f32 * 2.0) % std::f32::consts::TAU;
+                    handler.on_packet(*session_id, &crate::protocol::client::angle_frame(angle)).unwrap();
+                }
+            }
+            handler.tick(8);
+            for (_, rx) in players.iter_mut() {
+                while let Ok(frame) = rx.try_recv() {
+                    digest = fnv1a(digest, &frame[2..]);
+                    total += frame.len() - 2;
+                }
+            }
+        }
+
+        assert_eq!((total, digest), (3634, 0xe4321e753c9b455b));
+    }
+
+    fn identity_with_skin(skin: &[u8]) -> Vec<u8> {
+        let mut frame = vec![b's', 14, 0, 4, b'T', b'e', b's', b't'];
+        frame.extend_from_slice(skin);
+        frame
+    }
+
+    #[test]
+    fn test_custom_skin_validation() {
+        let handler = test_handler(GameConfig {
+            max_custom_skin_len: 8,
+            ..Default::default()
+        });
+
+        let (valid, _rx) = connect(&handler);
+        handler.on_packet(valid, &identity_with_skin(&[0, 5, 39, 12])).unwrap();
+        let snake_id = handler.sessions.get(valid).unwrap().snake_id.unwrap();
+        assert_eq!(
+            handler.world.read().get_snake(snake_id).unwrap().custom_skin.as_deref(),
+            Some(&[0, 5, 39, 12][..])
+        );
+
+        let (oversized, _rx) = connect(&handler);
+        assert_eq!(
+            handler.on_packet(oversized, &identity_with_skin(&[1; 9])),
+            Err(ProtocolError::CustomSkinTooLong { len: 9, max: 8 })
+        );
+        assert!(handler.sessions.get(oversized).unwrap().snake_id.is_none());
+
+        let (bad_color, _rx) = connect(&handler);
+        assert_eq!(
+            handler.on_packet(bad_color, &identity_with_skin(&[1, 2, CUSTOM_SKIN_COLOR_COUNT])),
+            Err(ProtocolError::InvalidSkinColor(CUSTOM_SKIN_COLOR_COUNT))
+        );
+        assert!(handler.sessions.get(bad_color).unwrap().snake_id.is_none());
+        assert_eq!(handler.world.read().snake_count(), 1);
+    }
+
+    #[test]
+    fn test_login_over_capacity_is_rejected() {
+        let handler = test_handler(GameConfig {
+            max_players: 2,
+            ..Default::default()
+        });
+        let mut players = Vec::new();
+        for _ in 0..3 {
+            let (session_id, rx) = connect(&handler);
+            handler.on_packet(session_id, &[b'c']).unwrap();
+            handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+            players.push((session_id, rx));
+        }
+
+        assert_eq!(handler.sessions.playing_count(), 2);
+        assert_eq!(handler.world.read().snake_count(), 2);
+
+        let (rejected, rx) = &mut players[2];
+        let rejected = *rejected;
+        assert!(handler.sessions.get(rejected).unwrap().snake_id.is_none());
+        assert_eq!(next_packet(rx, b'v'), vec![b'v', GameEndStatus::Disconnect as u8]);
+
+        handler.tick(8);
+        assert!(handler.sessions.get(rejected).is_none());
+    }
+
+    #[test]
+    fn test_concurrent_logins_respect_capacity() {
+        let handler = test_handler(GameConfig {
+            max_players: 4,
+            ..Default::default()
+        });
+        let players: Vec<_> = (0..64).map(|_| connect(&handler)).collect();
+        for (session_id, _) in &players {
+            handler.on_packet(*session_id, &[b'c']).unwrap();
+        }
+
+        let start = std::sync::Barrier::new(players.len());
+        std::thread::scope(|scope| {
+            for (session_id, _) in &players {
+                let (handler, start) = (&handler, &start);
+                scope.spawn(move || {
+                    start.wait();
+                    handler.on_packet(*session_id, &identity_with_skin(&[])).unwrap();
+                });
+            }
+        });
+
+        assert_eq!(handler.sessions.playing_count(), 4);
+        assert_eq!(handler.world.read().snake_count(), 4);
+    }
+
+    #[test]
+    fn test_login_checksum_is_verified_when_enabled() {
+        let handler = test_handler(GameConfig {
+            verify_checksum: true,
+            ..Default::default()
+        });
+        let good = expected_login_checksum(HANDSHAKE_SECRET);
+        let mut corrupted = good;
+        corrupted[7] ^= 0x20;
+
+        let (genuine, _genuine_rx) = connect(&handler);
+        handler.on_packet(genuine, &[b'c']).unwrap();
+        handler.on_packet(genuine, &login_frame_with_checksum(31, good)).unwrap();
+        assert!(handler.sessions.get(genuine).unwrap().is_playing());
+
+        let (forged, mut forged_rx) = connect(&handler);
+        handler.on_packet(forged, &[b'c']).unwrap();
+        handler.on_packet(forged, &login_frame_with_checksum(31, corrupted)).unwrap();
+        assert_eq!(next_packet(&mut forged_rx, b'v'), vec![b'v', GameEndStatus::Disconnect as u8]);
+        assert!(handler.sessions.get(forged).unwrap().is_disconnected());
+        assert_eq!(handler.world.read().snake_count(), 1);
+    }
+
+    #[test]
+    fn test_configured_handshake_secret_is_sent_and_verified() {
+        let custom: Vec<u8> = HANDSHAKE_SECRET.iter().rev().copied().collect();
+        let handler = test_handler(GameConfig {
+            verify_checksum: true,
+            handshake_secret: Some(custom.clone()),
+            ..Default::default()
+        });
+        assert_ne!(expected_login_checksum(&custom), expected_login_checksum(HANDSHAKE_SECRET));
+
+        let (session_id, mut rx) = connect(&handler);
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        assert_eq!(next_packet(&mut rx, b'6')[1..], custom[..]);
+
+        handler
+            .on_packet(session_id, &login_frame_with_checksum(31, expected_login_checksum(&custom)))
+            .unwrap();
+        assert!(handler.sessions.get(session_id).unwrap().is_playing());
+
+        let (stale, _stale_rx) = connect(&handler);
+        handler.on_packet(stale, &[b'c']).unwrap();
+        handler
+            .on_packet(stale, &login_frame_with_checksum(31, expected_login_checksum(HANDSHAKE_SECRET)))
+            .unwrap();
+        assert!(handler.sessions.get(stale).unwrap().is_disconnected());
+    }
+
+    #[test]
+    fn test_rotation_is_absolute_only_for_configured_versions() {
+        let handler = test_handler(GameConfig {
+            absolute_rotation_from: Some(crate::protocol::packet::protocol::VERSION_MODERN),
+            ..Default::default()
+        });
+        let mut snakes = Vec::new();
+        for login in [vec![b's', 14, 0, 4, b'T', b'e', b's', b't'], login_frame(31)] {
+            let (session_id, _rx) = connect(&handler);
+            handler.on_packet(session_id, &[b'c']).unwrap();
+            handler.on_packet(session_id, &login).unwrap();
+            let snake_id = handler.sessions.get(session_id).unwrap().snake_id.unwrap();
+            handler.world.write().get_snake_mut(snake_id).unwrap().angle = 1.0;
+            handler.on_packet(session_id, &[252, 64]).unwrap();
+            snakes.push(snake_id);
+        }
+
+        let world = handler.world.read();
+        let legacy = world.get_snake(snakes[0]).unwrap().target_angle;
+        let turn = RotationPacket {
+            value: 64,
+            is_legacy_left: false,
+            is_legacy_right: false,
+        };
+        let expected = 1.0 + turn.angle_delta();
+        assert!((legacy - expected).abs() < 1e-5, "legacy turn {legacy} != {expected}");
+        let absolute = world.get_snake(snakes[1]).unwrap().target_angle;
+        assert!((absolute - std::f32::consts::FRAC_PI_2).abs() < 1e-5, "absolute angle {absolute}");
+    }
+
+    #[test]
+    fn test_locked_skins_are_remapped_at_login() {
+        let registry = crate::config::SkinRegistry::from_json(
+            r#"{"fallback": 2, "skins": [{"index": 7, "name": "Gold", "color": 12, "locked": true}]}"#,
+        )
+        .unwrap();
+        let handler = test_handler(GameConfig {
+            skin_registry: registry,
+            ..Default::default()
+        });
+
+        let mut skins = Vec::new();
+        for requested in [7, 9] {
+            let (session_id, _rx) = connect(&handler);
+            handler.on_packet(session_id, &[b'c']).unwrap();
+            handler.on_packet(session_id, &[b's', 14, requested, 4, b'T', b'e', b's', b't']).unwrap();
+            let snake_id = handler.sessions.get(session_id).unwrap().snake_id.unwrap();
+            skins.push(handler.world.read().get_snake(snake_id).unwrap().skin);
+        }
+
+        assert_eq!(skins, vec![2, 9]);
+    }
+
+    #[test]
+    fn test_sent_packets_are_counted_per_opcode() {
+        let handler = test_handler(GameConfig::default());
+        let (session_id, _rx) = connect(&handler);
+
+        handler.send_packet(session_id, &PacketPong);
+        handler.send_packet(session_id, &PacketPong);
+        let remove = PacketRemoveSnake {
+            snake_id: 3,
+            status: SnakeRemoveStatus::Left,
+        };
+        handler.queue_packet(session_id, &remove);
+        handler.flush_packets(session_id);
+
+        let stats = handler.sessions.packet_stats();
+        assert_eq!(stats.count(b'p'), 2);
+        assert_eq!(stats.bytes(b'p'), 2 * PacketPong.to_bytes().len() as u64);
+        assert_eq!(stats.count(b's'), 1);
+        assert_eq!(stats.count(b'g'), 0);
+    }
+
+    #[test]
+    fn test_victory_message_is_sent_with_high_score() {
+        let handler = test_handler(GameConfig::default());
+        let (session_id, mut rx) = connect(&handler);
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        handler.on_packet(session_id, &[b's', 14, 0, 4, b'T', b'e', b's', b't']).unwrap();
+        handler.on_packet(session_id, b"\xffvgood game\x07 ").unwrap();
+        assert_eq!(
+            handler.sessions.get(session_id).unwrap().victory_message.as_deref(),
+            Some("good game")
+        );
+
+        handler.check_high_score();
+        let packet = next_packet(&mut rx, b'm');
+        assert!(packet.ends_with(b"Testgood game"));
+
+        handler.check_high_score();
+        assert!(std::iter::from_fn(|| rx.try_recv().ok()).all(|f| f.get(2) != Some(&b'm')));
+    }
+
+    #[test]
+    fn test_closed_channel_is_reaped_on_next_tick() {
+        let handler = test_handler(GameConfig::default());
+        let (session_id, rx) = connect(&handler);
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+        let snake_id = handler.sessions.get(session_id).unwrap().snake_id.unwrap();
+        drop(rx);
+
+        handler.tick(8);
+        handler.tick(8);
+
+        assert!(handler.sessions.get(session_id).is_none());
+        assert!(handler.world.read().get_snake(snake_id).is_none());
+    }
+
+    #[test]
+    fn test_out_of_range_skin_is_normalized() {
+        let handler = test_handler(GameConfig::default());
+        let (session_id, _rx) = connect(&handler);
+        handler.on_packet(session_id, &[b's', 14, 200, 4, b'T', b'e', b's', b't']).unwrap();
+
+        let session = handler.sessions.get(session_id).unwrap();
+        assert_eq!(session.skin, 200 % SKIN_COUNT);
+        let snake_id = session.snake_id.unwrap();
+        drop(session);
+
+        let skin = handler.world.read().get_snake(snake_id).unwrap().skin;
+        assert!(skin < SKIN_COUNT);
+        assert_eq!(skin, 200 % SKIN_COUNT);
+    }
+
+    #[test]
+    fn test_resync_sends_absolute_own_snake() {
+        let handler = test_handler(GameConfig {
+            resync_interval_ms: 0,
+            ..Default::default()
+        });
+        let (session_id, mut rx) = connect(&handler);
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+        let snake_id = handler.sessions.get(session_id).unwrap().snake_id.unwrap();
+
+        let added = handler.sessions.packet_stats().count(b's');
+        for _ in 0..50 {
+            handler.tick(8);
+        }
+        assert!(handler.sessions.packet_stats().count(b'G') >= 50);
+        assert_eq!(handler.sessions.packet_stats().count(b's'), added);
+        while rx.try_recv().is_ok() {}
+
+        handler.on_packet(session_id, &crate::protocol::client::resync_frame()).unwrap();
+        let packet = next_packet(&mut rx, b's');
+        let read_u24 = |b: &[u8]| ((b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32) as f32 / 5.0;
+        let world = handler.world.read();
+        let snake = world.get_snake(snake_id).unwrap();
+        let (head_x, head_y) = snake.head_pos();
+
+        assert_eq!(u16::from_be_bytes([packet[1], packet[2]]), snake_id);
+        assert!((read_u24(&packet[16..19]) - head_x).abs() < 1.0);
+        assert!((read_u24(&packet[19..22]) - head_y).abs() < 1.0);
+        let (x, y) = snake.head_pos_u16();
+        assert_eq!(next_packet(&mut rx, b'g')[1..], [x.to_be_bytes(), y.to_be_bytes()].concat());
+    }
+
+    #[test]
+    fn test_idle_spectator_is_reaped_before_idle_player() {
+        let handler = test_handler(GameConfig {
+            spectator_limit: 1,
+            spectator_idle_ms: 5,
+            ..Default::default()
+        });
+        let (player_id, _player_rx) = connect(&handler);
+        handler.on_packet(player_id, &[b'c']).unwrap();
+        handler.on_packet(player_id, &identity_with_skin(&[])).unwrap();
+        let (spectator_id, mut spectator_rx) = connect(&handler);
+        let (rejected_id, _rejected_rx) = connect(&handler);
+        let spectate = crate::protocol::client::spectate_frame();
+
+        handler.on_packet(player_id, &spectate).unwrap();
+        assert!(!handler.sessions.get(player_id).unwrap().spectator);
+        handler.on_packet(spectator_id, &[b'c']).unwrap();
+        handler.on_packet(spectator_id, &spectate).unwrap();
+        assert!(handler.sessions.get(spectator_id).unwrap().spectator);
+        handler.on_packet(rejected_id, &spectate).unwrap();
+        assert!(handler.sessions.get(rejected_id).unwrap().is_disconnected());
+
+        handler.broadcast_leaderboard();
+        handler.broadcast_minimap();
+        assert_eq!(next_packet(&mut spectator_rx, b'l')[1], 0);
+        assert!(!next_packet(&mut spectator_rx, b'u').is_empty());
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        handler.tick(8);
+
+        assert!(handler.sessions.get(spectator_id).is_none());
+        assert!(handler.sessions.get(player_id).unwrap().is_playing());
+    }
+
+    #[test]
+    fn test_buffered_inputs_apply_on_their_intended_ticks() {
+        let handler = test_handler(GameConfig {
+            input_buffer_ms: 100,
+            ..Default::default()
+        });
+        let (session_id, _rx) = connect(&handler);
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+        let snake_id = handler.sessions.get(session_id).unwrap().snake_id.unwrap();
+        let target = || handler.world.read().get_snake(snake_id).unwrap().target_angle;
+        let initial = target();
+
+        handler.on_packet(session_id, &[0, 0, 40]).unwrap();
+        handler.on_packet(session_id, &[0, 32, 200]).unwrap();
+        assert_eq!(handler.sessions.get(session_id).unwrap().input_buffer.len(), 2);
+        assert_eq!(target(), initial);
+
+        handler.tick(8);
+        let first = target();
+        assert_ne!(first, initial);
+
+        for _ in 0..3 {
+            handler.tick(8);
+            assert_eq!(target(), first);
+        }
+
+        handler.tick(8);
+        assert_ne!(target(), first);
+        assert!(handler.sessions.get(session_id).unwrap().input_buffer.is_empty());
+    }
+
+    #[test]
+    fn test_input_buffer_resyncs_after_respawn() {
+        let handler = test_handler(GameConfig {
+            input_buffer_ms: 100,
+            ..Default::default()
+        });
+        let (session_id, _rx) = connect(&handler);
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+        let snake_id = handler.sessions.get(session_id).unwrap().snake_id.unwrap();
+
+        handler.on_packet(session_id, &[0, 80, 40]).unwrap();
+        handler.world.write().get_snake_mut(snake_id).unwrap().kill(0, &FoodPalette::default(), &mut || 0.5);
+        handler.tick(8);
+        assert!(handler.sessions.get(session_id).unwrap().input_buffer.is_empty());
+
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+        let respawned = handler.sessions.get(session_id).unwrap().snake_id.unwrap();
+        let target = || handler.world.read().get_snake(respawned).unwrap().target_angle;
+        let initial = target();
+
+        handler.on_packet(session_id, &[0, 0, 200]).unwrap();
+        handler.tick(8);
+        let steered = target();
+        assert_ne!(steered, initial);
+        for _ in 0..15 {
+            handler.tick(8);
+        }
+        assert_eq!(target(), steered);
+
+        handler.on_packet(session_id, &[252, 64]).unwrap();
+        assert_ne!(target(), steered);
+    }
+
+    #[test]
+    fn test_mid_size_snake_fullness_is_not_saturated() {
+        let handler = test_handler(GameConfig::default());
+        let (session_id, mut rx) = connect(&handler);
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+        let snake_id = handler.sessions.get(session_id).unwrap().snake_id.unwrap();
+        while rx.try_recv().is_ok() {}
+
+        handler.world.write().get_snake_mut(snake_id).unwrap().fullness = 5_000_000;
+        handler.on_packet(session_id, &crate::protocol::client::resync_frame()).unwrap();
+
+        let packet = next_packet(&mut rx, b's');
+        let fam = (packet[12] as u32) << 16 | (packet[13] as u32) << 8 | packet[14] as u32;
+        assert!(fam > 0 && fam < 0xFFFFFF);
+        assert!((fam as f32 / 16777215.0 - 5_000_000.0 / 16777215.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_login_sectors_arrive_in_one_stacked_frame() {
+        let handler = test_handler(GameConfig::default());
+        let (session_id, mut rx) = connect(&handler);
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        while rx.try_recv().is_ok() {}
+        handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+
+        let snake_id = handler.sessions.get(session_id).unwrap().snake_id.unwrap();
+        let expected: Vec<_> = {
+            let world = handler.world.read();
+            let (hx, hy) = world.get_snake(snake_id).unwrap().head_pos();
+            world.sectors.sectors_in_viewport(hx, hy, 2000.0)
+        };
+
+        let frames: Vec<_> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+        let packets = frames
+            .iter()
+            .map(|frame| crate::protocol::reader::parse_stacked_packets(frame, 2))
+            .find(|packets| packets.first().is_some_and(|p| p[0] == b'W'))
+            .expect("no stacked sector frame was sent");
+
+        let sectors: Vec<_> = packets.iter().filter(|p| p[0] == b'W').map(|p| (p[1], p[2])).collect();
+        assert_eq!(sectors, expected);
+        assert!(packets.iter().all(|p| p[0] == b'W' || p[0] == b'F'));
+        for pair in packets.windows(2).filter(|w| w[1][0] == b'F') {
+            assert_eq!((pair[0][0], &pair[0][1..3]), (b'W', &pair[1][1..3]));
+        }
+    }
+
+    #[test]
+    fn test_packets_are_handled_while_ticking() {
+        use std::sync::atomic::AtomicBool;
+
+        let handler = test_handler(GameConfig::default());
+        let (session_id, _rx) = connect(&handler);
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+
+        let stop = AtomicBool::new(false);
+        let handled = std::thread::scope(|scope| {
+            let packets = scope.spawn(|| {
+                let mut handled = 0u32;
+                while !stop.load(Ordering::Relaxed) {
+                    handler.on_packet(session_id, &[10 + (handled % 50) as u8]).unwrap();
+                    handled += 1;
+                }
+                handled
+            });
+            for _ in 0..100 {
+                handler.tick(8);
+            }
+            stop.store(true, Ordering::Relaxed);
+            packets.join().unwrap()
+        });
+
+        assert_eq!(handler.world.read().tick_count, 100);
+        assert!(handled > 0);
+    }
+
+    #[cfg(feature = "bots")]
+    fn place_bot_near(handler: &GameHandler, snake_id: SnakeId) -> SnakeId {
+        place_bot_at(handler, snake_id, 300.0)
+    }
+
+    #[cfg(feature = "bots")]
+    fn place_bot_at(handler: &GameHandler, snake_id: SnakeId, offset_x: f32) -> SnakeId {
+        let mut world = handler.world.write();
+        let (hx, hy) = world.get_snake(snake_id).unwrap().head_pos();
+        let bot_id = world.spawn_bot().unwrap();
+        let bot = world.get_snake_mut(bot_id).unwrap();
+        let (old_x, old_y) = bot.head_pos();
+        for (i, part) in bot.body.iter_mut().enumerate() {
+            part.x = hx + offset_x;
+            part.y = hy + i as f32 * 10.0;
+        }
+        let (new_x, new_y) = bot.head_pos();
+        world.sectors.remove_snake(bot_id, old_x, old_y);
+        world.sectors.add_snake(bot_id, new_x, new_y);
+        bot_id
+    }
+
+    #[cfg(feature = "bots")]
+    fn add_snake_part_count(packet: &[u8]) -> usize {
+        let name_len = packet[22] as usize;
+        let skin_len = packet[23 + name_len] as usize;
+        let tail = 23 + name_len + 1 + skin_len + 1;
+        assert_eq!(packet[tail - 1], 255);
+        1 + (packet.len() - tail - 6) / 2
+    }
+
+    #[cfg(feature = "bots")]
+    #[test]
+    fn test_far_snakes_are_introduced_with_fewer_parts() {
+        let handler = test_handler(GameConfig {
+            far_snake_detail: 2,
+            far_snake_distance: 1000.0,
+            resync_interval_ms: 0,
+            ..Default::default()
+        });
+        let (session_id, mut rx) = connect(&handler);
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+        let snake_id = handler.sessions.get(session_id).unwrap().snake_id.unwrap();
+        let near = place_bot_at(&handler, snake_id, 300.0);
+        let far = place_bot_at(&handler, snake_id, 1500.0);
+        let full = handler.world.read().get_snake(far).unwrap().body.len();
+        assert!(full > 4);
+        while rx.try_recv().is_ok() {}
+
+        handler.on_packet(session_id, &crate::protocol::client::resync_frame()).unwrap();
+        let mut parts = HashMap::new();
+        while let Ok(frame) = rx.try_recv() {
+            if frame[2] == b's' && frame.len() > 6 {
+                parts.insert(u16::from_be_bytes([frame[3], frame[4]]), add_snake_part_count(&frame[2..]));
+            }
+        }
+
+        assert_eq!(parts[&snake_id], handler.world.read().get_snake(snake_id).unwrap().body.len());
+        assert_eq!(parts[&near], full);
+        assert!(parts[&far] < full, "far snake sent {} of {} parts", parts[&far], full);
+
+        {
+            let mut world = handler.world.write();
+            let bot = world.get_snake_mut(far).unwrap();
+            let (old_x, old_y) = bot.head_pos();
+            for part in bot.body.iter_mut() {
+                part.x -= 1000.0;
+            }
+            let (new_x, new_y) = bot.head_pos();
+            world.sectors.remove_snake(far, old_x, old_y);
+            world.sectors.add_snake(far, new_x, new_y);
+        }
+
+        let far_packets = |rx: &mut mpsc::UnboundedReceiver<Frame>| {
+            let mut seen = Vec::new();
+            while let Ok(frame) = rx.try_recv() {
+                for packet in crate::protocol::reader::parse_stacked_packets(&frame, 2) {
+                    if packet[0] == b's' && u16::from_be_bytes([packet[1], packet[2]]) == far {
+                        seen.push((packet.len() > 6).then(|| add_snake_part_count(packet)));
+                    }
+                }
+            }
+            seen
+        };
+        handler.tick(8);
+        let full = handler.world.read().get_snake(far).unwrap().body.len();
+        assert_eq!(far_packets(&mut rx), [None, Some(full)]);
+
+        handler.tick(8);
+        assert!(far_packets(&mut rx).is_empty());
+    }
+
+    #[cfg(feature = "bots")]
+    #[test]
+    fn test_updates_only_cover_visible_sectors() {
+        let handler = test_handler(GameConfig::default());
+        let (session_id, mut rx) = connect(&handler);
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+        let snake_id = handler.sessions.get(session_id).unwrap().snake_id.unwrap();
+        let near = place_bot_at(&handler, snake_id, 300.0);
+        let far = place_bot_at(&handler, snake_id, -4000.0);
+        let edge = place_bot_at(&handler, snake_id, 2050.0);
+        while rx.try_recv().is_ok() {}
+
+        let mut moves = HashMap::new();
+        for _ in 0..10 {
+            handler.tick(8);
+            while let Ok(frame) = rx.try_recv() {
+                for packet in crate::protocol::reader::parse_stacked_packets(&frame, 2) {
+                    if matches!((packet[0], packet.len()), (b'G', 5) | (b'g', 7)) {
+                        *moves.entry(u16::from_be_bytes([packet[1], packet[2]])).or_insert(0) += 1;
+                    }
+                }
+            }
+        }
+
+        assert_eq!(moves.get(&near), Some(&10));
+        assert_eq!(moves.get(&far), None);
+        assert_eq!(moves.get(&edge), None);
+
+        let world = handler.world.read();
+        let frame = FrameSnapshot::capture(&world, &[(session_id, snake_id)], 2000.0);
+        let player = &frame.players[0];
+        let range = world.sectors.sector_range_in_viewport(player.pos.0, player.pos.1, 2000.0).unwrap();
+        let sector_of = |id| {
+            let (x, y) = world.get_snake(id).unwrap().head_pos();
+            world.sectors.world_to_sector(x, y)
+        };
+        let (ex, ey) = sector_of(edge);
+        assert!(range.contains(ex, ey));
+        let (fx, fy) = sector_of(far);
+        assert!(!range.contains(fx, fy));
+
+        let changed: Vec<_> = player.changed.iter().map(|&i| frame.snakes[i].id).collect();
+        assert!(frame.snakes.iter().any(|snake| snake.id == far));
+        assert!(!changed.contains(&far), "{:?}", changed);
+        assert!(changed.contains(&near) && changed.contains(&edge), "{:?}", changed);
+    }
+
+    #[cfg(feature = "bots")]
+    #[test]
+    fn test_killed_snake_is_removed_as_died_before_id_is_recycled() {
+        let handler = test_handler(GameConfig {
+            bot_respawn: false,
+            ..Default::default()
+        });
+        let (session_id, mut rx) = connect(&handler);
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+        let snake_id = handler.sessions.get(session_id).unwrap().snake_id.unwrap();
+        let bot_id = place_bot_near(&handler, snake_id);
+        while rx.try_recv().is_ok() {}
+
+        handler.world.write().get_snake_mut(bot_id).unwrap().kill(0, &FoodPalette::default(), &mut || 0.5);
+        handler.tick(8);
+
+        let removal = [b's', (bot_id >> 8) as u8, bot_id as u8, SnakeRemoveStatus::Died as u8];
+        let mut removed = false;
+        while let Ok(frame) = rx.try_recv() {
+            removed |= crate::protocol::reader::parse_stacked_packets(&frame, 2).contains(&&removal[..]);
+        }
+        assert!(removed);
+        assert!(handler.world.read().get_snake(bot_id).is_some());
+
+        for _ in 0..crate::config::world_consts::DEATH_ANIMATION_TICKS {
+            handler.tick(8);
+        }
+        assert!(handler.world.read().get_snake(bot_id).is_none());
+        assert_eq!(handler.world.write().spawn_bot(), Some(bot_id));
+    }
+
+    #[test]
+    fn test_player_death_is_shown_to_every_viewer_in_range() {
+        let handler = test_handler(GameConfig::default());
+        let mut players = Vec::new();
+        for offset in [0.0, 300.0, 5000.0] {
+            let (session_id, rx) = connect(&handler);
+            handler.on_packet(session_id, &[b'c']).unwrap();
+            handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+            let snake_id = handler.sessions.get(session_id).unwrap().snake_id.unwrap();
+            let mut world = handler.world.write();
+            let center = world.config.game_radius as f32;
+            let snake = world.get_snake_mut(snake_id).unwrap();
+            let (old_x, old_y) = snake.head_pos();
+            for (i, part) in snake.body.iter_mut().enumerate() {
+                part.x = center + offset;
+                part.y = center + i as f32 * 10.0;
+            }
+            world.sectors.remove_snake(snake_id, old_x, old_y);
+            world.sectors.add_snake(snake_id, center + offset, center);
+            drop(world);
+            players.push((session_id, snake_id, rx));
+        }
+        for (_, _, rx) in players.iter_mut() {
+            while rx.try_recv().is_ok() {}
+        }
+
+        let victim = players[0].1;
+        handler.world.write().get_snake_mut(victim).unwrap().kill(0, &FoodPalette::default(), &mut || 0.5);
+        handler.tick(8);
+
+        let removal = [b's', (victim >> 8) as u8, victim as u8, SnakeRemoveStatus::Died as u8];
+        let mut saw_removal = Vec::new();
+        for (_, _, rx) in players.iter_mut().skip(1) {
+            let mut removed = false;
+            while let Ok(frame) = rx.try_recv() {
+                removed |= crate::protocol::reader::parse_stacked_packets(&frame, 2).contains(&&removal[..]);
+            }
+            saw_removal.push(removed);
+        }
+        assert_eq!(saw_removal, vec![true, false]);
+        assert_eq!(next_packet(&mut players[0].2, b'v'), [b'v', GameEndStatus::Normal as u8]);
+    }
+
+    #[test]
+    fn test_player_death_ends_the_game() {
+        let handler = test_handler(GameConfig::default());
+        let (session_id, mut rx) = connect(&handler);
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+        let snake_id = handler.sessions.get(session_id).unwrap().snake_id.unwrap();
+        while rx.try_recv().is_ok() {}
+
+        handler.world.write().get_snake_mut(snake_id).unwrap().kill(0, &FoodPalette::default(), &mut || 0.5);
+        handler.tick(8);
+
+        assert_eq!(next_packet(&mut rx, b'v'), [b'v', GameEndStatus::Normal as u8]);
+        let session = handler.sessions.get(session_id).unwrap();
+        assert_eq!(session.state, crate::server::session::SessionState::Dead);
+        assert_eq!(session.snake_id, None);
+    }
+
+    #[test]
+    fn test_identity_before_handshake_uses_default_framing() {
+        let handler = test_handler(GameConfig::default());
+        let (session_id, mut rx) = connect(&handler);
+        handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+
+        let session = handler.sessions.get(session_id).unwrap();
+        assert!(session.is_playing());
+        assert!(session.protocol.handshake_complete && session.protocol.want_etm);
+        drop(session);
+
+        let init = rx.try_recv().unwrap();
+        assert_eq!(init[2], b'a', "Init was not ETM-prefixed: {:?}", &init[..4]);
+
+        let (greeted, mut greeted_rx) = connect(&handler);
+        handler.on_packet(greeted, &[b'c']).unwrap();
+        handler.on_packet(greeted, &identity_with_skin(&[])).unwrap();
+        assert_eq!(init[2..], next_packet(&mut greeted_rx, b'a')[..]);
+    }
+
+    #[test]
+    fn test_respawn_handshake_timeout_starts_at_start_login() {
+        let handler = test_handler(GameConfig {
+            handshake_timeout_ms: 50,
+            ..Default::default()
+        });
+        let (session_id, _rx) = connect(&handler);
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(80));
+
+        let first = handler.sessions.get(session_id).unwrap().snake_id.unwrap();
+        handler.world.write().get_snake_mut(first).unwrap().kill(0, &FoodPalette::default(), &mut || 0.5);
+        handler.tick(8);
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        handler.tick(8);
+        assert_eq!(handler.sessions.get(session_id).unwrap().state, SessionState::Handshake);
+
+        handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+        assert!(handler.sessions.get(session_id).unwrap().is_playing());
+
+        let second = handler.sessions.get(session_id).unwrap().snake_id.unwrap();
+        handler.world.write().get_snake_mut(second).unwrap().kill(0, &FoodPalette::default(), &mut || 0.5);
+        handler.tick(8);
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(80));
+        handler.tick(8);
+        assert!(handler.sessions.get(session_id).is_none());
+    }
+
+    #[test]
+    fn test_dead_session_can_respawn_on_the_same_connection() {
+        let handler = test_handler(GameConfig::default());
+        let (session_id, mut rx) = connect(&handler);
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+        let first = handler.sessions.get(session_id).unwrap().snake_id.unwrap();
+        handler.world.write().get_snake_mut(first).unwrap().kill(0, &FoodPalette::default(), &mut || 0.5);
+        handler.tick(8);
+        assert_eq!(handler.sessions.get(session_id).unwrap().state, SessionState::Dead);
+        while rx.try_recv().is_ok() {}
+
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+
+        let session = handler.sessions.get(session_id).unwrap();
+        assert!(session.is_playing());
+        let second = session.snake_id.unwrap();
+        assert_ne!(second, first);
+        let world = handler.world.read();
+        let snake = world.get_snake(second).unwrap();
+        assert!(!snake.dead);
+        let (hx, hy) = snake.head_pos();
+        let (enter_radius, _) = handler.config.sector_view_radii(2000.0);
+        let in_view = world.sectors.sectors_in_viewport(hx, hy, enter_radius);
+        let visible = session.sector_tracker.visible_sectors();
+        assert!(!visible.is_empty() && visible.iter().all(|s| in_view.contains(s)));
+        drop(session);
+        next_packet(&mut rx, b'a');
+        let add = next_packet(&mut rx, b's');
+        assert_eq!(u16::from_be_bytes([add[1], add[2]]), second);
+
+        let disabled = test_handler(GameConfig {
+            player_respawn: false,
+            ..Default::default()
+        });
+        let (session_id, _rx) = connect(&disabled);
+        disabled.on_packet(session_id, &[b'c']).unwrap();
+        disabled.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+        let snake_id = disabled.sessions.get(session_id).unwrap().snake_id.unwrap();
+        disabled.world.write().get_snake_mut(snake_id).unwrap().kill(0, &FoodPalette::default(), &mut || 0.5);
+        disabled.tick(8);
+        disabled.on_packet(session_id, &[b'c']).unwrap();
+        disabled.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+        assert_eq!(disabled.sessions.get(session_id).unwrap().snake_id, None);
+    }
+
+    fn login_frame(protocol_version: u8) -> Vec<u8> {
+        login_frame_with_checksum(protocol_version, [0; 20])
+    }
+
+    fn login_frame_with_checksum(protocol_version: u8, checksum: [u8; 20]) -> Vec<u8> {
+        let mut frame = vec![b's', protocol_version, 0, 1];
+        frame.extend_from_slice(&checksum);
+        frame.extend_from_slice(&[3, 4, b'T', b'e', b's', b't']);
+        frame
+    }
+
+    #[test]
+    fn test_long_names_are_capped_by_char_count() {
+        let handler = test_handler(GameConfig {
+            max_name_chars: 16,
+            ..Default::default()
+        });
+        let name = "ñ".repeat(10) + &"x".repeat(30);
+        assert_eq!(name.chars().count(), 40);
+
+        let (session_id, _rx) = connect(&handler);
+        handler.on_packet(session_id, &crate::protocol::client::identity_frame(0, &name)).unwrap();
+        let snake_id = handler.sessions.get(session_id).unwrap().snake_id.unwrap();
+
+        let expected = "ñ".repeat(10) + "xxxxxx";
+        assert_eq!(handler.world.read().get_snake(snake_id).unwrap().name, expected);
+        assert_eq!(handler.sessions.get(session_id).unwrap().name, expected);
+        assert!(GameConfig::builder().max_name_chars(0).build().is_err());
+    }
+
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_blank_text_frames_are_ignored_quietly() {
+        let logs = CapturedLogs::default();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_ansi(false)
+            .with_writer({
+                let logs = logs.clone();
+                move || logs.clone()
+            })
+            .finish();
+
+        let handler = test_handler(GameConfig::default());
+        let (session_id, _rx) = connect(&handler);
+        tracing::subscriber::with_default(subscriber, || {
+            for text in ["", "\n", " \r\n\t"] {
+                handler.on_text(session_id, text).unwrap();
+            }
+            handler.on_packet(session_id, &[]).unwrap();
+            for _ in 0..10 {
+                handler.on_packet(session_id, &[b'x', 1]).unwrap();
+            }
+        });
+
+        let logs = String::from_utf8(logs.0.lock().clone()).unwrap();
+        assert!(!logs.contains("WARN"), "unexpected warnings:\n{}", logs);
+        assert_eq!(logs.matches("Ignoring packet").count(), 1, "{}", logs);
+        let session = handler.sessions.get(session_id).unwrap();
+        assert!(!session.is_disconnected());
+    }
+
+    #[test]
+    fn test_paused_world_keeps_its_clock_but_not_its_snakes() {
+        let handler = test_handler(GameConfig::default());
+        let (session_id, _rx) = connect(&handler);
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+        let snake_id = handler.sessions.get(session_id).unwrap().snake_id.unwrap();
+        let head = || handler.world.read().get_snake(snake_id).unwrap().head_pos();
+
+        handler.set_paused(true);
+        assert!(handler.is_paused());
+        let (ticks_before, head_before) = (handler.world.read().tick_count, head());
+        for _ in 0..10 {
+            handler.tick(8);
+        }
+        assert_eq!(handler.world.read().tick_count, ticks_before + 10);
+        assert_eq!(head(), head_before);
+        assert!(handler.sessions.get(session_id).unwrap().is_playing());
+
+        handler.set_paused(false);
+        handler.tick(8);
+        assert_ne!(head(), head_before);
+    }
+
+    fn announcements_received(rx: &mut UnboundedReceiver<Frame>, text: &str) -> usize {
+        std::iter::from_fn(|| rx.try_recv().ok())
+            .filter(|frame| frame.get(2) == Some(&b'm') && frame.ends_with(text.as_bytes()))
+            .count()
+    }
+
+    #[test]
+    fn test_scheduled_announcement_fires_after_its_interval() {
+        let handler = test_handler(GameConfig {
+            announcements: vec![crate::config::Announcement {
+                interval_ms: 50,
+                text: "Maintenance soon".to_string(),
+            }],
+            ..Default::default()
+        });
+        let (session_id, mut rx) = connect(&handler);
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+        while rx.try_recv().is_ok() {}
+
+        handler.tick(8);
+        assert_eq!(announcements_received(&mut rx, "Maintenance soon"), 0);
+
+        std::thread::sleep(std::time::Duration::from_millis(60));
+        handler.tick(8);
+        assert_eq!(announcements_received(&mut rx, "Maintenance soon"), 1);
+
+        handler.tick(8);
+        assert_eq!(announcements_received(&mut rx, "Maintenance soon"), 0);
+    }
+
+    #[test]
+    fn test_pre_login_movement_does_not_take_the_world_lock() {
+        let handler = Arc::new(test_handler(GameConfig::default()));
+        let (session_id, _rx) = connect(&handler);
+        let movement = [
+            crate::protocol::client::angle_frame(1.0),
+            crate::protocol::client::boost_frame(true),
+            crate::protocol::client::boost_frame(false),
+            vec![252, 64],
+        ];
+
+        let guard = handler.world.read();
+        let (done_tx, done_rx) = std::sync::mpsc::channel();
+        let flood = {
+            let handler = handler.clone();
+            std::thread::spawn(move || {
+                for frame in movement.iter().cycle().take(4000) {
+                    handler.on_packet(session_id, frame).unwrap();
+                }
+                done_tx.send(()).unwrap();
+            })
+        };
+
+        let finished = done_rx.recv_timeout(std::time::Duration::from_secs(5));
+        drop(guard);
+        flood.join().unwrap();
+        assert!(finished.is_ok(), "pre-login movement blocked on the world lock");
+    }
+
+    fn food_within(handler: &GameHandler, snake_id: SnakeId, radius: f32) -> usize {
+        let world = handler.world.read();
+        let (x, y) = world.get_snake(snake_id).unwrap().head_pos();
+        world
+            .sectors
+            .food_near(x, y, radius)
+            .iter()
+            .filter(|f| (f.x as f32 - x).hypot(f.y as f32 - y) <= radius + 1.0)
+            .count()
+    }
+
+    #[test]
+    fn test_feed_command_spawns_food_near_the_target() {
+        for debug_text in [false, true] {
+            let handler = test_handler(GameConfig {
+                debug_text,
+                ..Default::default()
+            });
+            let (session_id, mut rx) = connect(&handler);
+            handler.on_packet(session_id, &[b'c']).unwrap();
+            handler.on_packet(session_id, &identity_with_skin(&[])).unwrap();
+            let snake_id = handler.sessions.get(session_id).unwrap().snake_id.unwrap();
+            while rx.try_recv().is_ok() {}
+
+            let before = food_within(&handler, snake_id, world_consts::FEED_RADIUS);
+            let command = format!(r#"{{"cmd":"feed","session":{},"count":30}}"#, session_id);
+            handler.on_text(session_id, &command).unwrap();
+            let added = food_within(&handler, snake_id, world_consts::FEED_RADIUS) - before;
+
+            handler.tick(8);
+            let spawn_packets: usize = std::iter::from_fn(|| rx.try_recv().ok())
+                .map(|frame| {
+                    crate::protocol::reader::parse_stacked_packets(&frame, 2)
+                        .iter()
+                        .filter(|p| p[0] == b'b')
+                        .count()
+                })
+                .sum();
+
+            if debug_text {
+                assert_eq!(added, 30);
+                assert!(spawn_packets >= 30, "only {} spawn packets", spawn_packets);
             } else {
-                packet_bytes.to_vec()
-            };
+                assert_eq!(added, 0);
+            }
+        }
+    }
 
-            session.update_last_sent();
-            let _ = session.send(data);
+    fn next_packet(rx: &mut UnboundedReceiver<Frame>, packet_type: u8) -> Vec<u8> {
+        while let Ok(frame) = rx.try_recv() {
+            if frame.get(2) == Some(&packet_type) {
+                return frame[2..].to_vec();
+            }
         }
+        panic!("no packet of type {:?} was sent", packet_type as char);
+    }
+
+    #[test]
+    fn test_v25_client_keeps_its_protocol_version() {
+        let handler = test_handler(GameConfig::default());
+        let (session_id, mut rx) = connect(&handler);
+        handler.on_packet(session_id, &[b'c']).unwrap();
+        handler.on_packet(session_id, &login_frame(25)).unwrap();
+
+        let session = handler.sessions.get(session_id).unwrap();
+        assert_eq!(session.protocol.protocol_version, 25);
+        assert!(session.is_modern_protocol);
+        drop(session);
+
+        let init = next_packet(&mut rx, b'a');
+        assert_eq!((init.len(), init[23]), (27, 25));
+
+        let (future, mut future_rx) = connect(&handler);
+        handler.on_packet(future, &[b'c']).unwrap();
+        handler.on_packet(future, &login_frame(40)).unwrap();
+        assert_eq!(next_packet(&mut future_rx, b'a')[23], 31);
+    }
+
+    #[test]
+    fn test_protocol_version_selects_init_and_minimap_forms() {
+        let mut handler = test_handler(GameConfig::default());
+
+        let (modern, mut modern_rx) = connect(&handler);
+        handler.on_packet(modern, &[b'c']).unwrap();
+        handler.on_packet(modern, &login_frame(31)).unwrap();
+
+        let (legacy, mut legacy_rx) = connect(&handler);
+        handler.on_packet(legacy, &[b'c']).unwrap();
+        handler.on_packet(legacy, &[b's', 14, 0, 4, b'T', b'e', b's', b't']).unwrap();
+
+        assert!(handler.sessions.get(modern).unwrap().is_modern_protocol);
+        assert!(!handler.sessions.get(legacy).unwrap().is_modern_protocol);
+
+        let init = next_packet(&mut modern_rx, b'a');
+        assert_eq!(init.len(), 27);
+        assert_eq!(init[23], 31);
+        let init = next_packet(&mut legacy_rx, b'a');
+        assert_eq!(init.len(), 24);
+        assert_eq!(init[23], 14);
+
+        handler.broadcast_minimap();
+        next_packet(&mut modern_rx, b'M');
+        next_packet(&mut legacy_rx, b'u');
+
+        handler.config.max_protocol_version = 14;
+        let (capped, mut capped_rx) = connect(&handler);
+        handler.on_packet(capped, &[b'c']).unwrap();
+        handler.on_packet(capped, &login_frame(31)).unwrap();
+        assert_eq!(next_packet(&mut capped_rx, b'a').len(), 24);
     }
 }
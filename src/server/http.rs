@@ -0,0 +1,521 @@
+
+
+
+use crate::config::timing;
+use crate::game::world::SharedWorld;
+use crate::server::handler::GameHandler;
+use crate::server::metrics::TickTimings;
+use crate::server::session::SharedSessionManager;
+use crate::server::websocket::ServerStats;
+use serde_json::json;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tracing::{debug, info, warn};
+
+
+const MAX_REQUEST_HEAD: usize = 4096;
+
+
+#[derive(Debug)]
+pub struct LoopHealth {
+    started: Instant,
+    last_tick_ms: AtomicU64,
+    dropped_ticks: AtomicU64,
+    tick_timings: TickTimings,
+}
+
+impl LoopHealth {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+            last_tick_ms: AtomicU64::new(0),
+            dropped_ticks: AtomicU64::new(0),
+            tick_timings: TickTimings::new(),
+        }
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+   
+    pub fn record_tick(&self, took: Duration) {
+        self.tick_timings.record(took);
+    }
+
+    pub fn tick_timings(&self) -> &TickTimings {
+        &self.tick_timings
+    }
+
+   
+    pub fn add_dropped_ticks(&self, count: u64) {
+        self.dropped_ticks.fetch_add(count, Ordering::Relaxed);
+    }
+
+    pub fn dropped_ticks(&self) -> u64 {
+        self.dropped_ticks.load(Ordering::Relaxed)
+    }
+
+   
+    pub fn mark_tick(&self) {
+        let elapsed = self.started.elapsed().as_millis() as u64;
+        self.last_tick_ms.store(elapsed, Ordering::Relaxed);
+    }
+
+   
+    pub fn since_last_tick(&self, now: Instant) -> Duration {
+        let last_tick = self.started + Duration::from_millis(self.last_tick_ms.load(Ordering::Relaxed));
+        now.saturating_duration_since(last_tick)
+    }
+
+   
+    pub fn is_healthy_at(&self, now: Instant) -> bool {
+        self.since_last_tick(now) <= Duration::from_millis(timing::LOOP_STALL_THRESHOLD_MS)
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.is_healthy_at(Instant::now())
+    }
+}
+
+impl Default for LoopHealth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub type SharedLoopHealth = Arc<LoopHealth>;
+
+
+#[derive(Clone)]
+pub struct HttpContext {
+    pub health: SharedLoopHealth,
+    pub world: SharedWorld,
+    pub sessions: SharedSessionManager,
+   
+    pub admin: Option<AdminContext>,
+}
+
+
+#[derive(Clone)]
+pub struct AdminContext {
+    pub token: String,
+    pub handler: Arc<GameHandler>,
+}
+
+impl HttpContext {
+   
+    fn stats_json(&self) -> String {
+        let stats = ServerStats::gather(&self.world, &self.sessions, &self.health);
+        let hall_of_fame = self.world.read().hall_of_fame().to_json();
+        json!({
+            "connections": stats.connections,
+            "players": stats.players,
+            "snakes": stats.snakes,
+            "food": stats.food,
+            "tick_count": stats.tick_count,
+            "dropped_ticks": stats.dropped_ticks,
+            "uptime_ms": stats.uptime_ms,
+            "avg_tick_us": stats.avg_tick_us,
+            "p99_tick_us": stats.p99_tick_us,
+            "bots": stats.bots,
+            "humans": stats.humans,
+            "leaderboard": hall_of_fame,
+        })
+        .to_string()
+    }
+
+   
+    fn metrics_text(&self) -> String {
+        let stats = ServerStats::gather(&self.world, &self.sessions, &self.health);
+        self.sessions.packet_stats().render_prometheus() + &stats.render_prometheus()
+    }
+}
+
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub reason: &'static str,
+    pub content_type: &'static str,
+    pub body: String,
+}
+
+impl HttpResponse {
+    fn new(status: u16, reason: &'static str, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            reason,
+            content_type: "text/plain",
+            body: body.into(),
+        }
+    }
+
+    fn json(body: String) -> Self {
+        Self {
+            content_type: "application/json",
+            ..Self::new(200, "OK", body)
+        }
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            self.status,
+            self.reason,
+            self.content_type,
+            self.body.len(),
+            self.body
+        )
+        .into_bytes()
+    }
+}
+
+
+pub fn route(path: &str, ctx: &HttpContext, now: Instant) -> Option<HttpResponse> {
+    match path {
+        "/healthz" => Some(if ctx.health.is_healthy_at(now) {
+            HttpResponse::new(200, "OK", "ok")
+        } else {
+            HttpResponse::new(503, "Service Unavailable", "stalled")
+        }),
+        "/metrics" => Some(HttpResponse::new(200, "OK", ctx.metrics_text())),
+        "/stats" => Some(HttpResponse::json(ctx.stats_json())),
+        _ => None,
+    }
+}
+
+
+fn admin_route(request: &PlainRequest, admin: Option<&AdminContext>) -> HttpResponse {
+    let Some(admin) = admin else {
+        return HttpResponse::new(404, "Not Found", "not found");
+    };
+    let authorized = request
+        .authorization
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| token_matches(token.trim(), &admin.token));
+    if !authorized {
+        return HttpResponse::new(401, "Unauthorized", "unauthorized");
+    }
+    if request.method != "POST" {
+        return HttpResponse::new(405, "Method Not Allowed", "admin commands must be POSTed");
+    }
+
+    let handler = &admin.handler;
+    match request.path {
+        "/admin/ban" | "/admin/unban" => {
+            let Some(ip) = query_param(request.query, "ip").and_then(|ip| ip.parse::<IpAddr>().ok()) else {
+                return HttpResponse::new(400, "Bad Request", "ip must be an IP address");
+            };
+            let banned = request.path == "/admin/ban";
+            let result = if banned { handler.bans().ban(ip) } else { handler.bans().unban(ip) };
+            match result {
+                Ok(changed) => {
+                    info!(%ip, banned, changed, "Ban list updated");
+                    HttpResponse::new(200, "OK", if changed { "updated" } else { "unchanged" })
+                }
+                Err(e) => {
+                    warn!(%ip, banned, error = %e, "Could not persist ban list");
+                    HttpResponse::new(500, "Internal Server Error", e.to_string())
+                }
+            }
+        }
+        "/admin/say" => match query_param(request.query, "message").filter(|m| !m.is_empty()) {
+            Some(message) => {
+                handler.broadcast_announcement(&message);
+                HttpResponse::new(200, "OK", "ok")
+            }
+            None => HttpResponse::new(400, "Bad Request", "message is required"),
+        },
+        "/admin/pause" => {
+            let paused = match query_param(request.query, "value").as_deref() {
+                None | Some("true") => true,
+                Some("false") => false,
+                Some(_) => return HttpResponse::new(400, "Bad Request", "value must be true or false"),
+            };
+            handler.set_paused(paused);
+            HttpResponse::new(200, "OK", "ok")
+        }
+        _ => HttpResponse::new(404, "Not Found", "not found"),
+    }
+}
+
+
+fn token_matches(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given.bytes().zip(expected.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+
+fn query_param(query: &str, key: &str) -> Option<String> {
+    query
+        .split('&')
+        .find_map(|pair| {
+            let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+            (k == key).then(|| percent_decode(v))
+        })
+        .flatten()
+}
+
+fn percent_decode(text: &str) -> Option<String> {
+    let mut out = Vec::with_capacity(text.len());
+    let mut bytes = text.bytes();
+    while let Some(b) = bytes.next() {
+        match b {
+            b'+' => out.push(b' '),
+            b'%' => {
+                let hex = [bytes.next()?, bytes.next()?];
+                out.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            }
+            _ => out.push(b),
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PlainRequest<'a> {
+    method: &'a str,
+    path: &'a str,
+    query: &'a str,
+    authorization: Option<&'a str>,
+}
+
+
+fn plain_request(head: &[u8]) -> Option<PlainRequest<'_>> {
+    let head = std::str::from_utf8(head).ok()?;
+    let (request_line, headers) = head.split_once("\r\n")?;
+
+    let mut authorization = None;
+    for (name, value) in headers.lines().filter_map(|line| line.split_once(':')) {
+        if name.eq_ignore_ascii_case("upgrade") {
+            return None;
+        }
+        if name.eq_ignore_ascii_case("authorization") {
+            authorization = Some(value.trim());
+        }
+    }
+
+    let mut parts = request_line.split(' ');
+    let method = parts.next().filter(|m| matches!(*m, "GET" | "POST"))?;
+    let target = parts.next()?;
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    Some(PlainRequest { method, path, query, authorization })
+}
+
+
+pub async fn try_serve_http(stream: &mut TcpStream, ctx: &HttpContext) -> std::io::Result<bool> {
+    serve_http_within(stream, ctx, Duration::from_millis(timing::REQUEST_HEAD_TIMEOUT_MS)).await
+}
+
+async fn serve_http_within(
+    stream: &mut TcpStream,
+    ctx: &HttpContext,
+    head_timeout: Duration,
+) -> std::io::Result<bool> {
+    let mut buf = vec![0u8; MAX_REQUEST_HEAD];
+
+   
+    let peek_head = async {
+        loop {
+            let n = stream.peek(&mut buf).await?;
+            if n == 0 {
+                return Ok(None);
+            }
+            if let Some(pos) = buf[..n].windows(4).position(|w| w == b"\r\n\r\n") {
+                return Ok(Some(pos + 4));
+            }
+            if n == buf.len() {
+                return Ok(None);
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    };
+    let head_len = match tokio::time::timeout(head_timeout, peek_head).await {
+        Ok(Ok(Some(head_len))) => head_len,
+        Ok(Ok(None)) => return Ok(false),
+        Ok(Err(e)) => return Err(e),
+        Err(_) => {
+            debug!(?head_timeout, "No request head in time, closing");
+            stream.shutdown().await?;
+            return Ok(true);
+        }
+    };
+
+    let response = match plain_request(&buf[..head_len]) {
+        Some(request) if request.path.starts_with("/admin/") => admin_route(&request, ctx.admin.as_ref()),
+        Some(request) if request.method == "GET" => match route(request.path, ctx, Instant::now()) {
+            Some(response) => response,
+            None => HttpResponse::new(404, "Not Found", "not found"),
+        },
+        Some(_) => HttpResponse::new(405, "Method Not Allowed", "method not allowed"),
+        None => return Ok(false),
+    };
+
+    stream.read_exact(&mut buf[..head_len]).await?;
+    stream.write_all(&response.to_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GameConfig;
+    use crate::game::world::create_shared_world;
+    use crate::server::session::create_session_manager;
+
+    fn test_context() -> HttpContext {
+        HttpContext {
+            health: Arc::new(LoopHealth::new()),
+            world: create_shared_world(GameConfig::default()),
+            sessions: create_session_manager(),
+            admin: None,
+        }
+    }
+
+    fn admin_context() -> AdminContext {
+        let config = GameConfig::default();
+        let world = create_shared_world(config.clone());
+        AdminContext {
+            token: "s3cret".to_string(),
+            handler: Arc::new(GameHandler::new(world, create_session_manager(), config)),
+        }
+    }
+
+    fn admin_request<'a>(method: &'a str, target: &'a str, authorization: Option<&'a str>) -> PlainRequest<'a> {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        PlainRequest { method, path, query, authorization }
+    }
+
+    #[test]
+    fn test_fresh_loop_is_healthy() {
+        let ctx = test_context();
+        let response = route("/healthz", &ctx, Instant::now()).unwrap();
+        assert_eq!(response.status, 200);
+        assert_eq!(response.body, "ok");
+    }
+
+    #[test]
+    fn test_stalled_loop_is_unhealthy() {
+        let ctx = test_context();
+        ctx.health.mark_tick();
+
+        let later = Instant::now() + Duration::from_millis(timing::LOOP_STALL_THRESHOLD_MS + 1000);
+        let response = route("/healthz", &ctx, later).unwrap();
+        assert_eq!(response.status, 503);
+        assert!(!ctx.health.is_healthy_at(later));
+    }
+
+    #[test]
+    fn test_metrics_route() {
+        let ctx = test_context();
+        ctx.sessions.packet_stats().record(b"p");
+        let response = route("/metrics", &ctx, Instant::now()).unwrap();
+        assert_eq!(response.status, 200);
+        assert!(response.body.contains("slither_packets_total{opcode=\"p\"} 1"));
+        assert!(response.body.contains("# TYPE slither_uptime_ms gauge\n"));
+    }
+
+    #[test]
+    fn test_stats_route_includes_leaderboard() {
+        let ctx = test_context();
+        let response = route("/stats", &ctx, Instant::now()).unwrap();
+        assert_eq!(response.content_type, "application/json");
+
+        let body: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+        assert_eq!(body["players"], 0);
+        assert_eq!(body["bots"], 0);
+        assert!(body["p99_tick_us"].is_u64());
+        assert!(body["leaderboard"].as_array().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_silent_client_is_closed_after_the_head_timeout() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).await.unwrap();
+        let (mut stream, _) = listener.accept().await.unwrap();
+
+        let served = tokio::time::timeout(
+            Duration::from_secs(5),
+            serve_http_within(&mut stream, &test_context(), Duration::from_millis(50)),
+        )
+        .await
+        .expect("silent client held the connection open");
+        assert!(served.unwrap());
+
+        let mut rest = Vec::new();
+        client.read_to_end(&mut rest).await.unwrap();
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn test_plain_request() {
+        assert_eq!(
+            plain_request(b"GET /healthz?verbose=1 HTTP/1.1\r\nHost: x\r\n\r\n"),
+            Some(admin_request("GET", "/healthz?verbose=1", None))
+        );
+        assert_eq!(
+            plain_request(b"GET / HTTP/1.1\r\nHost: x\r\nUpgrade: websocket\r\n\r\n"),
+            None
+        );
+        assert_eq!(
+            plain_request(b"POST /admin/pause HTTP/1.1\r\nauthorization:  Bearer abc \r\n\r\n"),
+            Some(admin_request("POST", "/admin/pause", Some("Bearer abc")))
+        );
+        assert_eq!(plain_request(b"PUT /healthz HTTP/1.1\r\n\r\n"), None);
+    }
+
+    #[test]
+    fn test_admin_routes_require_the_token() {
+        let request = admin_request("POST", "/admin/pause", Some("Bearer s3cret"));
+        assert_eq!(admin_route(&request, None).status, 404);
+
+        let admin = admin_context();
+        for authorization in [None, Some("Bearer wrong"), Some("s3cret"), Some("Bearer s3cre")] {
+            let request = admin_request("POST", "/admin/pause", authorization);
+            assert_eq!(admin_route(&request, Some(&admin)).status, 401);
+        }
+        assert!(!admin.handler.is_paused());
+
+        let request = admin_request("GET", "/admin/pause", Some("Bearer s3cret"));
+        assert_eq!(admin_route(&request, Some(&admin)).status, 405);
+        assert!(!admin.handler.is_paused());
+    }
+
+    #[test]
+    fn test_admin_commands() {
+        let admin = admin_context();
+        let post = |target| admin_route(&admin_request("POST", target, Some("Bearer s3cret")), Some(&admin));
+
+        assert_eq!(post("/admin/pause").status, 200);
+        assert!(admin.handler.is_paused());
+        assert_eq!(post("/admin/pause?value=false").status, 200);
+        assert!(!admin.handler.is_paused());
+        assert_eq!(post("/admin/pause?value=maybe").status, 400);
+
+        let ip: IpAddr = "10.0.0.7".parse().unwrap();
+        assert_eq!(post("/admin/ban?ip=10.0.0.7").body, "updated");
+        assert!(admin.handler.bans().is_banned(ip));
+        assert_eq!(post("/admin/ban?ip=10.0.0.7").body, "unchanged");
+        assert_eq!(post("/admin/unban?ip=10.0.0.7").body, "updated");
+        assert!(!admin.handler.bans().is_banned(ip));
+        assert_eq!(post("/admin/ban?ip=example.com").status, 400);
+
+        assert_eq!(post("/admin/say?message=hello").status, 200);
+        assert_eq!(post("/admin/say").status, 400);
+        assert_eq!(post("/admin/teleport").status, 404);
+    }
+
+    #[test]
+    fn test_query_param_decoding() {
+        assert_eq!(query_param("a=1&message=hi+there%21", "message").as_deref(), Some("hi there!"));
+        assert_eq!(query_param("message=%zz", "message"), None);
+        assert_eq!(query_param("ip=1", "message"), None);
+    }
+}
@@ -0,0 +1,170 @@
+
+
+
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+
+#[derive(Debug)]
+pub struct PacketStats {
+    counts: [AtomicU64; 256],
+    bytes: [AtomicU64; 256],
+}
+
+impl PacketStats {
+    pub fn new() -> Self {
+        Self {
+            counts: std::array::from_fn(|_| AtomicU64::new(0)),
+            bytes: std::array::from_fn(|_| AtomicU64::new(0)),
+        }
+    }
+
+   
+    pub fn record(&self, packet: &[u8]) {
+        let Some(&opcode) = packet.first() else {
+            return;
+        };
+        self.counts[opcode as usize].fetch_add(1, Ordering::Relaxed);
+        self.bytes[opcode as usize].fetch_add(packet.len() as u64, Ordering::Relaxed);
+    }
+
+    pub fn count(&self, opcode: u8) -> u64 {
+        self.counts[opcode as usize].load(Ordering::Relaxed)
+    }
+
+    pub fn bytes(&self, opcode: u8) -> u64 {
+        self.bytes[opcode as usize].load(Ordering::Relaxed)
+    }
+
+   
+    pub fn snapshot(&self) -> Vec<(u8, u64, u64)> {
+        (0..=u8::MAX)
+            .map(|opcode| (opcode, self.count(opcode), self.bytes(opcode)))
+            .filter(|&(_, count, _)| count > 0)
+            .collect()
+    }
+
+   
+    pub fn render_prometheus(&self) -> String {
+        let snapshot = self.snapshot();
+        let mut out = String::new();
+
+        out.push_str("# TYPE slither_packets_total counter\n");
+        for &(opcode, count, _) in &snapshot {
+            let _ = writeln!(out, "slither_packets_total{{opcode=\"{}\"}} {}", opcode_label(opcode), count);
+        }
+
+        out.push_str("# TYPE slither_packet_bytes_total counter\n");
+        for &(opcode, _, bytes) in &snapshot {
+            let _ = writeln!(out, "slither_packet_bytes_total{{opcode=\"{}\"}} {}", opcode_label(opcode), bytes);
+        }
+
+        out
+    }
+}
+
+impl Default for PacketStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+pub const TICK_TIMING_WINDOW: usize = 1024;
+
+
+#[derive(Debug, Default)]
+pub struct TickTimings {
+    window: Mutex<VecDeque<Duration>>,
+}
+
+impl TickTimings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&self, took: Duration) {
+        let mut window = self.window.lock();
+        if window.len() == TICK_TIMING_WINDOW {
+            window.pop_front();
+        }
+        window.push_back(took);
+    }
+
+    pub fn len(&self) -> usize {
+        self.window.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn average(&self) -> Duration {
+        let window = self.window.lock();
+        if window.is_empty() {
+            return Duration::ZERO;
+        }
+        window.iter().sum::<Duration>() / window.len() as u32
+    }
+
+   
+    pub fn percentile(&self, p: f64) -> Duration {
+        let mut sorted: Vec<_> = self.window.lock().iter().copied().collect();
+        if sorted.is_empty() {
+            return Duration::ZERO;
+        }
+        sorted.sort_unstable();
+        let rank = (p.clamp(0.0, 1.0) * sorted.len() as f64).ceil() as usize;
+        sorted[rank.saturating_sub(1)]
+    }
+}
+
+
+fn opcode_label(opcode: u8) -> String {
+    if opcode.is_ascii_alphanumeric() {
+        (opcode as char).to_string()
+    } else {
+        opcode.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_prometheus() {
+        let stats = PacketStats::new();
+        stats.record(b"g\x00\x01\x80\x80");
+        stats.record(&[]);
+        stats.record(&[0x80, 1]);
+
+        let text = stats.render_prometheus();
+        assert!(text.contains("slither_packets_total{opcode=\"g\"} 1\n"));
+        assert!(text.contains("slither_packet_bytes_total{opcode=\"g\"} 5\n"));
+        assert!(text.contains("slither_packets_total{opcode=\"128\"} 1\n"));
+        assert_eq!(stats.snapshot().len(), 2);
+    }
+
+    #[test]
+    fn test_tick_timings_window() {
+        let timings = TickTimings::new();
+        assert_eq!(timings.average(), Duration::ZERO);
+
+        for ms in 1..=100 {
+            timings.record(Duration::from_millis(ms));
+        }
+        assert_eq!(timings.average(), Duration::from_micros(50_500));
+        assert_eq!(timings.percentile(0.99), Duration::from_millis(99));
+        assert_eq!(timings.percentile(1.0), Duration::from_millis(100));
+
+        for _ in 0..TICK_TIMING_WINDOW {
+            timings.record(Duration::from_millis(2));
+        }
+        assert_eq!(timings.len(), TICK_TIMING_WINDOW);
+        assert_eq!(timings.percentile(0.99), Duration::from_millis(2));
+    }
+}
@@ -8,7 +8,14 @@
 pub mod session;
 pub mod handler;
 pub mod websocket;
+pub mod http;
+pub mod metrics;
+pub mod bans;
+pub mod tls;
 
 pub use session::{Session, SessionManager};
 pub use handler::GameHandler;
-pub use websocket::run_server;
+pub use websocket::{
+    run_server, run_server_on, run_server_with, spawn_listeners, spawn_listeners_with, spawn_server,
+    ServerHandle,
+};
@@ -1,19 +1,28 @@
 
 
+use crate::config::timing;
 use crate::game::sector::SectorTracker;
-use crate::protocol::incoming::ProtocolState;
+use crate::protocol::incoming::{IncomingPacket, ProtocolState};
+use crate::protocol::writer::append_stacked_packet;
 use crate::protocol::types::SnakeId;
+use crate::server::metrics::PacketStats;
+use bytes::Bytes;
 use dashmap::DashMap;
+use parking_lot::Mutex;
+use std::collections::{HashSet, VecDeque};
 use std::net::SocketAddr;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
 
 pub type SessionId = u64;
 
 
+pub type Frame = Bytes;
+
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SessionState {
    
@@ -41,6 +50,10 @@ pub struct Session {
    
     pub protocol: ProtocolState,
    
+    pub connected_at: Instant,
+   
+    pub handshake_started_at: Instant,
+   
     pub last_packet_time: Instant,
    
     pub last_sent_time: Instant,
@@ -49,26 +62,93 @@ pub struct Session {
    
     pub name: String,
    
-    pub custom_skin: Option<String>,
+    pub custom_skin: Option<Vec<u8>>,
    
     pub skin: u8,
    
-    pub tx: mpsc::UnboundedSender<Vec<u8>>,
+    pub tx: mpsc::UnboundedSender<Frame>,
    
     pub sector_tracker: SectorTracker,
    
+    pub decimated_snakes: HashSet<SnakeId>,
+   
     pub is_modern_protocol: bool,
+   
+    pub victory_message: Option<String>,
+   
+    pub spectator: bool,
+   
+    pub input_buffer: InputBuffer,
+   
+    outbox: Vec<u8>,
+   
+    last_parse_error_log: Option<Instant>,
+    suppressed_parse_errors: u32,
+}
+
+
+#[derive(Debug, Default)]
+pub struct InputBuffer {
+   
+    clock: Option<(u64, u64)>,
+   
+    pending: VecDeque<(u64, IncomingPacket)>,
+}
+
+impl InputBuffer {
+   
+    pub fn push(&mut self, packet: IncomingPacket, etm: u16, now_tick: u64, frame_time_ms: u64, max_ticks: u64) -> u64 {
+        let (base_tick, client_ms) = self.clock.get_or_insert((now_tick, 0));
+        *client_ms += etm as u64;
+        let intended = *base_tick + *client_ms / frame_time_ms.max(1);
+
+        let due = if intended < now_tick {
+            *base_tick = now_tick;
+            *client_ms = 0;
+            now_tick
+        } else {
+            intended.min(now_tick + max_ticks)
+        };
+
+        let at = self.pending.partition_point(|(t, _)| *t <= due);
+        self.pending.insert(at, (due, packet));
+        due
+    }
+
+   
+    pub fn drain_due(&mut self, tick: u64) -> Vec<IncomingPacket> {
+        let count = self.pending.partition_point(|(t, _)| *t <= tick);
+        self.pending.drain(..count).map(|(_, packet)| packet).collect()
+    }
+
+   
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+   
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+   
+    pub fn clear(&mut self) {
+        self.clock = None;
+        self.pending.clear();
+    }
 }
 
 impl Session {
    
-    pub fn new(id: SessionId, addr: SocketAddr, tx: mpsc::UnboundedSender<Vec<u8>>) -> Self {
+    pub fn new(id: SessionId, addr: SocketAddr, tx: mpsc::UnboundedSender<Frame>) -> Self {
         Self {
             id,
             addr,
             snake_id: None,
             state: SessionState::Connected,
             protocol: ProtocolState::new(),
+            connected_at: Instant::now(),
+            handshake_started_at: Instant::now(),
             last_packet_time: Instant::now(),
             last_sent_time: Instant::now(),
             death_time: None,
@@ -77,13 +157,64 @@ impl Session {
             skin: 0,
             tx,
             sector_tracker: SectorTracker::new(),
+            decimated_snakes: HashSet::new(),
             is_modern_protocol: false,
+            victory_message: None,
+            spectator: false,
+            input_buffer: InputBuffer::default(),
+            outbox: Vec::new(),
+            last_parse_error_log: None,
+            suppressed_parse_errors: 0,
+        }
+    }
+
+   
+    pub fn take_parse_error_log(&mut self) -> Option<u32> {
+        let now = Instant::now();
+        let interval = Duration::from_millis(timing::PARSE_ERROR_LOG_INTERVAL_MS);
+        if self.last_parse_error_log.is_some_and(|last| now.duration_since(last) < interval) {
+            self.suppressed_parse_errors += 1;
+            return None;
         }
+        self.last_parse_error_log = Some(now);
+        Some(std::mem::take(&mut self.suppressed_parse_errors))
     }
 
    
-    pub fn send(&self, data: Vec<u8>) -> bool {
-        self.tx.send(data).is_ok()
+    pub fn send(&mut self, data: impl Into<Frame>) -> bool {
+        if self.tx.send(data.into()).is_ok() {
+            return true;
+        }
+        self.state = SessionState::Disconnected;
+        false
+    }
+
+   
+    pub fn is_disconnected(&self) -> bool {
+        self.state == SessionState::Disconnected
+    }
+
+   
+    pub fn queue(&mut self, packet: &[u8]) -> bool {
+        append_stacked_packet(&mut self.outbox, packet)
+    }
+
+   
+    pub fn flush(&mut self) -> bool {
+        if self.outbox.is_empty() {
+            return true;
+        }
+        let packets = std::mem::take(&mut self.outbox);
+        let frame = if self.protocol.want_etm {
+            let mut framed = Vec::with_capacity(2 + packets.len());
+            framed.extend_from_slice(&self.time_since_last_sent().to_be_bytes());
+            framed.extend_from_slice(&packets);
+            framed
+        } else {
+            packets
+        };
+        self.update_last_sent();
+        self.send(frame)
     }
 
    
@@ -107,9 +238,16 @@ impl Session {
     }
 
    
+    pub fn handshake_expired(&self, timeout_ms: u64) -> bool {
+        matches!(self.state, SessionState::Connected | SessionState::Handshake)
+            && self.handshake_started_at.elapsed().as_millis() as u64 > timeout_ms
+    }
+
+   
     pub fn mark_dead(&mut self) {
         self.state = SessionState::Dead;
         self.death_time = Some(Instant::now());
+        self.input_buffer.clear();
     }
 
    
@@ -136,6 +274,12 @@ pub struct SessionManager {
     snake_to_session: DashMap<SnakeId, SessionId>,
    
     next_id: AtomicU64,
+   
+    packet_stats: PacketStats,
+   
+    spectator_gate: Mutex<()>,
+   
+    player_slots: Mutex<usize>,
 }
 
 impl SessionManager {
@@ -145,14 +289,22 @@ impl SessionManager {
             sessions: DashMap::new(),
             snake_to_session: DashMap::new(),
             next_id: AtomicU64::new(1),
+            packet_stats: PacketStats::new(),
+            spectator_gate: Mutex::new(()),
+            player_slots: Mutex::new(0),
         }
     }
 
    
+    pub fn packet_stats(&self) -> &PacketStats {
+        &self.packet_stats
+    }
+
+   
     pub fn create_session(
         &self,
         addr: SocketAddr,
-        tx: mpsc::UnboundedSender<Vec<u8>>,
+        tx: mpsc::UnboundedSender<Frame>,
     ) -> SessionId {
         let id = self.next_id.fetch_add(1, Ordering::SeqCst);
         let session = Session::new(id, addr, tx);
@@ -238,19 +390,116 @@ impl SessionManager {
     }
 
    
+    pub fn reserve_player_slot(&self, limit: usize) -> bool {
+        let mut pending = self.player_slots.lock();
+        if self.playing_count() + *pending >= limit {
+            return false;
+        }
+        *pending += 1;
+        true
+    }
+
+   
+    pub fn release_player_slot(&self) {
+        let mut pending = self.player_slots.lock();
+        *pending = pending.saturating_sub(1);
+    }
+
+   
+    pub fn claim_player_slot(&self, session_id: SessionId, snake_id: SnakeId) {
+        let mut pending = self.player_slots.lock();
+        self.set_snake(session_id, snake_id);
+        *pending = pending.saturating_sub(1);
+    }
+
+   
+    pub fn spectator_count(&self) -> usize {
+        self.sessions.iter().filter(|r| r.spectator).count()
+    }
+
+   
+    pub fn start_spectating(&self, id: SessionId, limit: usize) -> bool {
+        let _gate = self.spectator_gate.lock();
+        if self.get(id).is_some_and(|s| s.spectator) {
+            return true;
+        }
+        if self.spectator_count() >= limit {
+            return false;
+        }
+        match self.sessions.get_mut(&id) {
+            Some(mut session) => {
+                session.spectator = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+   
+    pub fn spectator_ids(&self) -> Vec<SessionId> {
+        self.sessions
+            .iter()
+            .filter(|r| r.spectator)
+            .map(|r| *r.key())
+            .collect()
+    }
+
+   
+    pub fn idle_spectator_ids(&self, timeout_ms: u64) -> Vec<SessionId> {
+        self.sessions
+            .iter()
+            .filter(|r| r.spectator && r.idle_time_ms() > timeout_ms)
+            .map(|r| *r.key())
+            .collect()
+    }
+
+   
     pub fn broadcast(&self, data: &[u8]) {
-        for session in self.sessions.iter() {
-            let _ = session.send(data.to_vec());
+        let frame = Frame::copy_from_slice(data);
+        for mut session in self.sessions.iter_mut() {
+            session.send(frame.clone());
         }
     }
 
    
     pub fn broadcast_playing(&self, data: &[u8]) {
-        for session in self.sessions.iter() {
+        let frame = Frame::copy_from_slice(data);
+        for mut session in self.sessions.iter_mut() {
             if session.is_playing() {
-                let _ = session.send(data.to_vec());
+                session.send(frame.clone());
+            }
+        }
+    }
+
+   
+    pub fn drain_due_inputs(&self, tick: u64) -> Vec<(SessionId, IncomingPacket)> {
+        let mut due = Vec::new();
+        for mut entry in self.sessions.iter_mut() {
+            if entry.input_buffer.is_empty() {
+                continue;
             }
+            let id = *entry.key();
+            due.extend(entry.input_buffer.drain_due(tick).into_iter().map(|p| (id, p)));
         }
+        due
+    }
+
+   
+    pub fn handshake_expired_ids(&self, timeout_ms: u64) -> Vec<SessionId> {
+        self.sessions
+            .iter()
+            .filter(|r| r.handshake_expired(timeout_ms))
+            .map(|r| *r.key())
+            .collect()
+    }
+
+   
+    pub fn disconnected_ids(&self) -> Vec<SessionId> {
+        self.sessions
+            .iter()
+            .filter(|r| r.is_disconnected())
+            .map(|r| *r.key())
+            .collect()
     }
 
    
@@ -315,6 +564,41 @@ mod tests {
         assert!(session.is_playing());
     }
 
+    #[tokio::test]
+    async fn test_spectator_limit() {
+        let manager = SessionManager::new();
+        let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+        let ids: Vec<_> = (0..3)
+            .map(|_| manager.create_session(addr, mpsc::unbounded_channel().0))
+            .collect();
+
+        assert!(manager.start_spectating(ids[0], 2));
+        assert!(manager.start_spectating(ids[1], 2));
+        assert!(manager.start_spectating(ids[1], 2));
+        assert!(!manager.start_spectating(ids[2], 2));
+        assert_eq!(manager.spectator_count(), 2);
+        assert!(!manager.get(ids[2]).unwrap().spectator);
+    }
+
+    #[test]
+    fn test_concurrent_spectators_respect_the_limit() {
+        let manager = SessionManager::new();
+        let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+        let ids: Vec<_> = (0..64)
+            .map(|_| manager.create_session(addr, mpsc::unbounded_channel().0))
+            .collect();
+
+        let admitted = std::thread::scope(|scope| {
+            let workers: Vec<_> = ids
+                .chunks(8)
+                .map(|chunk| scope.spawn(|| chunk.iter().filter(|&&id| manager.start_spectating(id, 5)).count()))
+                .collect();
+            workers.into_iter().map(|w| w.join().unwrap()).sum::<usize>()
+        });
+        assert_eq!(admitted, 5);
+        assert_eq!(manager.spectator_count(), 5);
+    }
+
     #[tokio::test]
     async fn test_session_removal() {
         let manager = SessionManager::new();
@@ -325,4 +609,102 @@ mod tests {
         assert!(manager.remove(id).is_some());
         assert!(manager.get(id).is_none());
     }
+
+    #[tokio::test]
+    async fn test_queued_packets_flush_as_one_message() {
+        let manager = SessionManager::new();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+
+        let id = manager.create_session(addr, tx);
+        {
+            let mut session = manager.get_mut(id).unwrap();
+            assert!(session.queue(b"g\x00\x01"));
+            assert!(session.queue(b"p"));
+            assert!(session.flush());
+            assert!(session.flush());
+        }
+
+        let frame = rx.recv().await.unwrap();
+        let packets = crate::protocol::reader::parse_stacked_packets(&frame, 0);
+        assert_eq!(packets, vec![&b"g\x00\x01"[..], &b"p"[..]]);
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_etm_prefixes_the_frame_once() {
+        let manager = SessionManager::new();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+
+        let id = manager.create_session(addr, tx);
+        {
+            let mut session = manager.get_mut(id).unwrap();
+            session.protocol.want_etm = true;
+            session.last_sent_time = Instant::now() - std::time::Duration::from_millis(40);
+            assert!(session.queue(b"g\x00\x01"));
+            assert!(session.queue(b"p"));
+            assert!(session.flush());
+        }
+
+        let frame = rx.recv().await.unwrap();
+        let etm = u16::from_be_bytes([frame[0], frame[1]]);
+        assert!(etm >= 40);
+        assert_eq!(frame.len(), 2 + 1 + 3 + 1 + 1);
+        let packets = crate::protocol::reader::parse_stacked_packets(&frame, 2);
+        assert_eq!(packets, vec![&b"g\x00\x01"[..], &b"p"[..]]);
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_shares_one_payload() {
+        let manager = SessionManager::new();
+        let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+        let mut receivers: Vec<_> = (0..100)
+            .map(|_| {
+                let (tx, rx) = mpsc::unbounded_channel();
+                manager.create_session(addr, tx);
+                rx
+            })
+            .collect();
+
+        manager.broadcast(b"announcement");
+
+        let frames: Vec<Frame> = receivers.iter_mut().map(|rx| rx.try_recv().unwrap()).collect();
+        assert!(frames.iter().all(|f| f.as_ptr() == frames[0].as_ptr()));
+        assert_eq!(&frames[0][..], b"announcement");
+    }
+
+    #[tokio::test]
+    async fn test_unicast_frame_reuses_its_buffer() {
+        let manager = SessionManager::new();
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let id = manager.create_session("127.0.0.1:8080".parse().unwrap(), tx);
+
+        let mut data = Vec::with_capacity(64);
+        data.extend_from_slice(b"\x00\x00p");
+        let ptr = data.as_ptr();
+        assert!(manager.get_mut(id).unwrap().send(data));
+
+        let frame = rx.try_recv().unwrap();
+        assert_eq!(frame.as_ptr(), ptr);
+        let message = Vec::from(frame);
+        assert_eq!(message.as_ptr(), ptr);
+        assert_eq!(message, b"\x00\x00p");
+    }
+
+    #[tokio::test]
+    async fn test_failed_send_marks_session_disconnected() {
+        let manager = SessionManager::new();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let addr: SocketAddr = "127.0.0.1:8080".parse().unwrap();
+
+        let id = manager.create_session(addr, tx);
+        manager.set_snake(id, 1);
+        drop(rx);
+
+        manager.broadcast_playing(b"p");
+        assert!(manager.get(id).unwrap().is_disconnected());
+        assert_eq!(manager.disconnected_ids(), vec![id]);
+        assert_eq!(manager.playing_count(), 0);
+    }
 }
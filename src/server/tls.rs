@@ -0,0 +1,78 @@
+use crate::config::TlsConfig;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+use tokio_rustls::rustls::crypto::ring;
+use tokio_rustls::rustls::pki_types::pem::{self, PemObject};
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
+
+
+#[derive(Debug, Error)]
+pub enum TlsSetupError {
+    #[error("{path}: {source}")]
+    Pem { path: PathBuf, source: pem::Error },
+    #[error("{0} contains no certificates")]
+    NoCertificates(PathBuf),
+    #[error("invalid TLS configuration: {0}")]
+    Rustls(#[from] tokio_rustls::rustls::Error),
+}
+
+fn pem_error(path: &Path) -> impl FnOnce(pem::Error) -> TlsSetupError + '_ {
+    move |source| TlsSetupError::Pem {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+
+pub fn load_acceptor(config: &TlsConfig) -> Result<TlsAcceptor, TlsSetupError> {
+    let certs = CertificateDer::pem_file_iter(&config.cert_path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(pem_error(&config.cert_path))?;
+    if certs.is_empty() {
+        return Err(TlsSetupError::NoCertificates(config.cert_path.clone()));
+    }
+    let key = PrivateKeyDer::from_pem_file(&config.key_path).map_err(pem_error(&config.key_path))?;
+
+    let server_config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)?;
+    Ok(TlsAcceptor::from(Arc::new(server_config)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_pair(name: &str, cert: &str, key: &str) -> TlsConfig {
+        let dir = std::env::temp_dir().join(format!("slither_tls_{}_{}", name, std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let config = TlsConfig {
+            cert_path: dir.join("cert.pem"),
+            key_path: dir.join("key.pem"),
+        };
+        std::fs::write(&config.cert_path, cert).unwrap();
+        std::fs::write(&config.key_path, key).unwrap();
+        config
+    }
+
+    #[test]
+    fn test_load_acceptor_from_pem_files() {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let config = write_pair("ok", &cert.cert.pem(), &cert.key_pair.serialize_pem());
+        assert!(load_acceptor(&config).is_ok());
+
+        let swapped = write_pair("swapped", &cert.key_pair.serialize_pem(), &cert.cert.pem());
+        assert!(matches!(load_acceptor(&swapped), Err(TlsSetupError::NoCertificates(_))));
+
+        let missing = TlsConfig {
+            cert_path: config.cert_path.with_file_name("missing.pem"),
+            key_path: config.key_path.clone(),
+        };
+        let err = load_acceptor(&missing).err().unwrap();
+        assert!(err.to_string().contains("missing.pem"), "{}", err);
+    }
+}
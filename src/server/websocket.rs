@@ -1,119 +1,474 @@
 
 
-use crate::config::GameConfig;
+use crate::config::{timing, GameConfig, ListenerConfig, TickCatchUp};
 use crate::game::world::{create_shared_world, SharedWorld};
+use crate::game::HallOfFame;
+use crate::server::bans::{client_ip, BanList};
 use crate::server::handler::GameHandler;
-use crate::server::session::{create_session_manager, SessionId, SharedSessionManager};
-use futures_util::{SinkExt, StreamExt};
-use std::net::SocketAddr;
+use crate::server::http::{try_serve_http, AdminContext, HttpContext, LoopHealth, SharedLoopHealth};
+use crate::server::session::{create_session_manager, Frame, SessionId, SharedSessionManager};
+use crate::server::tls::load_acceptor;
+use futures_util::{Sink, SinkExt, StreamExt};
+use parking_lot::Mutex;
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncWrite};
 use tokio::net::{TcpListener, TcpStream};
-use tokio::sync::{mpsc, RwLock};
-use tokio::time::interval;
-use tokio_tungstenite::{accept_async, tungstenite::Message};
+use tokio_rustls::TlsAcceptor;
+use tokio::sync::{mpsc, Notify};
+use tokio::task::{JoinHandle, JoinSet};
+use tokio::time::{interval, MissedTickBehavior};
+use tokio_tungstenite::tungstenite::handshake::server::{ErrorResponse, Request, Response};
+use tokio_tungstenite::tungstenite::http::StatusCode;
+use tokio_tungstenite::{accept_hdr_async, tungstenite::Message};
 use tracing::{error, info, warn};
 
 
-type SharedHandler = Arc<RwLock<GameHandler>>;
+type SharedHandler = Arc<GameHandler>;
 
 
 pub async fn run_server(port: u16, config: GameConfig) -> anyhow::Result<()> {
-    let addr = format!("0.0.0.0:{}", port);
-    let listener = TcpListener::bind(&addr).await?;
+    let world = create_shared_world(config.clone());
+    run_server_with(port, config, world, create_session_manager()).await
+}
 
-    info!("Slither.io server listening on {}", addr);
 
-   
+pub async fn run_server_with(
+    port: u16,
+    config: GameConfig,
+    world: SharedWorld,
+    sessions: SharedSessionManager,
+) -> anyhow::Result<()> {
+    let server = spawn_listeners_with(&[ListenerConfig::plain(port)], config, world, sessions).await?;
+    server.task.await?;
+    Ok(())
+}
+
+
+pub async fn run_server_on(listeners: &[ListenerConfig], config: GameConfig) -> anyhow::Result<()> {
+    let server = spawn_listeners(listeners, config).await?;
+    server.task.await?;
+    Ok(())
+}
+
+
+pub async fn spawn_server(port: u16, config: GameConfig) -> anyhow::Result<ServerHandle> {
+    spawn_listeners(&[ListenerConfig::plain(port)], config).await
+}
+
+
+pub async fn spawn_listeners(
+    listeners: &[ListenerConfig],
+    config: GameConfig,
+) -> anyhow::Result<ServerHandle> {
     let world = create_shared_world(config.clone());
-    let sessions = create_session_manager();
-    let handler = Arc::new(RwLock::new(GameHandler::new(
-        world.clone(),
-        sessions.clone(),
-        config.clone(),
-    )));
-
-   
-    let game_handler = handler.clone();
-    let frame_time = config.frame_time_ms;
-    tokio::spawn(async move {
-        game_loop(game_handler, frame_time).await;
+    spawn_listeners_with(listeners, config, world, create_session_manager()).await
+}
+
+
+pub async fn spawn_listeners_with(
+    listeners: &[ListenerConfig],
+    config: GameConfig,
+    world: SharedWorld,
+    sessions: SharedSessionManager,
+) -> anyhow::Result<ServerHandle> {
+    if listeners.is_empty() {
+        anyhow::bail!("at least one listener is required");
+    }
+
+    let mut bound = Vec::with_capacity(listeners.len());
+    let mut local_addrs = Vec::with_capacity(listeners.len());
+    for listener_config in listeners {
+        let tls = listener_config.tls.as_ref().map(load_acceptor).transpose()?;
+        let listener = TcpListener::bind(("0.0.0.0", listener_config.port)).await?;
+        let local_addr = listener.local_addr()?;
+        info!(tls = tls.is_some(), "Slither.io server listening on {}", local_addr);
+        local_addrs.push(local_addr);
+        bound.push((listener, tls));
+    }
+
+   
+    let handler = Arc::new(GameHandler::new(world.clone(), sessions.clone(), config.clone()));
+    let admin = config.admin_token.clone().map(|token| AdminContext {
+        token,
+        handler: handler.clone(),
+    });
+    let context = Arc::new(HttpContext {
+        health: Arc::new(LoopHealth::new()),
+        world,
+        sessions,
+        admin,
     });
+    let shutdown = Arc::new(Notify::new());
+
+    let task = tokio::spawn(serve(
+        bound,
+        handler,
+        context.clone(),
+        shutdown.clone(),
+        config.frame_time_ms,
+    ));
+
+    Ok(ServerHandle {
+        local_addrs,
+        context,
+        shutdown,
+        task,
+    })
+}
+
+
+pub struct ServerHandle {
+    local_addrs: Vec<SocketAddr>,
+    context: Arc<HttpContext>,
+    shutdown: Arc<Notify>,
+    task: JoinHandle<()>,
+}
+
+impl ServerHandle {
+   
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addrs[0]
+    }
+
+   
+    pub fn local_addrs(&self) -> &[SocketAddr] {
+        &self.local_addrs
+    }
 
    
-    while let Ok((stream, addr)) = listener.accept().await {
-        let handler = handler.clone();
-        let sessions = sessions.clone();
+    pub fn stats(&self) -> ServerStats {
+        let ctx = &self.context;
+        ServerStats::gather(&ctx.world, &ctx.sessions, &ctx.health)
+    }
+
+   
+    pub async fn shutdown(self) {
+        self.shutdown.notify_one();
+        if let Err(e) = self.task.await {
+            error!("Server task failed during shutdown: {}", e);
+        }
+    }
+}
+
+
+async fn serve(
+    listeners: Vec<(TcpListener, Option<TlsAcceptor>)>,
+    handler: SharedHandler,
+    context: Arc<HttpContext>,
+    shutdown: Arc<Notify>,
+    frame_time_ms: u64,
+) {
+    let game_loop = tokio::spawn(game_loop(handler.clone(), context.health.clone(), frame_time_ms));
+    let mut connections = JoinSet::new();
+
+   
+    let (accepted_tx, mut accepted_rx) = mpsc::channel(listeners.len() * 16);
+    let mut acceptors = JoinSet::new();
+    for (listener, tls) in listeners {
+        acceptors.spawn(accept_loop(listener, tls, accepted_tx.clone()));
+    }
+    drop(accepted_tx);
 
-        tokio::spawn(async move {
-            if let Err(e) = handle_connection(stream, addr, handler, sessions).await {
-                error!("Connection error from {}: {}", addr, e);
+    loop {
+        tokio::select! {
+            accepted = accepted_rx.recv() => {
+                let Some((stream, addr, tls)) = accepted else { break };
+                let handler = handler.clone();
+                let context = context.clone();
+
+                connections.spawn(async move {
+                    if let Err(e) = handle_connection(stream, addr, tls, handler, context).await {
+                        error!(%addr, error = %e, "Connection error");
+                    }
+                });
             }
-        });
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+            _ = shutdown.notified() => break,
+        }
     }
 
-    Ok(())
+    info!("Shutting down server");
+    acceptors.shutdown().await;
+    game_loop.abort();
+    connections.shutdown().await;
+
+   
+    for session_id in context.sessions.session_ids() {
+        handler.on_disconnect(session_id);
+    }
+    if let Some(save) = flush_hall_of_fame(&handler) {
+        let _ = save.await;
+    }
+}
+
+
+async fn accept_loop(
+    listener: TcpListener,
+    tls: Option<TlsAcceptor>,
+    accepted: mpsc::Sender<(TcpStream, SocketAddr, Option<TlsAcceptor>)>,
+) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, addr)) => {
+                if accepted.send((stream, addr, tls.clone())).await.is_err() {
+                    return;
+                }
+            }
+            Err(e) => {
+                error!("Accept failed on {:?}: {}", listener.local_addr(), e);
+                return;
+            }
+        }
+    }
+}
+
+
+fn timed_tick(handler: &GameHandler, health: &LoopHealth, frame_time: Duration) {
+    let started = Instant::now();
+    handler.tick(frame_time.as_millis() as u64);
+    let took = started.elapsed();
+    health.record_tick(took);
+    if took > frame_time {
+        warn!("Slow tick: {:?} (frame time {:?})", took, frame_time);
+    }
 }
 
 
-async fn game_loop(handler: SharedHandler, frame_time_ms: u64) {
-    let mut ticker = interval(Duration::from_millis(frame_time_ms));
+async fn game_loop(handler: SharedHandler, health: SharedLoopHealth, frame_time_ms: u64) {
+    let frame_time = Duration::from_millis(frame_time_ms);
+    let catch_up = handler.config().tick_catch_up;
+    let mut ticker = interval(frame_time);
+    ticker.set_missed_tick_behavior(missed_tick_behavior(catch_up));
+    let mut pacer = TickPacer::new(frame_time, catch_up);
+    let mut last_wake = Instant::now();
+    let mut hall_of_fame_save: Option<JoinHandle<()>> = None;
 
     loop {
         ticker.tick().await;
 
-        let mut handler = handler.write().await;
-        handler.tick(frame_time_ms);
+        let now = Instant::now();
+        let plan = pacer.advance(now - last_wake);
+        last_wake = now;
+
+        if plan.dropped > 0 {
+            warn!("Game loop fell behind, dropping {} ticks", plan.dropped);
+            health.add_dropped_ticks(plan.dropped);
+        }
+
+        for _ in 0..plan.run {
+            timed_tick(&handler, &health, frame_time);
+        }
+        health.mark_tick();
+
+        if hall_of_fame_save.as_ref().is_none_or(|save| save.is_finished()) {
+            hall_of_fame_save = flush_hall_of_fame(&handler);
+        }
+    }
+}
+
+
+fn flush_hall_of_fame(handler: &GameHandler) -> Option<JoinHandle<()>> {
+    let path = handler.config().leaderboard_path.clone()?;
+    let hall = handler.take_hall_of_fame_changes()?;
+    Some(tokio::task::spawn_blocking(move || save_hall_of_fame(&path, &hall)))
+}
+
+
+fn save_hall_of_fame(path: &Path, hall: &HallOfFame) {
+    static SAVING: Mutex<()> = Mutex::new(());
+    let _saving = SAVING.lock();
+    if let Err(e) = hall.save(path) {
+        warn!("Could not save leaderboard to {}: {}", path.display(), e);
+    }
+}
+
+
+fn missed_tick_behavior(catch_up: TickCatchUp) -> MissedTickBehavior {
+    match catch_up {
+        TickCatchUp::Burst => MissedTickBehavior::Burst,
+        TickCatchUp::Delay => MissedTickBehavior::Delay,
+        TickCatchUp::Skip => MissedTickBehavior::Skip,
+    }
+}
+
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TickPlan {
+    run: u32,
+    dropped: u64,
+}
+
+
+#[derive(Debug)]
+struct TickPacer {
+    frame_time: Duration,
+    max_run: u32,
+    debt: Duration,
+}
+
+impl TickPacer {
+    fn new(frame_time: Duration, catch_up: TickCatchUp) -> Self {
+        let max_run = match catch_up {
+            TickCatchUp::Burst => timing::MAX_CATCH_UP_TICKS,
+            TickCatchUp::Delay | TickCatchUp::Skip => 1,
+        };
+        Self {
+            frame_time,
+            max_run,
+            debt: Duration::ZERO,
+        }
+    }
+
+   
+    fn advance(&mut self, elapsed: Duration) -> TickPlan {
+        self.debt += elapsed;
+        let frame_nanos = self.frame_time.as_nanos().max(1);
+        let due = (self.debt.as_nanos() / frame_nanos) as u64;
+        let run = due.min(self.max_run as u64);
+
+        self.debt -= self.frame_time * due as u32;
+        TickPlan {
+            run: run as u32,
+            dropped: due - run,
+        }
+    }
+}
+
+
+pub fn origin_allowed(allowed_origins: &[String], origin: Option<&str>) -> bool {
+    if allowed_origins.is_empty() {
+        return true;
+    }
+    origin.is_some_and(|origin| allowed_origins.iter().any(|allowed| allowed.eq_ignore_ascii_case(origin)))
+}
+
+
+#[allow(clippy::result_large_err)]
+pub fn origin_check(
+    allowed_origins: &[String],
+) -> impl FnOnce(&Request, Response) -> Result<Response, ErrorResponse> + '_ {
+    move |request, response| {
+        let origin = request.headers().get("origin").and_then(|v| v.to_str().ok());
+        if origin_allowed(allowed_origins, origin) {
+            return Ok(response);
+        }
+        warn!("Rejecting WebSocket handshake from origin {:?}", origin);
+        let mut rejection = ErrorResponse::new(Some("origin not allowed".to_string()));
+        *rejection.status_mut() = StatusCode::FORBIDDEN;
+        Err(rejection)
+    }
+}
+
+
+
+#[allow(clippy::result_large_err)]
+pub fn handshake_check<'a>(
+    config: &'a GameConfig,
+    bans: &'a BanList,
+    peer: IpAddr,
+) -> impl FnOnce(&Request, Response) -> Result<Response, ErrorResponse> + 'a {
+    move |request, response| {
+        let forwarded = config
+            .real_ip_header
+            .as_deref()
+            .and_then(|name| request.headers().get(name))
+            .and_then(|v| v.to_str().ok());
+        let ip = client_ip(peer, forwarded, &config.trusted_proxies);
+        if bans.is_banned(ip) {
+            warn!(%ip, "Rejecting WebSocket handshake from banned IP");
+            let mut rejection = ErrorResponse::new(Some("banned".to_string()));
+            *rejection.status_mut() = StatusCode::FORBIDDEN;
+            return Err(rejection);
+        }
+        origin_check(&config.allowed_origins)(request, response)
     }
 }
 
 
 async fn handle_connection(
-    stream: TcpStream,
+    mut stream: TcpStream,
     addr: SocketAddr,
+    tls: Option<TlsAcceptor>,
     handler: SharedHandler,
-    sessions: SharedSessionManager,
+    context: Arc<HttpContext>,
 ) -> anyhow::Result<()> {
-    info!("New connection from {}", addr);
+    let Some(acceptor) = tls else {
+       
+        if try_serve_http(&mut stream, &context).await? {
+            return Ok(());
+        }
+        return serve_websocket(stream, addr, handler, context).await;
+    };
+
+    let handshake_timeout = Duration::from_millis(timing::REQUEST_HEAD_TIMEOUT_MS);
+    let stream = tokio::time::timeout(handshake_timeout, acceptor.accept(stream)).await??;
+    serve_websocket(stream, addr, handler, context).await
+}
+
+
+async fn serve_websocket<S>(
+    stream: S,
+    addr: SocketAddr,
+    handler: SharedHandler,
+    context: Arc<HttpContext>,
+) -> anyhow::Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let sessions = &context.sessions;
+
+    info!(%addr, "New connection");
 
    
-    let ws_stream = accept_async(stream).await?;
-    let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+    let ws_stream = accept_hdr_async(stream, handshake_check(handler.config(), handler.bans(), addr.ip())).await?;
+    let (ws_sender, mut ws_receiver) = ws_stream.split();
 
    
-    let (tx, mut rx) = mpsc::unbounded_channel::<Vec<u8>>();
+    let (tx, rx) = mpsc::unbounded_channel::<Frame>();
 
    
     let session_id = sessions.create_session(addr, tx);
 
    
-    {
-        let handler = handler.read().await;
-        handler.on_connect(session_id);
-    }
+    handler.on_connect(session_id);
 
    
-    let send_task = tokio::spawn(async move {
-        while let Some(data) = rx.recv().await {
-            if ws_sender.send(Message::Binary(data)).await.is_err() {
-                break;
-            }
-        }
-    });
+    let write_timeout = Duration::from_millis(handler.config().write_timeout_ms);
+    let mut send_task = tokio::spawn(pump_frames(ws_sender, rx, write_timeout));
 
    
-    while let Some(result) = ws_receiver.next().await {
+    loop {
+        let result = tokio::select! {
+            result = ws_receiver.next() => match result {
+                Some(result) => result,
+                None => break,
+            },
+            outcome = &mut send_task => {
+                if let Ok(WriteOutcome::TimedOut) = outcome {
+                    let timeout_ms = write_timeout.as_millis() as u64;
+                    warn!(%addr, session_id, timeout_ms, "Write timed out, closing connection");
+                }
+                break;
+            }
+        };
         match result {
             Ok(msg) => {
                 match msg {
                     Message::Binary(data) => {
-                        let handler = handler.read().await;
-                        handler.on_packet(session_id, &data);
+                        if let Err(e) = handler.on_packet(session_id, &data) {
+                            warn!(%addr, session_id, error = %e, "Closing connection");
+                            break;
+                        }
                     }
                     Message::Text(text) => {
                        
-                        let handler = handler.read().await;
-                        handler.on_packet(session_id, text.as_bytes());
+                        if let Err(e) = handler.on_text(session_id, &text) {
+                            warn!(%addr, session_id, error = %e, "Closing connection");
+                            break;
+                        }
                     }
                     Message::Ping(data) => {
                        
@@ -130,25 +485,57 @@ async fn handle_connection(
                 }
             }
             Err(e) => {
-                warn!("WebSocket error from {}: {}", addr, e);
+                warn!(%addr, session_id, error = %e, "WebSocket error");
                 break;
             }
         }
     }
 
    
-    info!("Connection closed from {}", addr);
+    info!(%addr, session_id, "Connection closed");
     send_task.abort();
 
-    {
-        let handler = handler.read().await;
-        handler.on_disconnect(session_id);
-    }
+    handler.on_disconnect(session_id);
 
     Ok(())
 }
 
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WriteOutcome {
+    Closed,
+    Failed,
+    TimedOut,
+}
+
+
+async fn pump_frames<S>(
+    mut sink: S,
+    mut rx: mpsc::UnboundedReceiver<Frame>,
+    write_timeout: Duration,
+) -> WriteOutcome
+where
+    S: Sink<Message> + Unpin,
+{
+    while let Some(data) = rx.recv().await {
+        let send = sink.send(Message::Binary(Vec::from(data)));
+        let sent = if write_timeout.is_zero() {
+            Ok(send.await)
+        } else {
+            tokio::time::timeout(write_timeout, send).await
+        };
+        match sent {
+            Ok(Ok(())) => {}
+            Ok(Err(_)) => return WriteOutcome::Failed,
+            Err(_) => return WriteOutcome::TimedOut,
+        }
+    }
+   
+    let _ = sink.close().await;
+    WriteOutcome::Closed
+}
+
+
 #[derive(Debug, Clone, Default)]
 pub struct ServerStats {
     pub connections: usize,
@@ -156,11 +543,19 @@ pub struct ServerStats {
     pub snakes: usize,
     pub food: usize,
     pub tick_count: u64,
+    pub dropped_ticks: u64,
+    pub uptime_ms: u64,
+    pub avg_tick_us: u64,
+    pub p99_tick_us: u64,
+    pub bots: usize,
+    pub humans: usize,
 }
 
 impl ServerStats {
-    pub fn gather(world: &SharedWorld, sessions: &SharedSessionManager) -> Self {
+    pub fn gather(world: &SharedWorld, sessions: &SharedSessionManager, health: &LoopHealth) -> Self {
         let world = world.read();
+        let bots = world.snakes().values().filter(|snake| snake.is_bot).count();
+        let timings = health.tick_timings();
 
         Self {
             connections: sessions.active_count(),
@@ -168,8 +563,29 @@ impl ServerStats {
             snakes: world.snake_count(),
             food: world.sectors.total_food(),
             tick_count: world.tick_count,
+            dropped_ticks: health.dropped_ticks(),
+            uptime_ms: health.uptime().as_millis() as u64,
+            avg_tick_us: timings.average().as_micros() as u64,
+            p99_tick_us: timings.percentile(0.99).as_micros() as u64,
+            bots,
+            humans: world.snake_count() - bots,
         }
     }
+
+   
+    pub fn render_prometheus(&self) -> String {
+        let gauges = [
+            ("slither_uptime_ms", self.uptime_ms),
+            ("slither_tick_duration_avg_us", self.avg_tick_us),
+            ("slither_tick_duration_p99_us", self.p99_tick_us),
+            ("slither_bots", self.bots as u64),
+            ("slither_humans", self.humans as u64),
+        ];
+        gauges
+            .iter()
+            .map(|(name, value)| format!("# TYPE {} gauge\n{} {}\n", name, name, value))
+            .collect()
+    }
 }
 
 #[cfg(test)]
@@ -183,7 +599,149 @@ mod tests {
         let world = create_shared_world(config.clone());
         let sessions = create_session_manager();
 
-        let stats = ServerStats::gather(&world, &sessions);
+        let stats = ServerStats::gather(&world, &sessions, &LoopHealth::new());
         assert_eq!(stats.players, 0);
+        assert_eq!(stats.dropped_ticks, 0);
+    }
+
+    async fn handshake_from(allowed_origins: &[&str], origin: &str) -> bool {
+        use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+        let allowed: Vec<String> = allowed_origins.iter().map(|o| o.to_string()).collect();
+        let (client, server) = tokio::io::duplex(4096);
+        let accepted = tokio::spawn(async move { accept_hdr_async(server, origin_check(&allowed)).await.is_ok() });
+
+        let mut request = "ws://localhost/".into_client_request().unwrap();
+        request.headers_mut().insert("origin", origin.parse().unwrap());
+        let connected = tokio_tungstenite::client_async(request, client).await.is_ok();
+        accepted.await.unwrap() && connected
+    }
+
+    #[tokio::test]
+    async fn test_origin_allowlist_gates_the_handshake() {
+        let allowed = ["https://slither.example"];
+        assert!(handshake_from(&allowed, "https://slither.example").await);
+        assert!(handshake_from(&allowed, "HTTPS://SLITHER.EXAMPLE").await);
+        assert!(!handshake_from(&allowed, "https://evil.example").await);
+        assert!(handshake_from(&[], "https://evil.example").await);
+        assert!(!origin_allowed(&["https://slither.example".to_string()], None));
+    }
+
+    #[cfg(feature = "bots")]
+    #[test]
+    fn test_stats_report_tick_timing_uptime_and_population() {
+        let config = GameConfig::default();
+        let world = create_shared_world(config.clone());
+        let sessions = create_session_manager();
+        let handler = GameHandler::new(world.clone(), sessions.clone(), config);
+        let health = LoopHealth::new();
+
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let session_id = sessions.create_session("127.0.0.1:9000".parse().unwrap(), tx);
+        handler.on_packet(session_id, &crate::protocol::client::identity_frame(0, "Human")).unwrap();
+        world.write().spawn_bot().unwrap();
+
+        for _ in 0..20 {
+            timed_tick(&handler, &health, Duration::from_millis(8));
+        }
+        std::thread::sleep(Duration::from_millis(2));
+
+        let stats = ServerStats::gather(&world, &sessions, &health);
+        assert!(stats.uptime_ms > 0);
+        assert!(stats.avg_tick_us > 0 && stats.avg_tick_us < 1_000_000, "avg {}us", stats.avg_tick_us);
+        assert!(stats.p99_tick_us >= stats.avg_tick_us);
+        assert_eq!((stats.bots, stats.humans), (1, 1));
+        assert!(stats.render_prometheus().contains("slither_tick_duration_p99_us "));
+    }
+
+    #[test]
+    fn test_slow_tick_drops_ticks_instead_of_lagging() {
+        let frame = Duration::from_millis(8);
+        let mut pacer = TickPacer::new(frame, TickCatchUp::Burst);
+        let health = LoopHealth::new();
+
+        assert_eq!(pacer.advance(frame), TickPlan { run: 1, dropped: 0 });
+
+        let plan = pacer.advance(Duration::from_millis(500));
+        assert_eq!(plan.run, timing::MAX_CATCH_UP_TICKS);
+        assert_eq!(plan.dropped, 62 - timing::MAX_CATCH_UP_TICKS as u64);
+        health.add_dropped_ticks(plan.dropped);
+        assert_eq!(health.dropped_ticks(), plan.dropped);
+
+        assert!(pacer.debt < frame);
+        assert_eq!(pacer.advance(frame), TickPlan { run: 1, dropped: 0 });
+    }
+
+    #[test]
+    fn test_skip_catch_up_moves_snakes_one_frame_after_a_stall() {
+        let frame = Duration::from_millis(8);
+        let mut pacer = TickPacer::new(frame, TickCatchUp::Skip);
+        let mut world = crate::game::World::new(GameConfig::default());
+        let id = world.create_snake("stall".to_string(), 0).unwrap();
+        world.tick(8);
+
+        let before = world.get_snake(id).unwrap().head_pos();
+        let plan = pacer.advance(Duration::from_millis(500));
+        assert_eq!(plan, TickPlan { run: 1, dropped: 61 });
+        for _ in 0..plan.run {
+            world.tick(8);
+        }
+
+        let snake = world.get_snake(id).unwrap();
+        let (x, y) = snake.head_pos();
+        let moved = ((x - before.0).powi(2) + (y - before.1).powi(2)).sqrt();
+        assert!(moved <= snake.speed * 0.008 + 0.01, "snake jumped {} units", moved);
+        assert_eq!(missed_tick_behavior(TickCatchUp::Skip), MissedTickBehavior::Skip);
+    }
+
+    struct StalledSink;
+
+    impl Sink<Message> for StalledSink {
+        type Error = std::io::Error;
+
+        fn poll_ready(
+            self: std::pin::Pin<&mut Self>,
+            _: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            std::task::Poll::Pending
+        }
+
+        fn start_send(self: std::pin::Pin<&mut Self>, _: Message) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn poll_flush(
+            self: std::pin::Pin<&mut Self>,
+            _: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            std::task::Poll::Pending
+        }
+
+        fn poll_close(
+            self: std::pin::Pin<&mut Self>,
+            _: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<Result<(), Self::Error>> {
+            std::task::Poll::Pending
+        }
+    }
+
+    #[tokio::test]
+    async fn test_stalled_socket_is_torn_down_after_write_timeout() {
+        let sessions = create_session_manager();
+        let (tx, rx) = mpsc::unbounded_channel();
+        let session_id = sessions.create_session("127.0.0.1:9000".parse().unwrap(), tx);
+        let writer = tokio::spawn(pump_frames(StalledSink, rx, Duration::from_millis(50)));
+
+        assert!(sessions.get_mut(session_id).unwrap().send(vec![1, 2, 3]));
+        let outcome = tokio::time::timeout(Duration::from_secs(5), writer)
+            .await
+            .expect("writer hung on a stalled socket")
+            .unwrap();
+        assert_eq!(outcome, WriteOutcome::TimedOut);
+        assert!(!sessions.get_mut(session_id).unwrap().send(vec![4]));
+        assert_eq!(
+            sessions.get(session_id).unwrap().state,
+            crate::server::session::SessionState::Disconnected
+        );
     }
 }
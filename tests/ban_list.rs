@@ -0,0 +1,72 @@
+use rust_slither::server::spawn_server;
+use rust_slither::GameConfig;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+async fn connects_as(url: &str, forwarded_for: &str) -> bool {
+    let mut request = url.into_client_request().unwrap();
+    request
+        .headers_mut()
+        .insert("x-forwarded-for", forwarded_for.parse().unwrap());
+    connect_async(request).await.is_ok()
+}
+
+async fn admin_post(port: u16, target: &str, token: &str) -> String {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let request = format!("POST {target} HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer {token}\r\n\r\n");
+    stream.write_all(request.as_bytes()).await.unwrap();
+    let mut response = String::new();
+    tokio::time::timeout(Duration::from_secs(5), stream.read_to_string(&mut response))
+        .await
+        .unwrap()
+        .unwrap();
+    response
+}
+
+async fn eventually(url: &str, forwarded_for: &str, expected: bool) -> bool {
+    for _ in 0..50 {
+        if connects_as(url, forwarded_for).await == expected {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    false
+}
+
+#[tokio::test]
+async fn banned_ip_is_rejected_until_unbanned() {
+    let path = std::env::temp_dir().join(format!("slither_ban_list_{}.txt", std::process::id()));
+    let config = GameConfig {
+        admin_token: Some("s3cret".to_string()),
+        ban_file: Some(path.clone()),
+        real_ip_header: Some("x-forwarded-for".to_string()),
+        trusted_proxies: vec!["127.0.0.1".parse().unwrap()],
+        ..Default::default()
+    };
+    let server = spawn_server(0, config).await.unwrap();
+    let port = server.local_addr().port();
+    let url = format!("ws://127.0.0.1:{}", port);
+
+    assert!(connects_as(&url, "10.0.0.7").await);
+
+    let response = admin_post(port, "/admin/ban?ip=10.0.0.7", "wrong").await;
+    assert!(response.starts_with("HTTP/1.1 401"), "{}", response);
+    assert!(connects_as(&url, "10.0.0.7").await);
+
+    let response = admin_post(port, "/admin/ban?ip=10.0.0.7", "s3cret").await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    assert!(eventually(&url, "10.0.0.7", false).await, "banned IP was still accepted");
+    assert!(connects_as(&url, "10.0.0.8").await);
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "10.0.0.7\n");
+
+    let response = admin_post(port, "/admin/unban?ip=10.0.0.7", "s3cret").await;
+    assert!(response.starts_with("HTTP/1.1 200"), "{}", response);
+    assert!(eventually(&url, "10.0.0.7", true).await, "unbanned IP was still rejected");
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "");
+
+    std::fs::remove_file(&path).unwrap();
+    server.shutdown().await;
+}
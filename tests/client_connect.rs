@@ -0,0 +1,100 @@
+use futures_util::{SinkExt, StreamExt};
+use rust_slither::protocol::client::{
+    identity_frame, is_init_frame, is_pre_init_frame, start_login_frame,
+};
+use rust_slither::game::world::create_shared_world;
+use rust_slither::server::session::create_session_manager;
+use rust_slither::server::{run_server, run_server_with};
+use rust_slither::GameConfig;
+use std::time::Duration;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+
+fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+#[tokio::test]
+async fn simulated_client_receives_init() {
+    let port = free_port();
+    tokio::spawn(run_server(port, GameConfig::default()));
+
+    let url = format!("ws://127.0.0.1:{}", port);
+    let mut connection = None;
+    for _ in 0..50 {
+        if let Ok((ws, _)) = connect_async(&url).await {
+            connection = Some(ws);
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let mut ws = connection.expect("server did not accept connections");
+
+    ws.send(Message::Binary(start_login_frame())).await.unwrap();
+
+    let got_init = tokio::time::timeout(Duration::from_secs(5), async {
+        while let Some(Ok(msg)) = ws.next().await {
+            let Message::Binary(data) = msg else { continue };
+            if is_pre_init_frame(&data, true) {
+                ws.send(Message::Binary(identity_frame(0, "tester"))).await.unwrap();
+            } else if is_init_frame(&data, true) {
+                return true;
+            }
+        }
+        false
+    })
+    .await
+    .unwrap_or(false);
+
+    assert!(got_init, "client never received an Init packet");
+}
+
+#[tokio::test]
+async fn preseeded_world_snake_is_visible_to_first_client() {
+    let config = GameConfig {
+        game_radius: 1500,
+        death_radius: 1400,
+        sector_size: 300,
+        sector_count_along_edge: 10,
+        ..Default::default()
+    };
+    let world = create_shared_world(config.clone());
+    let guide = world.write().create_snake("Guide".to_string(), 3).unwrap();
+    let sessions = create_session_manager();
+
+    let port = free_port();
+    tokio::spawn(run_server_with(port, config, world, sessions.clone()));
+
+    let url = format!("ws://127.0.0.1:{}", port);
+    let mut connection = None;
+    for _ in 0..50 {
+        if let Ok((ws, _)) = connect_async(&url).await {
+            connection = Some(ws);
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    let mut ws = connection.expect("server did not accept connections");
+
+    ws.send(Message::Binary(start_login_frame())).await.unwrap();
+
+    let saw_guide = tokio::time::timeout(Duration::from_secs(5), async {
+        while let Some(Ok(msg)) = ws.next().await {
+            let Message::Binary(data) = msg else { continue };
+            if is_pre_init_frame(&data, true) {
+                ws.send(Message::Binary(identity_frame(0, "tester"))).await.unwrap();
+            } else if data.len() > 6 && data[2] == b's' && data[3..5] == guide.to_be_bytes() {
+                return true;
+            }
+        }
+        false
+    })
+    .await
+    .unwrap_or(false);
+
+    assert!(saw_guide, "pre-seeded snake was never sent to the client");
+    assert_eq!(sessions.playing_count(), 1);
+}
@@ -0,0 +1,165 @@
+use futures_util::{SinkExt, StreamExt};
+use rust_slither::protocol::client::{is_pre_init_frame, start_login_frame};
+use rust_slither::server::{spawn_listeners, spawn_server};
+use rust_slither::{GameConfig, ListenerConfig};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio_rustls::rustls;
+use tokio_rustls::TlsConnector;
+use tokio_tungstenite::{client_async, connect_async, tungstenite::Message};
+
+#[tokio::test]
+async fn spawned_server_reports_port_and_shuts_down() {
+    let server = spawn_server(0, GameConfig::default()).await.unwrap();
+    let port = server.local_addr().port();
+    assert_ne!(port, 0);
+
+    let url = format!("ws://127.0.0.1:{}", port);
+    let (mut ws, _) = connect_async(&url).await.unwrap();
+    ws.send(Message::Binary(start_login_frame())).await.unwrap();
+
+    let got_pre_init = tokio::time::timeout(Duration::from_secs(5), async {
+        while let Some(Ok(msg)) = ws.next().await {
+            if let Message::Binary(data) = msg {
+                if is_pre_init_frame(&data, true) {
+                    return true;
+                }
+            }
+        }
+        false
+    })
+    .await
+    .unwrap_or(false);
+    assert!(got_pre_init, "client never received a PreInit packet");
+    assert_eq!(server.stats().connections, 1);
+
+    tokio::time::timeout(Duration::from_secs(5), server.shutdown())
+        .await
+        .expect("shutdown did not finish");
+
+    let closed = tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            match ws.next().await {
+                None | Some(Err(_)) | Some(Ok(Message::Close(_))) => return true,
+                Some(Ok(_)) => continue,
+            }
+        }
+    })
+    .await
+    .unwrap_or(false);
+    assert!(closed, "client connection stayed open after shutdown");
+    assert!(connect_async(&url).await.is_err());
+}
+
+async fn wait_for_pre_init<S>(ws: &mut tokio_tungstenite::WebSocketStream<S>) -> bool
+where
+    S: tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin,
+{
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while let Some(Ok(msg)) = ws.next().await {
+            if let Message::Binary(data) = msg {
+                if is_pre_init_frame(&data, true) {
+                    return true;
+                }
+            }
+        }
+        false
+    })
+    .await
+    .unwrap_or(false)
+}
+
+#[tokio::test]
+async fn listeners_share_one_game() {
+    let listeners = [ListenerConfig::plain(0), ListenerConfig::plain(0)];
+    let server = spawn_listeners(&listeners, GameConfig::default()).await.unwrap();
+    let addrs = server.local_addrs().to_vec();
+    assert_eq!(addrs.len(), 2);
+    assert_ne!(addrs[0].port(), addrs[1].port());
+
+    let mut clients = Vec::new();
+    for addr in &addrs {
+        let url = format!("ws://127.0.0.1:{}", addr.port());
+        let (mut ws, _) = connect_async(&url).await.unwrap();
+        ws.send(Message::Binary(start_login_frame())).await.unwrap();
+        assert!(wait_for_pre_init(&mut ws).await, "no PreInit from {}", addr);
+        clients.push(ws);
+    }
+    assert_eq!(server.stats().connections, 2);
+
+    tokio::time::timeout(Duration::from_secs(5), server.shutdown())
+        .await
+        .expect("shutdown did not finish");
+    for addr in &addrs {
+        assert!(connect_async(format!("ws://127.0.0.1:{}", addr.port())).await.is_err());
+    }
+}
+
+fn write_self_signed_pair() -> (rust_slither::TlsConfig, rustls::pki_types::CertificateDer<'static>) {
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let dir = std::env::temp_dir().join(format!("slither_wss_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let config = rust_slither::TlsConfig {
+        cert_path: dir.join("cert.pem"),
+        key_path: dir.join("key.pem"),
+    };
+    std::fs::write(&config.cert_path, cert.cert.pem()).unwrap();
+    std::fs::write(&config.key_path, cert.key_pair.serialize_pem()).unwrap();
+    (config, cert.cert.der().clone())
+}
+
+#[tokio::test]
+async fn tls_and_plain_listeners_share_one_game() {
+    let (tls_config, cert) = write_self_signed_pair();
+    let listeners = [
+        ListenerConfig::plain(0),
+        ListenerConfig {
+            port: 0,
+            tls: Some(tls_config),
+        },
+    ];
+    let server = spawn_listeners(&listeners, GameConfig::default()).await.unwrap();
+    let addrs = server.local_addrs().to_vec();
+
+    let (mut plain, _) = connect_async(format!("ws://127.0.0.1:{}", addrs[0].port())).await.unwrap();
+    plain.send(Message::Binary(start_login_frame())).await.unwrap();
+    assert!(wait_for_pre_init(&mut plain).await, "no PreInit over ws://");
+
+    let mut roots = rustls::RootCertStore::empty();
+    roots.add(cert).unwrap();
+    let client_config = rustls::ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    let tcp = TcpStream::connect(("127.0.0.1", addrs[1].port())).await.unwrap();
+    let tls = TlsConnector::from(Arc::new(client_config))
+        .connect(rustls::pki_types::ServerName::try_from("localhost").unwrap(), tcp)
+        .await
+        .unwrap();
+    let (mut secure, _) = client_async("wss://localhost/", tls).await.unwrap();
+    secure.send(Message::Binary(start_login_frame())).await.unwrap();
+    assert!(wait_for_pre_init(&mut secure).await, "no PreInit over wss://");
+    assert_eq!(server.stats().connections, 2);
+
+    let plain_tcp = TcpStream::connect(("127.0.0.1", addrs[1].port())).await.unwrap();
+    assert!(client_async("ws://localhost/", plain_tcp).await.is_err());
+
+    tokio::time::timeout(Duration::from_secs(5), server.shutdown())
+        .await
+        .expect("shutdown did not finish");
+}
+
+#[tokio::test]
+async fn tls_listener_with_unreadable_certificate_fails_to_start() {
+    let listeners = [ListenerConfig {
+        port: 0,
+        tls: Some(rust_slither::TlsConfig {
+            cert_path: "missing-cert.pem".into(),
+            key_path: "missing-key.pem".into(),
+        }),
+    }];
+    let err = spawn_listeners(&listeners, GameConfig::default()).await.err().unwrap();
+    assert!(err.to_string().contains("missing-cert.pem"), "{}", err);
+}